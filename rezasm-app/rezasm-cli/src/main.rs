@@ -9,25 +9,54 @@ extern crate scanner_rust;
 
 use std::process;
 
-use rezasm_core::instructions::implementation::register_instructions;
 use rezasm_core::util::error::handle_error;
 
 use crate::util::application::Application;
 use crate::util::cli;
-use crate::util::cli::Arguments;
+use crate::util::cli::{Arguments, Command};
 use crate::util::cli_arguments::handle_arguments;
+use crate::util::format::format_files;
+use crate::util::inspect_core::inspect_core;
+use crate::util::instructions::list_instructions;
+use crate::util::lint::lint_files;
 
 fn main() {
-    register_instructions();
     let args: Arguments = cli::get_args();
-    let application: Application = match handle_arguments(args) {
+    if let Some(command) = args.get_command() {
+        let result = match command {
+            Command::InspectCore { core_file, json } => inspect_core(core_file, *json),
+            Command::Instructions { name, format } => list_instructions(name, format),
+            Command::Fmt {
+                files,
+                check,
+                indent,
+            } => format_files(files, *check, *indent),
+            Command::Lint {
+                files,
+                format,
+                disable,
+            } => lint_files(files, format, disable),
+        };
+        match result {
+            Ok(_) => process::exit(0),
+            Err(error) => handle_error(error),
+        }
+    }
+
+    let mut application: Application = match handle_arguments(args) {
         Ok(app) => app,
         Err(error) => handle_error(error),
     };
 
     let exit_code = match application.run_until_completion() {
         Ok(exit_code) => exit_code,
-        Err(error) => handle_error(error.into()),
+        Err(error) => {
+            println!("{}", error);
+            if let Some(backtrace) = application.get_backtrace() {
+                println!("{}", backtrace);
+            }
+            process::exit(1);
+        }
     };
 
     process::exit(exit_code as i32);
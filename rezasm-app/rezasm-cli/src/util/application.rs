@@ -1,4 +1,6 @@
-use rezasm_core::parser::lexer;
+use rezasm_core::parser::linker::Linker;
+use rezasm_core::simulation::call_stack::Backtrace;
+use rezasm_core::simulation::core_dump::CoreDump;
 use rezasm_core::simulation::registry;
 use rezasm_core::simulation::simulator::Simulator;
 use rezasm_core::util::error::SimulatorError;
@@ -6,35 +8,64 @@ use rezasm_core::util::io::RezasmFileReader;
 
 pub struct Application {
     simulator: Simulator,
-    code_file: RezasmFileReader,
+    code_file: Option<RezasmFileReader>,
+    linker: Linker,
+    save_state: Option<String>,
+    save_history: bool,
+    stop_after: Option<usize>,
+    core_dump: Option<String>,
+    backtrace: Option<Backtrace>,
 }
 
 impl Application {
-    pub fn new(simulator: Simulator, code_file: RezasmFileReader) -> Application {
+    pub fn new(
+        simulator: Simulator,
+        code_file: Option<RezasmFileReader>,
+        linker: Linker,
+        save_state: Option<String>,
+        save_history: bool,
+        stop_after: Option<usize>,
+        core_dump: Option<String>,
+    ) -> Application {
         Application {
             simulator,
             code_file,
+            linker,
+            save_state,
+            save_history,
+            stop_after,
+            core_dump,
+            backtrace: None,
         }
     }
 
-    pub fn run_until_completion(mut self) -> Result<i64, SimulatorError> {
-        let lines = self.code_file.lines().map_err(SimulatorError::from)?;
-        for line in lines {
-            match lexer::parse_line(&line, self.simulator.get_word_size()) {
-                Some(line_result) => match line_result {
-                    Ok(line) => self.simulator.add_line(
-                        line,
-                        self.code_file.get_path().to_string_lossy().to_string(),
-                    )?,
-                    Err(error) => return Err(error.into()),
-                },
-                None => {}
-            };
+    /// Gets the functions which were running when the program failed, if it failed while running.
+    pub fn get_backtrace(&self) -> &Option<Backtrace> {
+        &self.backtrace
+    }
+
+    pub fn run_until_completion(&mut self) -> Result<i64, SimulatorError> {
+        if let Some(code_file) = &self.code_file {
+            let code = code_file.lines().map_err(SimulatorError::from)?;
+            let files = self.linker.link_code(
+                code_file.get_path(),
+                &code.join("\n"),
+                self.simulator.get_word_size(),
+                self.simulator.get_instruction_set(),
+            )?;
+            self.simulator.add_files(files)?;
         }
 
-        while !self.simulator.is_done() {
-            self.simulator.run_line_from_pc()?;
+        let result = self.run();
+        if result.is_err() {
+            self.backtrace = Some(self.simulator.backtrace());
+        }
+        if let (Err(error), Some(path)) = (&result, &self.core_dump) {
+            CoreDump::capture(&self.simulator, error).save(path)?;
         }
+        let saved = self.save_state();
+        result?;
+        saved?;
 
         let r = self
             .simulator
@@ -43,4 +74,27 @@ impl Application {
             .unwrap();
         Ok(r.get_data().int_value())
     }
+
+    fn run(&mut self) -> Result<(), SimulatorError> {
+        let max_steps = self.stop_after.unwrap_or(usize::MAX);
+        if !self.simulator.explains() {
+            return self.simulator.run(max_steps).map(|_| ());
+        }
+        // run one line at a time so that each explanation is printed as its line runs
+        let mut steps = 0usize;
+        while steps < max_steps && self.simulator.run(1)? == 1 {
+            if let Some(explanation) = self.simulator.get_explanation() {
+                eprintln!("{}", explanation);
+            }
+            steps += 1;
+        }
+        Ok(())
+    }
+
+    fn save_state(&self) -> Result<(), SimulatorError> {
+        match &self.save_state {
+            Some(path) => self.simulator.snapshot(self.save_history).save(path),
+            None => Ok(()),
+        }
+    }
 }
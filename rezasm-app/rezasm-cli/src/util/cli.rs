@@ -1,21 +1,73 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 /// REzASM: An assembly like programming language for use in education
 #[derive(Parser, Debug)]
-#[command(version, about)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
 pub struct Arguments {
-    /// The file of code to open
-    #[arg()]
-    code_file: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The file of code to open; must be omitted when loading a saved state, which holds the code
+    #[arg(required_unless_present = "load_state")]
+    code_file: Option<String>,
 
     /// The number of words to allocate space for on the stack and heap each; must be larger than 0
     #[arg(short, long, default_value_t = 20_000)]
     memory_size: usize,
 
-    /// The size in bytes of a word (4 or 8)
+    /// The size in bytes of a word (2, 4 or 8)
     #[arg(short, long, default_value_t = 4)]
     word_size: usize,
 
+    /// How memory is stored: `dense` allocates all of it up front, `sparse` allocates pages as
+    /// they are used
+    #[arg(long, default_value = "dense")]
+    memory_backend: String,
+
+    /// The byte order of words in memory (big or little)
+    #[arg(long, default_value = "big")]
+    endianness: String,
+
+    /// Fail with an error when integer arithmetic overflows a word instead of wrapping around
+    #[arg(long)]
+    trap_overflow: bool,
+
+    /// Fail with an error when a function returns without restoring `$s0`-`$s9`, `$fs0`-`$fs9`
+    /// and `$sp` to their values at its call
+    #[arg(long)]
+    check_calling_convention: bool,
+
+    /// How floating point results are rounded (nearest-even, nearest-away, toward-zero, up or down)
+    #[arg(long, default_value = "nearest-even")]
+    rounding_mode: String,
+
+    /// A JSON file restricting which instructions the program may use, with the optional fields
+    /// `allow`, `forbid` and `limits`
+    #[arg(long)]
+    policy: Option<String>,
+
+    /// Instructions or categories the program may use, separated by commas; all others are
+    /// forbidden
+    #[arg(long, value_delimiter = ',')]
+    allow: Vec<String>,
+
+    /// Instructions or categories the program may not use, separated by commas
+    #[arg(long, value_delimiter = ',')]
+    forbid: Vec<String>,
+
+    /// The most times an instruction or category may be used, as `name=count`; may be repeated
+    #[arg(long)]
+    limit: Vec<String>,
+
+    /// A directory to look for imported files in when they are not found beside the file importing
+    /// them; may be repeated
+    #[arg(long)]
+    import_path: Vec<String>,
+
+    /// Refuse imports of files outside of this directory
+    #[arg(long)]
+    import_root: Option<String>,
+
     /// A file to receive standard input from
     #[arg(short, long)]
     input_file: Option<String>,
@@ -23,6 +75,89 @@ pub struct Arguments {
     /// A file to print standard output to
     #[arg(short, long)]
     output_file: Option<String>,
+
+    /// A file to save the simulator state to once the program stops
+    #[arg(long)]
+    save_state: Option<String>,
+
+    /// A file of saved simulator state to resume from instead of starting the program anew
+    #[arg(long, conflicts_with = "code_file")]
+    load_state: Option<String>,
+
+    /// Include the undo history when saving the simulator state
+    #[arg(long, requires = "save_state")]
+    save_history: bool,
+
+    /// The number of instructions to run before stopping; runs to completion if omitted
+    #[arg(long)]
+    stop_after: Option<usize>,
+
+    /// A file to write a core dump to if the program faults
+    #[arg(long)]
+    core_dump: Option<String>,
+
+    /// The number of most recently run instructions to include in a core dump
+    #[arg(long, default_value_t = 32, requires = "core_dump")]
+    trace_length: usize,
+
+    /// Explain what each instruction did in words as it runs, on standard error
+    #[arg(long)]
+    explain: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Examine a core dump written with `--core-dump`
+    InspectCore {
+        /// The core dump file to examine
+        #[arg()]
+        core_file: String,
+
+        /// Print the machine-readable JSON instead of the report
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List the available instructions, or write a reference of them
+    Instructions {
+        /// Only show the instructions with this name
+        #[arg()]
+        name: Option<String>,
+
+        /// The format to write the instructions in
+        #[arg(long, default_value = "text", value_parser = ["text", "markdown", "html", "json"])]
+        format: String,
+    },
+
+    /// Rewrite code files in the canonical style
+    Fmt {
+        /// The code files to format
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Fail if any file is not already formatted, without changing it
+        #[arg(long)]
+        check: bool,
+
+        /// The number of spaces to indent instructions by
+        #[arg(long, default_value_t = 4)]
+        indent: usize,
+    },
+
+    /// Check code files for likely mistakes without running them, failing if any are found
+    Lint {
+        /// The code files to check
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// The format to write the findings in
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+
+        /// Rules not to check, e.g. `unused-label,uninitialized-read`
+        #[arg(long, value_delimiter = ',')]
+        disable: Vec<String>,
+    },
 }
 
 pub fn get_args() -> Arguments {
@@ -30,6 +165,10 @@ pub fn get_args() -> Arguments {
 }
 
 impl Arguments {
+    pub fn get_command(&self) -> &Option<Command> {
+        &self.command
+    }
+
     pub fn get_memory_size(&self) -> usize {
         self.memory_size
     }
@@ -38,7 +177,51 @@ impl Arguments {
         self.word_size
     }
 
-    pub fn get_code_file(&self) -> &String {
+    pub fn get_memory_backend(&self) -> &String {
+        &self.memory_backend
+    }
+
+    pub fn get_endianness(&self) -> &String {
+        &self.endianness
+    }
+
+    pub fn get_trap_overflow(&self) -> bool {
+        self.trap_overflow
+    }
+
+    pub fn get_check_calling_convention(&self) -> bool {
+        self.check_calling_convention
+    }
+
+    pub fn get_rounding_mode(&self) -> &String {
+        &self.rounding_mode
+    }
+
+    pub fn get_policy(&self) -> &Option<String> {
+        &self.policy
+    }
+
+    pub fn get_allow(&self) -> &Vec<String> {
+        &self.allow
+    }
+
+    pub fn get_forbid(&self) -> &Vec<String> {
+        &self.forbid
+    }
+
+    pub fn get_limit(&self) -> &Vec<String> {
+        &self.limit
+    }
+
+    pub fn get_import_path(&self) -> &Vec<String> {
+        &self.import_path
+    }
+
+    pub fn get_import_root(&self) -> &Option<String> {
+        &self.import_root
+    }
+
+    pub fn get_code_file(&self) -> &Option<String> {
         &self.code_file
     }
 
@@ -49,4 +232,32 @@ impl Arguments {
     pub fn get_output_file(&self) -> &Option<String> {
         &self.output_file
     }
+
+    pub fn get_save_state(&self) -> &Option<String> {
+        &self.save_state
+    }
+
+    pub fn get_load_state(&self) -> &Option<String> {
+        &self.load_state
+    }
+
+    pub fn get_save_history(&self) -> bool {
+        self.save_history
+    }
+
+    pub fn get_stop_after(&self) -> Option<usize> {
+        self.stop_after
+    }
+
+    pub fn get_core_dump(&self) -> &Option<String> {
+        &self.core_dump
+    }
+
+    pub fn get_trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    pub fn get_explain(&self) -> bool {
+        self.explain
+    }
 }
@@ -1,9 +1,16 @@
 use crate::util::application::Application;
 use crate::util::cli::Arguments;
 use crate::util::cli_io::{InputSource, OutputSink};
+use rezasm_core::instructions::instruction_policy::InstructionPolicy;
+use rezasm_core::parser::linker::Linker;
+use rezasm_core::simulation::memory::Memory;
+use rezasm_core::simulation::memory_backend::MemoryBackendKind;
 use rezasm_core::simulation::reader_cell::ReaderCell;
 use rezasm_core::simulation::simulator::Simulator;
+use rezasm_core::simulation::snapshot::SimulatorSnapshot;
+use rezasm_core::util::endianness::Endianness;
 use rezasm_core::util::error::{EzasmError, IoError, SimulatorError};
+use rezasm_core::util::floating_point::RoundingMode;
 use rezasm_core::util::io::{RezasmFileReader, RezasmFileWriter};
 use rezasm_core::util::word_size::WordSize;
 use std::fs::File;
@@ -32,22 +39,43 @@ fn get_file_from_path(path_string: &String) -> Result<File, EzasmError> {
 }
 
 pub fn handle_arguments(arguments: Arguments) -> Result<Application, EzasmError> {
-    let word_size = match &arguments.get_word_size() {
-        4 => WordSize::Four,
-        8 => WordSize::Eight,
-        _ => {
-            return Err(SimulatorError::InvalidWordSizeError(
-                arguments.get_word_size(),
-            ))?
-        }
-    };
+    let word_size = WordSize::from(arguments.get_word_size())?;
 
     let memory_size = match arguments.get_memory_size() {
         0 => return Err(SimulatorError::InvalidMemorySizeError(0))?,
         x => x,
     };
 
-    let code_file = RezasmFileReader::new(arguments.get_code_file())?;
+    let memory_backend = MemoryBackendKind::from(arguments.get_memory_backend())?;
+    let endianness = Endianness::from(arguments.get_endianness())?;
+    let rounding_mode = RoundingMode::from(arguments.get_rounding_mode())?;
+
+    let mut policy = match arguments.get_policy() {
+        Some(policy_file_string) => InstructionPolicy::load(policy_file_string)?,
+        None => InstructionPolicy::new(),
+    };
+    for name in arguments.get_allow() {
+        policy.allow(name);
+    }
+    for name in arguments.get_forbid() {
+        policy.forbid(name);
+    }
+    for limit in arguments.get_limit() {
+        policy.parse_limit(limit)?;
+    }
+
+    let mut linker = Linker::new();
+    for path in arguments.get_import_path() {
+        linker.add_search_path(path);
+    }
+    if let Some(root) = arguments.get_import_root() {
+        linker.set_root(root);
+    }
+
+    let code_file = match arguments.get_code_file() {
+        Some(code_file_string) => Some(RezasmFileReader::new(code_file_string)?),
+        None => None,
+    };
 
     let input_file: InputSource = match arguments.get_input_file() {
         Some(input_file_string) => InputSource::new_file(RezasmFileReader::new(input_file_string)?),
@@ -61,12 +89,44 @@ pub fn handle_arguments(arguments: Arguments) -> Result<Application, EzasmError>
         None => OutputSink::new_console(),
     };
 
-    let simulator: Simulator = Simulator::new_custom(
-        &word_size,
-        memory_size,
+    let mut simulator: Simulator = Simulator::new_with_memory(
+        Memory::new_custom(&word_size, memory_size, memory_backend, endianness),
         ReaderCell::new(input_file),
         Box::new(output_file),
     );
 
-    Ok(Application::new(simulator, code_file))
+    if arguments.get_core_dump().is_some() {
+        simulator.set_trace_capacity(arguments.get_trace_length());
+    }
+
+    // a code file can't be given alongside a saved state, which already holds the program
+    let mut restored_history = false;
+    if let Some(state_file_string) = arguments.get_load_state() {
+        let snapshot = SimulatorSnapshot::load(state_file_string)?;
+        restored_history = snapshot
+            .history
+            .as_ref()
+            .is_some_and(|history| !history.is_empty());
+        simulator.restore(snapshot)?;
+    }
+
+    // nothing is ever undone from the command line, so only keep history when it will be saved;
+    // history which was loaded is kept and saved again rather than silently dropped
+    let save_history = arguments.get_save_history() || restored_history;
+    simulator.set_can_undo(save_history);
+    simulator.set_trap_overflow(arguments.get_trap_overflow());
+    simulator.set_check_calling_convention(arguments.get_check_calling_convention());
+    simulator.set_rounding_mode(rounding_mode);
+    simulator.set_explain(arguments.get_explain());
+    simulator.get_instruction_set_mut().set_policy(policy)?;
+
+    Ok(Application::new(
+        simulator,
+        code_file,
+        linker,
+        arguments.get_save_state().clone(),
+        save_history,
+        arguments.get_stop_after(),
+        arguments.get_core_dump().clone(),
+    ))
 }
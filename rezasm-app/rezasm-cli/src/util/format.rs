@@ -0,0 +1,27 @@
+use std::fs;
+
+use rezasm_core::parser::formatter::Formatter;
+use rezasm_core::util::error::{EzasmError, IoError};
+
+/// Formats code files in place, or with `check`, fails if any of them would change.
+pub fn format_files(files: &Vec<String>, check: bool, indent: usize) -> Result<(), EzasmError> {
+    let formatter = Formatter::new_custom(indent);
+    let mut unformatted = Vec::new();
+    for file in files {
+        let code = fs::read_to_string(file)
+            .map_err(|_| IoError::CouldNotOpenFileError(file.to_string()))?;
+        let formatted = formatter.format(&code);
+        if formatted == code {
+            continue;
+        }
+        if check {
+            unformatted.push(file.to_string());
+        } else {
+            fs::write(file, formatted).map_err(IoError::StdIoError)?;
+        }
+    }
+    if !unformatted.is_empty() {
+        return Err(IoError::UnformattedFilesError(unformatted.join(", ")).into());
+    }
+    Ok(())
+}
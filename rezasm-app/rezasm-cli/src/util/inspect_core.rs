@@ -0,0 +1,15 @@
+use rezasm_core::simulation::core_dump::CoreDump;
+use rezasm_core::util::error::{EzasmError, SimulatorError};
+
+/// Prints a core dump written with `--core-dump`, either as a report or as JSON.
+pub fn inspect_core(core_file: &String, json: bool) -> Result<(), EzasmError> {
+    let dump = CoreDump::load(core_file)?;
+    if json {
+        let json = serde_json::to_string_pretty(&dump)
+            .map_err(|e| SimulatorError::CoreDumpError(e.to_string()))?;
+        println!("{}", json);
+    } else {
+        println!("{}", dump);
+    }
+    Ok(())
+}
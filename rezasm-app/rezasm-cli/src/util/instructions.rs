@@ -0,0 +1,27 @@
+use rezasm_core::instructions::instruction_set::InstructionSet;
+use rezasm_core::instructions::reference;
+use rezasm_core::util::error::{EzasmError, ParserError};
+
+/// Prints the standard instructions, or those with one name, in the given format.
+pub fn list_instructions(name: &Option<String>, format: &str) -> Result<(), EzasmError> {
+    let instructions = InstructionSet::standard();
+    let references = match name {
+        Some(name) => {
+            let references = instructions.find_references(name);
+            if references.is_empty() {
+                return Err(ParserError::InvalidInstructionError(name.to_string()).into());
+            }
+            references
+        }
+        None => instructions.get_references(),
+    };
+    let output = match format {
+        "markdown" => reference::to_markdown(&references),
+        "html" => reference::to_html(&references),
+        "json" => serde_json::to_string_pretty(&references)
+            .expect("instruction references can always be serialized"),
+        _ => reference::to_text(&references),
+    };
+    println!("{}", output.trim_end());
+    Ok(())
+}
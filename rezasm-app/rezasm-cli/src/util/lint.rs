@@ -0,0 +1,60 @@
+use std::fs;
+use std::process;
+
+use rezasm_core::parser::linter::{LintRule, Linter};
+use rezasm_core::util::error::{EzasmError, IoError};
+use serde_json::{json, Value};
+
+/// Prints the mistakes found in code files, exiting with a status of 1 if there are any.
+pub fn lint_files(
+    files: &Vec<String>,
+    format: &str,
+    disable: &Vec<String>,
+) -> Result<(), EzasmError> {
+    let mut linter = Linter::new();
+    for rule in disable {
+        linter.disable(LintRule::from(rule)?);
+    }
+
+    let mut findings = Vec::new();
+    for file in files {
+        let code = fs::read_to_string(file)
+            .map_err(|_| IoError::CouldNotOpenFileError(file.to_string()))?;
+        findings.extend(
+            linter
+                .lint(&code)
+                .into_iter()
+                .map(|finding| (file, finding)),
+        );
+    }
+
+    if format == "json" {
+        let findings: Vec<Value> = findings
+            .iter()
+            .map(|(file, finding)| {
+                json!({
+                    "file": file,
+                    "line": finding.line_number,
+                    "rule": finding.rule,
+                    "message": finding.message,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&findings).expect("findings can always be serialized")
+        );
+    } else {
+        for (file, finding) in &findings {
+            println!(
+                "{}:{}: {}: {}",
+                file, finding.line_number, finding.rule, finding.message
+            );
+        }
+    }
+
+    if !findings.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
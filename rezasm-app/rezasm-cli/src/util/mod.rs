@@ -2,3 +2,7 @@ pub mod application;
 pub mod cli;
 pub mod cli_arguments;
 pub mod cli_io;
+pub mod format;
+pub mod inspect_core;
+pub mod instructions;
+pub mod lint;
@@ -0,0 +1,91 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+use rezasm_core::simulation::reader::Reader;
+use rezasm_core::simulation::writer::Writer;
+use rezasm_core::util::as_any::AsAny;
+
+/// Input for the program, filled from the input file and from what is typed into the debug
+/// console.
+#[derive(Debug)]
+pub struct ConsoleReader {
+    buffer: VecDeque<u8>,
+}
+
+impl ConsoleReader {
+    pub fn new() -> ConsoleReader {
+        ConsoleReader {
+            buffer: VecDeque::new(),
+        }
+    }
+}
+
+impl Reader for ConsoleReader {}
+
+impl Read for ConsoleReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.buffer.read(buf)
+    }
+}
+
+impl Write for ConsoleReader {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.buffer.flush()
+    }
+}
+
+impl AsAny for ConsoleReader {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Collects what the program prints until it is sent to the editor's debug console.
+#[derive(Debug)]
+pub struct ConsoleWriter {
+    buffer: Vec<u8>,
+}
+
+impl ConsoleWriter {
+    pub fn new() -> ConsoleWriter {
+        ConsoleWriter { buffer: Vec::new() }
+    }
+
+    /// Takes everything printed since the last call.
+    pub fn take_output(&mut self) -> String {
+        let output = String::from_utf8_lossy(&self.buffer).to_string();
+        self.buffer.clear();
+        output
+    }
+}
+
+impl Writer for ConsoleWriter {}
+
+impl Write for ConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsAny for ConsoleWriter {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
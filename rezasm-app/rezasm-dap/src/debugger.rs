@@ -0,0 +1,481 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+
+use rezasm_core::parser::linker::Linker;
+use rezasm_core::simulation::reader_cell::ReaderCell;
+use rezasm_core::simulation::registry;
+use rezasm_core::simulation::simulator::Simulator;
+use serde::Deserialize;
+
+use crate::console::{ConsoleReader, ConsoleWriter};
+
+/// The file identifier of the program being debugged, which is loaded before the files it imports.
+const MAIN_FILE: i64 = 0;
+
+/// What the editor asks for when it starts debugging a program.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchArguments {
+    /// The path of the code file to debug.
+    pub program: String,
+    /// The path of a file to read the program's input from before anything typed into the debug
+    /// console.
+    pub input_file: Option<String>,
+    #[serde(default)]
+    pub stop_on_entry: bool,
+    /// Programs do not take arguments, so any given are refused rather than ignored.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// How far to run the program before stopping again.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Resume {
+    Continue,
+    StepIn,
+    /// Runs a line, along with the whole of any function it calls.
+    StepOver,
+    /// Runs until the current function returns.
+    StepOut,
+}
+
+/// Why the program stopped running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    Entry,
+    Step,
+    Breakpoint,
+    Pause,
+    AwaitingInput,
+    Exited(i64),
+    Error(String),
+}
+
+/// A function which is running, along with the line it is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    pub name: String,
+    /// The path of the code file the function is in.
+    pub path: String,
+    pub line: i64,
+}
+
+/// Runs a program in a simulator on behalf of the editor, mapping the lines of its code files to
+/// the lines the simulator runs.
+#[derive(Debug)]
+pub struct Debugger {
+    simulator: Simulator,
+    // the path of each code file, by file identifier
+    paths: Vec<String>,
+    // the (1-based) line of its code file each line of each file came from, by file identifier
+    source_lines: Vec<Vec<i64>>,
+    // the (file identifier, line) of each line of the program to stop before running
+    breakpoints: HashSet<(i64, i64)>,
+    running: Option<(Resume, usize)>,
+}
+
+impl Debugger {
+    /// Loads a program, ready to run from its first line.
+    pub fn launch(arguments: &LaunchArguments) -> Result<Debugger, String> {
+        if !arguments.args.is_empty() {
+            return Err(
+                "programs do not take arguments; give them input with `inputFile` or the debug \
+                 console instead"
+                    .to_string(),
+            );
+        }
+        let code = fs::read_to_string(&arguments.program)
+            .map_err(|error| format!("could not read `{}`: {}", arguments.program, error))?;
+        let mut debugger = Debugger::new(&arguments.program, &code)?;
+        if let Some(input_file) = &arguments.input_file {
+            let input = fs::read(input_file)
+                .map_err(|error| format!("could not read `{}`: {}", input_file, error))?;
+            debugger.provide_input(&input);
+        }
+        Ok(debugger)
+    }
+
+    pub fn new(path: &str, code: &str) -> Result<Debugger, String> {
+        let mut simulator = Simulator::new_custom_reader_writer(
+            ReaderCell::new(ConsoleReader::new()),
+            Box::new(ConsoleWriter::new()),
+        );
+        let files = Linker::new()
+            .link_code(
+                path,
+                code,
+                simulator.get_word_size(),
+                simulator.get_instruction_set(),
+            )
+            .map_err(|error| error.to_string())?;
+        let mut paths = Vec::new();
+        let mut source_lines = Vec::new();
+        for file in files {
+            paths.push(file.name.to_string());
+            source_lines.push(Vec::new());
+            for (line_number, line) in file.lines {
+                source_lines.last_mut().unwrap().push(line_number as i64);
+                simulator
+                    .add_numbered_line(line, line_number, file.name.to_string())
+                    .map_err(|error| error.to_string())?;
+            }
+        }
+        Ok(Debugger {
+            simulator,
+            paths,
+            source_lines,
+            breakpoints: HashSet::new(),
+            running: None,
+        })
+    }
+
+    /// Gets the path of the program being debugged.
+    pub fn get_path(&self) -> &String {
+        &self.paths[MAIN_FILE as usize]
+    }
+
+    /// Replaces the breakpoints of one of the code files of the program, returning the line each
+    /// one was placed on, which is the first line of code at or after the line asked for, or `None`
+    /// if there is no code there. The breakpoints of the other files are kept.
+    pub fn set_breakpoints(
+        &mut self,
+        path: &str,
+        lines: &[i64],
+    ) -> Result<Vec<Option<i64>>, String> {
+        let fid = self
+            .find_file(path)
+            .ok_or_else(|| format!("`{}` is not part of the program", path))?;
+        self.breakpoints.retain(|(other, _)| *other != fid);
+        let source_lines = &self.source_lines[fid as usize];
+        Ok(lines
+            .iter()
+            .map(|line| {
+                let index = source_lines
+                    .iter()
+                    .position(|source_line| source_line >= line)?;
+                self.breakpoints.insert((fid, index as i64));
+                Some(source_lines[index])
+            })
+            .collect())
+    }
+
+    /// Starts running the program, which continues through calls to `advance`.
+    pub fn resume(&mut self, resume: Resume) {
+        self.running = Some((resume, self.simulator.get_call_stack().depth()));
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    pub fn pause(&mut self) -> Stop {
+        self.running = None;
+        Stop::Pause
+    }
+
+    /// Runs up to `max_steps` lines of the program, returning why it stopped, or `None` if it is
+    /// still running.
+    pub fn advance(&mut self, max_steps: usize) -> Option<Stop> {
+        let (resume, depth) = self.running?;
+        let mut steps = 0usize;
+        while steps < max_steps {
+            if self.simulator.is_done() {
+                return self.stop(Stop::Exited(self.exit_code()));
+            }
+            let budget = max_steps - steps;
+            let breakpoints = &self.breakpoints;
+            let at_breakpoint = |simulator: &Simulator| is_at_breakpoint(breakpoints, simulator);
+            // stepping over or out goes through the simulator, which records the lines it runs as
+            // one step, so that stepping back undoes all of them
+            let result = match resume {
+                Resume::Continue | Resume::StepIn => self.simulator.run(1),
+                Resume::StepOver if self.simulator.get_call_stack().depth() <= depth => {
+                    self.simulator.step_over_until(budget, at_breakpoint)
+                }
+                Resume::StepOver | Resume::StepOut => {
+                    self.simulator.step_out_until(budget, at_breakpoint)
+                }
+            };
+            match result {
+                Err(error) => return self.stop(Stop::Error(error.to_string())),
+                Ok(0) => return self.stop(Stop::AwaitingInput),
+                Ok(run) => steps += run,
+            }
+            if self.simulator.is_done() {
+                return self.stop(Stop::Exited(self.exit_code()));
+            }
+            let current_depth = self.simulator.get_call_stack().depth();
+            let stepped = match resume {
+                Resume::Continue => false,
+                Resume::StepIn => true,
+                Resume::StepOver => current_depth <= depth,
+                Resume::StepOut => current_depth < depth,
+            };
+            if stepped {
+                return self.stop(Stop::Step);
+            }
+            if is_at_breakpoint(&self.breakpoints, &self.simulator) {
+                return self.stop(Stop::Breakpoint);
+            }
+        }
+        None
+    }
+
+    /// Undoes the last line which was run.
+    pub fn step_back(&mut self) -> Stop {
+        match self.simulator.undo_last_transformation() {
+            Ok(_) => Stop::Step,
+            Err(error) => Stop::Error(error.to_string()),
+        }
+    }
+
+    /// Undoes lines until reaching a breakpoint or the start of the program.
+    pub fn reverse_continue(&mut self) -> Stop {
+        loop {
+            match self.simulator.undo_last_transformation() {
+                Ok(false) => return Stop::Entry,
+                Ok(true) if is_at_breakpoint(&self.breakpoints, &self.simulator) => {
+                    return Stop::Breakpoint
+                }
+                Ok(true) => {}
+                Err(error) => return Stop::Error(error.to_string()),
+            }
+        }
+    }
+
+    /// Gets the running functions, from innermost to outermost, each named after the label it was
+    /// called through.
+    pub fn stack_frames(&self) -> Vec<StackFrame> {
+        let registers = self.simulator.get_registers();
+        let mut location = (
+            registers.get_fid().get_data().int_value(),
+            registers.get_pc().get_data().int_value(),
+        );
+        let mut frames = Vec::new();
+        for frame in self.simulator.get_call_stack().frames().iter().rev() {
+            frames.push(self.stack_frame(&frame.label, location));
+            location = frame.call_site;
+        }
+        frames.push(self.stack_frame("main", location));
+        frames
+    }
+
+    /// Gets the name and value of every register.
+    pub fn registers(&self, hex: bool) -> Vec<(String, i64, String)> {
+        let registers = self.simulator.get_registers();
+        (0..registry::ALL_REGISTERS.len())
+            .filter_map(|number| {
+                let value = registers
+                    .get_register_by_number(number)
+                    .ok()?
+                    .get_data()
+                    .int_value();
+                let display = if hex {
+                    format!("{:#x}", value)
+                } else {
+                    value.to_string()
+                };
+                Some((registry::get_register_display_name(number), value, display))
+            })
+            .collect()
+    }
+
+    /// Gets the value of a register by its name, e.g. `$t0`.
+    pub fn evaluate(&self, expression: &str) -> Option<i64> {
+        let name = expression.trim().strip_prefix('$')?;
+        self.simulator
+            .get_registers()
+            .get_register(name)
+            .ok()
+            .map(|register| register.get_data().int_value())
+    }
+
+    /// Reads the bytes of memory which exist within a range, returning the address of the first
+    /// one.
+    pub fn read_memory(&self, address: i64, count: usize) -> (i64, Vec<u8>) {
+        let memory = self.simulator.get_memory();
+        let start = address.max(memory.initial_text_pointer() as i64);
+        let end = address
+            .saturating_add(count as i64)
+            .min(memory.memory_size() as i64);
+        if start >= end {
+            return (address, Vec::new());
+        }
+        let bytes = memory
+            .read_raw(start as usize, (end - start) as usize)
+            .unwrap_or_default();
+        (start, bytes)
+    }
+
+    /// Gives the program input, as if it had been typed.
+    pub fn provide_input(&mut self, input: &[u8]) {
+        // writing to the reader of a simulator appends to the input it has yet to read
+        let _ = self.simulator.get_reader_mut().write_all(input);
+    }
+
+    /// Takes everything the program has printed since the last call.
+    pub fn take_output(&mut self) -> String {
+        self.simulator
+            .get_writer_mut()
+            .as_any_mut()
+            .downcast_mut::<ConsoleWriter>()
+            .map(ConsoleWriter::take_output)
+            .unwrap_or_default()
+    }
+
+    fn stop(&mut self, stop: Stop) -> Option<Stop> {
+        self.running = None;
+        Some(stop)
+    }
+
+    /// Finds the file identifier of a code file of the program, however its path is written.
+    fn find_file(&self, path: &str) -> Option<i64> {
+        let identity = fs::canonicalize(path).ok();
+        self.paths
+            .iter()
+            .position(|other| {
+                other == path || (identity.is_some() && fs::canonicalize(other).ok() == identity)
+            })
+            .map(|fid| fid as i64)
+    }
+
+    fn exit_code(&self) -> i64 {
+        self.simulator
+            .get_registers()
+            .get_register(registry::R0)
+            .map(|register| register.get_data().int_value())
+            .unwrap_or(0)
+    }
+
+    fn stack_frame(&self, name: &str, (fid, line): (i64, i64)) -> StackFrame {
+        StackFrame {
+            name: name.to_string(),
+            path: self
+                .paths
+                .get(fid as usize)
+                .unwrap_or(self.get_path())
+                .to_string(),
+            line: self.source_line(fid, line),
+        }
+    }
+
+    /// Gets the line of its code file a line of the program came from; past the end of a file, this
+    /// is the last line of code.
+    fn source_line(&self, fid: i64, line: i64) -> i64 {
+        let Some(source_lines) = self.source_lines.get(fid as usize) else {
+            return 1;
+        };
+        let index = (line.max(0) as usize).min(source_lines.len().saturating_sub(1));
+        source_lines.get(index).copied().unwrap_or(1)
+    }
+}
+
+/// Whether the line at the program counter of a simulator has a breakpoint.
+fn is_at_breakpoint(breakpoints: &HashSet<(i64, i64)>, simulator: &Simulator) -> bool {
+    let registers = simulator.get_registers();
+    breakpoints.contains(&(
+        registers.get_fid().get_data().int_value(),
+        registers.get_pc().get_data().int_value(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debugger() {
+        let code =
+            "# count to three\nadd $t0 $t0 1\n\nadd $t0 $t0 1\nadd $t0 $t0 1\nmove $r0 $t0\n";
+        let mut debugger = Debugger::new("count.ez", code).unwrap();
+
+        assert_eq!(
+            debugger.set_breakpoints("count.ez", &[1, 4, 9]),
+            Ok(vec![Some(2), Some(4), None])
+        );
+        assert!(debugger.set_breakpoints("other.ez", &[1]).is_err());
+        debugger.resume(Resume::Continue);
+        assert!(debugger.is_running());
+        assert_eq!(debugger.advance(100), Some(Stop::Breakpoint));
+        assert_eq!(debugger.stack_frames()[0].line, 4);
+        assert_eq!(debugger.stack_frames()[0].path, "count.ez");
+        assert_eq!(debugger.evaluate("$t0"), Some(1));
+
+        debugger.resume(Resume::StepIn);
+        assert_eq!(debugger.advance(100), Some(Stop::Step));
+        assert_eq!(debugger.stack_frames()[0].line, 5);
+        assert_eq!(debugger.step_back(), Stop::Step);
+        assert_eq!(debugger.evaluate("$t0"), Some(1));
+        assert_eq!(debugger.reverse_continue(), Stop::Breakpoint);
+        assert_eq!(debugger.evaluate("$t0"), Some(0));
+        assert_eq!(debugger.reverse_continue(), Stop::Entry);
+
+        debugger.set_breakpoints("count.ez", &[]).unwrap();
+        debugger.resume(Resume::Continue);
+        assert_eq!(debugger.advance(100), Some(Stop::Exited(3)));
+        assert!(!debugger.is_running());
+
+        let registers = debugger.registers(true);
+        assert!(registers.contains(&("$t0".to_string(), 3, "0x3".to_string())));
+    }
+    #[test]
+    fn test_step_over() {
+        let code = "call twice\nmove $r0 $t0\nexit\ntwice:\nadd $t0 $t0 1\nadd $t0 $t0 1\nreturn\n";
+        let mut debugger = Debugger::new("twice.ez", code).unwrap();
+
+        debugger.resume(Resume::StepOver);
+        assert_eq!(debugger.advance(100), Some(Stop::Step));
+        assert_eq!(debugger.stack_frames()[0].line, 2);
+        assert_eq!(debugger.evaluate("$t0"), Some(2));
+        assert_eq!(debugger.step_back(), Stop::Step);
+        assert_eq!(debugger.stack_frames()[0].line, 1);
+        assert_eq!(debugger.evaluate("$t0"), Some(0));
+
+        // a breakpoint in the function stops the step there
+        debugger.set_breakpoints("twice.ez", &[6]).unwrap();
+        debugger.resume(Resume::StepOver);
+        assert_eq!(debugger.advance(100), Some(Stop::Breakpoint));
+        assert_eq!(debugger.stack_frames()[0].line, 6);
+        debugger.resume(Resume::StepOut);
+        assert_eq!(debugger.advance(100), Some(Stop::Step));
+        assert_eq!(debugger.stack_frames()[0].line, 2);
+        assert_eq!(debugger.evaluate("$t0"), Some(2));
+    }
+    #[test]
+    fn test_breakpoints_in_imported_files() {
+        let directory = std::env::temp_dir().join(format!("rezasm-dap-{}", std::process::id()));
+        fs::create_dir_all(directory.join("lib")).unwrap();
+        let main = directory.join("main.ez");
+        let lib = directory.join("lib/math.ez");
+        fs::write(&main, "import \"lib/math.ez\"\ncall square\nexit").unwrap();
+        fs::write(&lib, ".export square\nsquare:\nmul $t0 $t0 $t0\nreturn").unwrap();
+        let mut debugger = Debugger::launch(&LaunchArguments {
+            program: main.to_string_lossy().to_string(),
+            ..LaunchArguments::default()
+        })
+        .unwrap();
+        assert!(Debugger::launch(&LaunchArguments {
+            program: main.to_string_lossy().to_string(),
+            args: vec!["1".to_string()],
+            ..LaunchArguments::default()
+        })
+        .is_err());
+
+        let main = main.to_string_lossy().to_string();
+        let lib = lib.to_string_lossy().to_string();
+        assert_eq!(debugger.set_breakpoints(&main, &[3]), Ok(vec![Some(3)]));
+        assert_eq!(debugger.set_breakpoints(&lib, &[3]), Ok(vec![Some(3)]));
+        debugger.resume(Resume::Continue);
+        assert_eq!(debugger.advance(100), Some(Stop::Breakpoint));
+        let frame = &debugger.stack_frames()[0];
+        assert_eq!((frame.path.as_str(), frame.line), (lib.as_str(), 3));
+        // setting the breakpoints of the imported file kept those of the main file
+        debugger.resume(Resume::Continue);
+        assert_eq!(debugger.advance(100), Some(Stop::Breakpoint));
+        let frame = &debugger.stack_frames()[0];
+        assert_eq!((frame.path.as_str(), frame.line), (main.as_str(), 3));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+mod console;
+mod debugger;
+mod protocol;
+mod server;
+
+extern crate base64;
+extern crate rezasm_core;
+extern crate serde;
+extern crate serde_json;
+
+use std::io::{self, BufReader};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::protocol::{Request, Sender};
+
+/// Runs the debug adapter over standard input and output, as editors expect.
+///
+/// Requests are read on their own thread, so that a running program can be paused.
+fn main() -> io::Result<()> {
+    let (requests, receiver) = mpsc::channel::<Request>();
+    thread::spawn(move || {
+        let mut input = BufReader::new(io::stdin());
+        while let Ok(Some(message)) = protocol::read_message(&mut input) {
+            // only requests are expected from the editor, as the adapter never makes requests
+            let Ok(request) = serde_json::from_value(message) else {
+                continue;
+            };
+            if requests.send(request).is_err() {
+                break;
+            }
+        }
+    });
+    server::run(receiver, &mut Sender::new(io::stdout()))
+}
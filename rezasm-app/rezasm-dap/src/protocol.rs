@@ -0,0 +1,158 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A request from the editor, e.g. to set breakpoints or to step.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Request {
+    pub seq: i64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// A message to the editor, either answering a request or telling it something happened.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OutgoingMessage {
+    Response {
+        seq: i64,
+        request_seq: i64,
+        success: bool,
+        command: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        body: Value,
+    },
+    Event {
+        seq: i64,
+        event: String,
+        body: Value,
+    },
+}
+
+/// Reads a message, which is a JSON object preceded by a `Content-Length` header, returning `None`
+/// once the input has ended.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message has no Content-Length header",
+        ));
+    };
+    let mut content = vec![0u8; length];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &OutgoingMessage) -> io::Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+/// Sends responses and events, numbering each message it sends.
+pub struct Sender<W: Write> {
+    writer: W,
+    seq: i64,
+}
+
+impl<W: Write> Sender<W> {
+    pub fn new(writer: W) -> Sender<W> {
+        Sender { writer, seq: 0 }
+    }
+
+    /// Answers a request with a body, or with an error message.
+    pub fn respond(&mut self, request: &Request, result: Result<Value, String>) -> io::Result<()> {
+        let (success, message, body) = match result {
+            Ok(body) => (true, None, body),
+            Err(message) => (false, Some(message), Value::Null),
+        };
+        let message = OutgoingMessage::Response {
+            seq: self.next_seq(),
+            request_seq: request.seq,
+            success,
+            command: request.command.to_string(),
+            message,
+            body,
+        };
+        write_message(&mut self.writer, &message)
+    }
+
+    pub fn event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        let message = OutgoingMessage::Event {
+            seq: self.next_seq(),
+            event: event.to_string(),
+            body,
+        };
+        write_message(&mut self.writer, &message)
+    }
+
+    fn next_seq(&mut self) -> i64 {
+        self.seq += 1;
+        self.seq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_protocol() {
+        let mut sender = Sender::new(Vec::new());
+        let request = Request {
+            seq: 4,
+            command: "threads".to_string(),
+            arguments: Value::Null,
+        };
+        sender
+            .respond(&request, Ok(json!({"threads": []})))
+            .unwrap();
+        sender.event("stopped", json!({"reason": "step"})).unwrap();
+
+        let mut reader = BufReader::new(sender.writer.as_slice());
+        let response = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(
+            response,
+            json!({
+                "type": "response",
+                "seq": 1,
+                "request_seq": 4,
+                "success": true,
+                "command": "threads",
+                "body": {"threads": []},
+            })
+        );
+        let event = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(event["event"], "stopped");
+        assert_eq!(event["seq"], 2);
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+}
@@ -0,0 +1,363 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::debugger::{Debugger, LaunchArguments, Resume, Stop};
+use crate::protocol::{Request, Sender};
+
+/// The only thread of a program, since the simulator runs one line at a time.
+const THREAD_ID: i64 = 1;
+
+/// The variables reference of the registers scope; variables references must be positive.
+const REGISTERS_REFERENCE: i64 = 1;
+
+/// How many lines to run between checking for requests, such as to pause, while running.
+const STEPS_PER_CHECK: usize = 10_000;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceBreakpoint {
+    line: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Source {
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetBreakpointsArguments {
+    #[serde(default)]
+    source: Source,
+    #[serde(default)]
+    breakpoints: Vec<SourceBreakpoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ValueFormat {
+    #[serde(default)]
+    hex: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VariablesArguments {
+    variables_reference: i64,
+    #[serde(default)]
+    format: Option<ValueFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EvaluateArguments {
+    expression: String,
+    #[serde(default)]
+    context: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadMemoryArguments {
+    memory_reference: String,
+    #[serde(default)]
+    offset: i64,
+    count: usize,
+}
+
+/// The state of a debugging session.
+#[derive(Debug, Default)]
+struct Session {
+    debugger: Option<Debugger>,
+    stop_on_entry: bool,
+}
+
+impl Session {
+    fn get_debugger(&mut self) -> Result<&mut Debugger, String> {
+        self.debugger
+            .as_mut()
+            .ok_or_else(|| "no program has been launched".to_string())
+    }
+}
+
+/// Answers requests from the editor until it disconnects, running the program in between.
+pub fn run<W: Write>(requests: Receiver<Request>, sender: &mut Sender<W>) -> io::Result<()> {
+    let mut session = Session::default();
+    loop {
+        let running = session.debugger.as_ref().is_some_and(Debugger::is_running);
+        let request = if running {
+            match requests.try_recv() {
+                Ok(request) => Some(request),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        } else {
+            match requests.recv() {
+                Ok(request) => Some(request),
+                Err(_) => return Ok(()),
+            }
+        };
+        if let Some(request) = request {
+            if !handle_request(&mut session, sender, request)? {
+                return Ok(());
+            }
+        }
+        if let Some(debugger) = &mut session.debugger {
+            let stop = debugger.advance(STEPS_PER_CHECK);
+            send_output(debugger, sender)?;
+            if let Some(stop) = stop {
+                report(sender, stop)?;
+            }
+        }
+    }
+}
+
+/// Answers a request, returning whether the session should go on.
+fn handle_request<W: Write>(
+    session: &mut Session,
+    sender: &mut Sender<W>,
+    request: Request,
+) -> io::Result<bool> {
+    let mut stop = None;
+    let result = match request.command.as_str() {
+        "initialize" => Ok(json!({
+            "supportsConfigurationDoneRequest": true,
+            "supportsStepBack": true,
+            "supportsReadMemoryRequest": true,
+            "supportsEvaluateForHovers": true,
+        })),
+        "launch" => arguments(&request).and_then(|arguments: LaunchArguments| {
+            session.debugger = Some(Debugger::launch(&arguments)?);
+            session.stop_on_entry = arguments.stop_on_entry;
+            Ok(Value::Null)
+        }),
+        "setBreakpoints" => arguments(&request).and_then(|arguments: SetBreakpointsArguments| {
+            let lines: Vec<i64> = arguments.breakpoints.iter().map(|b| b.line).collect();
+            let path = arguments.source.path.unwrap_or_default();
+            let placed = session.get_debugger()?.set_breakpoints(&path, &lines);
+            let breakpoints: Vec<Value> = lines
+                .iter()
+                .enumerate()
+                .map(|(index, line)| match &placed {
+                    Ok(placed) => match placed[index] {
+                        Some(placed) => json!({"verified": true, "line": placed}),
+                        None => json!({
+                            "verified": false,
+                            "line": line,
+                            "message": "there is no code on or after this line",
+                        }),
+                    },
+                    Err(message) => json!({"verified": false, "line": line, "message": message}),
+                })
+                .collect();
+            Ok(json!({ "breakpoints": breakpoints }))
+        }),
+        "configurationDone" => {
+            let stop_on_entry = session.stop_on_entry;
+            session.get_debugger().map(|debugger| {
+                if stop_on_entry {
+                    stop = Some(Stop::Entry);
+                } else {
+                    debugger.resume(Resume::Continue);
+                }
+                Value::Null
+            })
+        }
+        "threads" => Ok(json!({ "threads": [{"id": THREAD_ID, "name": "main"}] })),
+        "stackTrace" => session.get_debugger().map(|debugger| {
+            let frames: Vec<Value> = debugger
+                .stack_frames()
+                .into_iter()
+                .enumerate()
+                .map(|(id, frame)| {
+                    let name = Path::new(&frame.path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| frame.path.to_string());
+                    json!({
+                        "id": id,
+                        "name": frame.name,
+                        "source": {"name": name, "path": frame.path},
+                        "line": frame.line,
+                        "column": 1,
+                    })
+                })
+                .collect();
+            json!({ "stackFrames": frames, "totalFrames": frames.len() })
+        }),
+        // registers are shared by every frame
+        "scopes" => Ok(json!({
+            "scopes": [{
+                "name": "Registers",
+                "presentationHint": "registers",
+                "variablesReference": REGISTERS_REFERENCE,
+                "expensive": false,
+            }]
+        })),
+        "variables" => arguments(&request).and_then(|arguments: VariablesArguments| {
+            if arguments.variables_reference != REGISTERS_REFERENCE {
+                return Ok(json!({ "variables": [] }));
+            }
+            let hex = arguments.format.unwrap_or_default().hex;
+            let variables: Vec<Value> = session
+                .get_debugger()?
+                .registers(hex)
+                .into_iter()
+                .map(|(name, value, display)| {
+                    json!({
+                        "name": name,
+                        "value": display,
+                        "variablesReference": 0,
+                        "memoryReference": format!("{:#x}", value),
+                    })
+                })
+                .collect();
+            Ok(json!({ "variables": variables }))
+        }),
+        "evaluate" => arguments(&request).and_then(|arguments: EvaluateArguments| {
+            let debugger = session.get_debugger()?;
+            if let Some(value) = debugger.evaluate(&arguments.expression) {
+                return Ok(json!({ "result": value.to_string(), "variablesReference": 0 }));
+            }
+            if arguments.context.as_deref() != Some("repl") {
+                return Err(format!("`{}` is not a register", arguments.expression));
+            }
+            // anything else typed into the debug console is input for the program
+            debugger.provide_input(format!("{}\n", arguments.expression).as_bytes());
+            Ok(json!({ "result": "", "variablesReference": 0 }))
+        }),
+        "readMemory" => arguments(&request).and_then(|arguments: ReadMemoryArguments| {
+            let address = parse_address(&arguments.memory_reference)? + arguments.offset;
+            let (start, bytes) = session
+                .get_debugger()?
+                .read_memory(address, arguments.count);
+            Ok(json!({
+                "address": format!("{:#x}", start),
+                "data": BASE64.encode(&bytes),
+                "unreadableBytes": arguments.count - bytes.len(),
+            }))
+        }),
+        "continue" => resume(session, Resume::Continue),
+        "next" => resume(session, Resume::StepOver),
+        "stepIn" => resume(session, Resume::StepIn),
+        "stepOut" => resume(session, Resume::StepOut),
+        "stepBack" => session.get_debugger().map(|debugger| {
+            stop = Some(debugger.step_back());
+            Value::Null
+        }),
+        "reverseContinue" => session.get_debugger().map(|debugger| {
+            stop = Some(debugger.reverse_continue());
+            Value::Null
+        }),
+        "pause" => session.get_debugger().map(|debugger| {
+            if debugger.is_running() {
+                stop = Some(debugger.pause());
+            }
+            Value::Null
+        }),
+        "disconnect" => {
+            sender.respond(&request, Ok(Value::Null))?;
+            return Ok(false);
+        }
+        command => Err(format!("unsupported request `{}`", command)),
+    };
+
+    let launched = request.command == "launch" && result.is_ok();
+    sender.respond(&request, result)?;
+    if launched {
+        // breakpoints can only be set once the program is loaded
+        sender.event("initialized", Value::Null)?;
+    }
+    if let Some(stop) = stop {
+        report(sender, stop)?;
+    }
+    Ok(true)
+}
+
+fn arguments<T: DeserializeOwned>(request: &Request) -> Result<T, String> {
+    serde_json::from_value(request.arguments.clone()).map_err(|error| error.to_string())
+}
+
+fn resume(session: &mut Session, resume: Resume) -> Result<Value, String> {
+    session.get_debugger()?.resume(resume);
+    Ok(json!({ "allThreadsContinued": true }))
+}
+
+/// Reads a memory reference, which is an address in decimal or hexadecimal.
+fn parse_address(reference: &str) -> Result<i64, String> {
+    let parsed = match reference.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => reference.parse::<i64>(),
+    };
+    parsed.map_err(|_| format!("`{}` is not an address", reference))
+}
+
+fn send_output<W: Write>(debugger: &mut Debugger, sender: &mut Sender<W>) -> io::Result<()> {
+    let output = debugger.take_output();
+    if output.is_empty() {
+        return Ok(());
+    }
+    sender.event("output", json!({"category": "stdout", "output": output}))
+}
+
+/// Tells the editor why the program stopped, or that it ended.
+fn report<W: Write>(sender: &mut Sender<W>, stop: Stop) -> io::Result<()> {
+    let (reason, text) = match stop {
+        Stop::Entry => ("entry", None),
+        Stop::Step => ("step", None),
+        Stop::Breakpoint => ("breakpoint", None),
+        Stop::Pause => ("pause", None),
+        Stop::AwaitingInput => {
+            sender.event(
+                "output",
+                json!({
+                    "category": "console",
+                    "output": "The program is waiting for input; type it into the debug console, then continue.\n",
+                }),
+            )?;
+            ("pause", Some("Waiting for input".to_string()))
+        }
+        Stop::Error(message) => {
+            sender.event(
+                "output",
+                json!({"category": "stderr", "output": format!("{}\n", message)}),
+            )?;
+            ("exception", Some(message))
+        }
+        Stop::Exited(exit_code) => {
+            sender.event("exited", json!({ "exitCode": exit_code }))?;
+            return sender.event("terminated", json!({}));
+        }
+    };
+    let mut body = json!({
+        "reason": reason,
+        "threadId": THREAD_ID,
+        "allThreadsStopped": true,
+    });
+    if let Some(text) = text {
+        body["description"] = Value::String(text.to_string());
+        body["text"] = Value::String(text);
+    }
+    sender.event("stopped", body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("0x10"), Ok(16));
+        assert_eq!(parse_address("24"), Ok(24));
+        assert!(parse_address("$t0").is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use rezasm_core::instructions::instruction_set::InstructionSet;
+use rezasm_core::parser::labels::FileLabels;
+use rezasm_core::parser::lexer;
+use rezasm_core::util::word_size::WordSize;
+
+/// What a token of a line is, which decides how it is highlighted and what is known about it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    Mnemonic,
+    LabelDefinition,
+    LabelReference,
+    Register,
+    Number,
+    String,
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub kind: TokenKind,
+    pub range: Range,
+}
+
+impl Token {
+    /// Gets the name of the label a token defines or refers to.
+    pub fn get_label(&self) -> Option<&str> {
+        match self.kind {
+            TokenKind::LabelDefinition => Some(self.text.trim_end_matches(':')),
+            TokenKind::LabelReference => Some(&self.text),
+            _ => None,
+        }
+    }
+}
+
+/// A file named by an `import` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub file: String,
+    pub range: Range,
+}
+
+/// Everything that can be learned about a file of code on its own, without the files it imports.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    tokens: Vec<Token>,
+    imports: Vec<Import>,
+    // the labels named by `.global` and `.export` lines
+    exports: Vec<String>,
+    labels: FileLabels,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Analysis {
+    pub fn new(text: &str, instructions: &InstructionSet) -> Analysis {
+        let word_size = WordSize::default();
+        let mut analysis = Analysis::default();
+        for (line_number, line) in text.lines().enumerate() {
+            let line_number = line_number as u32;
+            let spans = lexer::tokenize_line_spans(line);
+            let mut line_tokens: Vec<Token> = spans
+                .iter()
+                .enumerate()
+                .map(|(index, (start, text))| Token {
+                    text: text.to_string(),
+                    kind: classify(index, text),
+                    range: Range::new(
+                        Position::new(line_number, column(line, *start)),
+                        Position::new(line_number, column(line, start + text.len())),
+                    ),
+                })
+                .collect();
+
+            if let (Some(first), Some(last)) = (line_tokens.first(), line_tokens.last()) {
+                let range = Range::new(first.range.start, last.range.end);
+                if let Some(Err(error)) =
+                    lexer::parse_line(&line.to_string(), &word_size, instructions)
+                {
+                    analysis
+                        .diagnostics
+                        .push(error_diagnostic(range, error.to_string()));
+                }
+                if first.text == "import" && line_tokens.len() == 2 {
+                    if let Ok(file) = lexer::get_string_immediate(&line_tokens[1].text) {
+                        analysis.imports.push(Import {
+                            file: file.trim_matches('"').to_string(),
+                            range: line_tokens[1].range,
+                        });
+                    }
+                }
+                if lexer::is_export_directive(&first.text) && line_tokens.len() == 2 {
+                    analysis.exports.push(line_tokens[1].text.to_string());
+                }
+            }
+
+            let comment_search = spans
+                .last()
+                .map(|(start, text)| start + text.len())
+                .unwrap_or(0);
+            if let Some(offset) = line[comment_search..].find('#') {
+                let start = comment_search + offset;
+                line_tokens.push(Token {
+                    text: line[start..].to_string(),
+                    kind: TokenKind::Comment,
+                    range: Range::new(
+                        Position::new(line_number, column(line, start)),
+                        Position::new(line_number, column(line, line.len())),
+                    ),
+                });
+            }
+            analysis.tokens.extend(line_tokens);
+        }
+        for token in analysis.tokens.iter() {
+            if let (TokenKind::LabelDefinition, Some(label)) = (token.kind, token.get_label()) {
+                analysis.labels.define(label, token.range.start.line as i64);
+            }
+        }
+        analysis
+    }
+
+    /// Gets every token, including comments, in the order they appear.
+    pub fn get_tokens(&self) -> &Vec<Token> {
+        &self.tokens
+    }
+
+    pub fn get_imports(&self) -> &Vec<Import> {
+        &self.imports
+    }
+
+    /// Gets the labels which the file lets other files refer to.
+    pub fn get_exports(&self) -> &Vec<String> {
+        &self.exports
+    }
+
+    /// Gets the problems which parsing each line on its own finds.
+    pub fn get_diagnostics(&self) -> &Vec<Diagnostic> {
+        &self.diagnostics
+    }
+
+    /// Gets the token which a position is in or just after, so that a cursor at the end of a
+    /// word still finds it.
+    pub fn token_at(&self, position: Position) -> Option<&Token> {
+        self.tokens.iter().find(|token| {
+            token.range.start.line == position.line
+                && token.range.start.character <= position.character
+                && position.character <= token.range.end.character
+        })
+    }
+
+    /// Finds the definition in this file which a label on a line means, which for a local label
+    /// such as `.loop` or `1b` depends on where it is.
+    pub fn find_definition(&self, label: &str, line: u32) -> Option<&Token> {
+        let on_line = self
+            .label_definitions()
+            .find(|token| token.range.start.line == line && token.get_label() == Some(label));
+        if on_line.is_some() {
+            return on_line;
+        }
+        let defined = self.resolve_label(label, line)?;
+        self.label_definitions()
+            .find(|token| token.range.start.line == defined)
+    }
+
+    /// Gets the line of the definition in this file which a label on a line means, following the
+    /// same rules as the simulator.
+    pub fn resolve_label(&self, label: &str, line: u32) -> Option<u32> {
+        self.labels
+            .resolve(label, line as i64)
+            .map(|line| line as u32)
+    }
+
+    /// Gets the tokens which define labels.
+    pub fn label_definitions(&self) -> impl Iterator<Item = &Token> {
+        self.tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::LabelDefinition)
+    }
+
+    /// Gets the tokens which define or refer to a label.
+    pub fn label_tokens<'a>(&'a self, label: &'a str) -> impl Iterator<Item = &'a Token> {
+        self.tokens
+            .iter()
+            .filter(move |token| token.get_label() == Some(label))
+    }
+}
+
+/// Works out what a token is from its position in the line and how it is written.
+fn classify(index: usize, text: &String) -> TokenKind {
+    if index == 0 {
+        return if lexer::looks_like_label(text) {
+            TokenKind::LabelDefinition
+        } else {
+            TokenKind::Mnemonic
+        };
+    }
+    if lexer::is_register(text) || lexer::looks_like_dereference(text) {
+        TokenKind::Register
+    } else if lexer::looks_like_string_immediate(text)
+        || lexer::looks_like_character_immediate(text)
+    {
+        TokenKind::String
+    } else if lexer::looks_like_label_reference(text) && !lexer::is_numeric(text) {
+        TokenKind::LabelReference
+    } else {
+        TokenKind::Number
+    }
+}
+
+/// Converts a byte offset within a line to a column, which editors count in UTF-16 code units.
+pub fn column(line: &str, offset: usize) -> u32 {
+    line[..offset].encode_utf16().count() as u32
+}
+
+pub fn error_diagnostic(range: Range, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("rezasm".to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analysis() {
+        let text =
+            "import \"lib.ez\"\nloop: # the loop\n  add $t0, $t0 1\n  frob $t0\n  bne $t0 10 loop";
+        let analysis = Analysis::new(text, &InstructionSet::standard());
+
+        let kinds: Vec<TokenKind> = analysis
+            .get_tokens()
+            .iter()
+            .map(|token| token.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Mnemonic,
+                TokenKind::String,
+                TokenKind::LabelDefinition,
+                TokenKind::Comment,
+                TokenKind::Mnemonic,
+                TokenKind::Register,
+                TokenKind::Register,
+                TokenKind::Number,
+                TokenKind::Mnemonic,
+                TokenKind::Register,
+                TokenKind::Mnemonic,
+                TokenKind::Register,
+                TokenKind::Number,
+                TokenKind::LabelReference,
+            ]
+        );
+
+        assert_eq!(analysis.get_imports()[0].file, "lib.ez");
+        assert_eq!(analysis.get_diagnostics().len(), 1);
+        assert_eq!(
+            analysis.get_diagnostics()[0].range.start,
+            Position::new(3, 2)
+        );
+
+        let token = analysis.token_at(Position::new(4, 16)).unwrap();
+        assert_eq!(token.get_label(), Some("loop"));
+        assert_eq!(analysis.label_tokens("loop").count(), 2);
+        assert_eq!(analysis.label_definitions().count(), 1);
+
+        let text = "f:\n1:\n.loop:\n  jump 1b\ng:\n.loop:\n  jump .loop\n1:";
+        let analysis = Analysis::new(text, &InstructionSet::standard());
+        assert_eq!(analysis.resolve_label("1b", 3), Some(1));
+        assert_eq!(analysis.resolve_label("1f", 3), Some(7));
+        assert_eq!(
+            analysis
+                .find_definition(".loop", 6)
+                .unwrap()
+                .range
+                .start
+                .line,
+            5
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, DocumentSymbol, Documentation, Hover,
+    HoverContents, Location, MarkupContent, MarkupKind, Position, Range, SemanticToken,
+    SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend, SymbolKind, TextEdit, Url,
+};
+use rezasm_core::simulation::registry;
+
+use crate::analysis::{Token, TokenKind};
+use crate::workspace::{file_name, Workspace};
+
+/// The kinds of semantic tokens, in the order their indices refer to.
+const TOKEN_TYPES: [SemanticTokenType; 6] = [
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::COMMENT,
+];
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: vec![SemanticTokenModifier::DECLARATION],
+    }
+}
+
+/// Shows the documentation of an instruction, what a register is for, or where a label is defined.
+pub fn hover(workspace: &Workspace, uri: &Url, position: Position) -> Option<Hover> {
+    let token = workspace
+        .get_document(uri)?
+        .get_analysis()
+        .token_at(position)?;
+    let markdown = match token.kind {
+        TokenKind::Mnemonic => instruction_markdown(workspace, &token.text)?,
+        TokenKind::Register => register_markdown(&token.text)?,
+        TokenKind::LabelDefinition | TokenKind::LabelReference => {
+            let label = token.get_label()?;
+            let definitions = workspace.find_definitions(uri, label, token.range.start.line);
+            let definition = definitions.first()?;
+            format!(
+                "label `{}`, defined on line {} of `{}`",
+                label,
+                definition.range.start.line + 1,
+                file_name(&definition.uri)
+            )
+        }
+        _ => return None,
+    };
+    Some(Hover {
+        contents: HoverContents::Markup(markdown_content(markdown)),
+        range: Some(token.range),
+    })
+}
+
+/// Completes instruction names at the start of a line, and registers and labels after them.
+pub fn completion(workspace: &Workspace, uri: &Url, position: Position) -> Vec<CompletionItem> {
+    let Some(document) = workspace.get_document(uri) else {
+        return Vec::new();
+    };
+    let analysis = document.get_analysis();
+    let current = analysis.token_at(position);
+    if current.is_some_and(|token| token.kind == TokenKind::Comment) {
+        return Vec::new();
+    }
+    let is_first = !analysis.get_tokens().iter().any(|token| {
+        token.range.start.line == position.line
+            && token.range.end.character < position.character
+            && Some(token) != current
+    });
+    let prefix_range = current.map(|token| Range::new(token.range.start, position));
+
+    let mut items = Vec::new();
+    if is_first {
+        let mut names: Vec<String> = workspace
+            .get_instructions()
+            .get_references()
+            .into_iter()
+            .map(|reference| reference.name)
+            .collect();
+        names.dedup();
+        for name in names {
+            let references = workspace.get_instructions().find_references(&name);
+            items.push(CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail: references
+                    .first()
+                    .map(|reference| reference.summary.to_string()),
+                documentation: instruction_markdown(workspace, &name)
+                    .map(|markdown| Documentation::MarkupContent(markdown_content(markdown))),
+                ..CompletionItem::default()
+            });
+        }
+    } else {
+        for number in 0..registry::ALL_REGISTERS.len() {
+            items.push(CompletionItem {
+                label: registry::get_register_display_name(number),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: registry::get_register_description(number),
+                ..CompletionItem::default()
+            });
+        }
+        if !current.is_some_and(|token| token.text.starts_with('$')) {
+            for label in workspace.get_labels(uri) {
+                items.push(CompletionItem {
+                    label,
+                    kind: Some(CompletionItemKind::FUNCTION),
+                    ..CompletionItem::default()
+                });
+            }
+        }
+    }
+
+    // replace the whole word typed so far, since editors may not count `$` as part of a word
+    if let Some(range) = prefix_range {
+        for item in &mut items {
+            item.text_edit = Some(CompletionTextEdit::Edit(TextEdit::new(
+                range,
+                item.label.to_string(),
+            )));
+        }
+    }
+    items
+}
+
+/// Finds where the label at a position is defined, or the file an `import` line names.
+pub fn definition(workspace: &Workspace, uri: &Url, position: Position) -> Vec<Location> {
+    let Some(analysis) = workspace
+        .get_document(uri)
+        .map(|document| document.get_analysis())
+    else {
+        return Vec::new();
+    };
+    if let Some(import) = analysis.get_imports().iter().find(|import| {
+        import.range.start.line == position.line
+            && import.range.start.character <= position.character
+            && position.character <= import.range.end.character
+    }) {
+        return workspace
+            .resolve_import(uri, &import.file)
+            .filter(|imported| workspace.get_document(imported).is_some())
+            .map(|imported| Location::new(imported, Range::default()))
+            .into_iter()
+            .collect();
+    }
+    match analysis.token_at(position).and_then(Token::get_label) {
+        Some(label) => workspace.find_definitions(uri, label, position.line),
+        None => Vec::new(),
+    }
+}
+
+/// Finds everywhere the label at a position is used across the project.
+pub fn references(
+    workspace: &Workspace,
+    uri: &Url,
+    position: Position,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let label = workspace
+        .get_document(uri)
+        .and_then(|document| document.get_analysis().token_at(position))
+        .and_then(Token::get_label);
+    let Some(label) = label else {
+        return Vec::new();
+    };
+    let mut locations = workspace.find_label_tokens(uri, label, false);
+    if !include_declaration {
+        let definitions = workspace.find_definitions(uri, label, position.line);
+        locations.retain(|location| !definitions.contains(location));
+    }
+    locations
+}
+
+/// Lists the labels of a file, each spanning the lines up to the next label.
+pub fn document_symbols(workspace: &Workspace, uri: &Url) -> Vec<DocumentSymbol> {
+    let Some(document) = workspace.get_document(uri) else {
+        return Vec::new();
+    };
+    let line_count = document.get_text().lines().count().max(1) as u32;
+    let labels: Vec<&Token> = document.get_analysis().label_definitions().collect();
+    labels
+        .iter()
+        .enumerate()
+        .map(|(index, token)| {
+            let end_line = labels
+                .get(index + 1)
+                .map(|next| next.range.start.line)
+                .unwrap_or(line_count)
+                .saturating_sub(1)
+                .max(token.range.start.line);
+            #[allow(deprecated)]
+            DocumentSymbol {
+                name: token.get_label().unwrap_or_default().to_string(),
+                detail: None,
+                kind: SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                range: Range::new(token.range.start, Position::new(end_line, u32::MAX)),
+                selection_range: token.range,
+                children: None,
+            }
+        })
+        .collect()
+}
+
+/// Encodes the tokens of a file for highlighting, each relative to the one before it.
+pub fn semantic_tokens(workspace: &Workspace, uri: &Url) -> Vec<SemanticToken> {
+    let Some(document) = workspace.get_document(uri) else {
+        return Vec::new();
+    };
+    let mut previous = Position::new(0, 0);
+    let mut tokens = Vec::new();
+    for token in document.get_analysis().get_tokens() {
+        let (token_type, modifiers) = match token.kind {
+            TokenKind::Mnemonic => (SemanticTokenType::KEYWORD, 0),
+            TokenKind::LabelDefinition => (SemanticTokenType::FUNCTION, 1),
+            TokenKind::LabelReference => (SemanticTokenType::FUNCTION, 0),
+            TokenKind::Register => (SemanticTokenType::VARIABLE, 0),
+            TokenKind::Number => (SemanticTokenType::NUMBER, 0),
+            TokenKind::String => (SemanticTokenType::STRING, 0),
+            TokenKind::Comment => (SemanticTokenType::COMMENT, 0),
+        };
+        let start = token.range.start;
+        let delta_line = start.line - previous.line;
+        tokens.push(SemanticToken {
+            delta_line,
+            delta_start: if delta_line == 0 {
+                start.character - previous.character
+            } else {
+                start.character
+            },
+            length: token.range.end.character - start.character,
+            token_type: TOKEN_TYPES
+                .iter()
+                .position(|kind| *kind == token_type)
+                .unwrap_or_default() as u32,
+            token_modifiers_bitset: modifiers,
+        });
+        previous = start;
+    }
+    tokens
+}
+
+fn instruction_markdown(workspace: &Workspace, name: &str) -> Option<String> {
+    let references = workspace.get_instructions().find_references(name);
+    if references.is_empty() {
+        return None;
+    }
+    Some(
+        references
+            .iter()
+            .map(|reference| reference.to_markdown())
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+    )
+}
+
+/// Describes the register a register or dereference token uses.
+fn register_markdown(text: &str) -> Option<String> {
+    let name = match (text.find('('), text.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &text[start + 1..end],
+        _ => text,
+    };
+    let number = registry::get_register_number(&name.to_string()).ok()?;
+    let description = registry::get_register_description(number)?;
+    Some(format!(
+        "`{}` (register {}): {}",
+        registry::get_register_display_name(number),
+        number,
+        description
+    ))
+}
+
+fn markdown_content(value: String) -> MarkupContent {
+    MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_features() {
+        let uri = Url::parse("file:///project/main.ez").unwrap();
+        let mut workspace = Workspace::new();
+        workspace.open(
+            uri.clone(),
+            "loop:\n  add $t0 $t0 1\n  bne $t0 10 loop\nend:\n  ".to_string(),
+        );
+
+        let hover = hover(&workspace, &uri, Position::new(1, 3)).unwrap();
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("hover should be markdown");
+        };
+        assert!(content.value.starts_with("### `add output input1 input2`"));
+        let hover = super::hover(&workspace, &uri, Position::new(1, 7)).unwrap();
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("hover should be markdown");
+        };
+        assert_eq!(content.value, "`$t0` (register 22): Temporary register 0");
+
+        let items = completion(&workspace, &uri, Position::new(4, 2));
+        assert!(items.iter().any(|item| item.label == "add"));
+        let items = completion(&workspace, &uri, Position::new(2, 16));
+        assert!(items.iter().any(|item| item.label == "loop"));
+        assert!(items.iter().any(|item| item.label == "$t0"));
+
+        let definitions = definition(&workspace, &uri, Position::new(2, 15));
+        assert_eq!(definitions[0].range.start, Position::new(0, 0));
+        assert_eq!(
+            references(&workspace, &uri, Position::new(0, 1), false).len(),
+            1
+        );
+        assert_eq!(
+            references(&workspace, &uri, Position::new(0, 1), true).len(),
+            2
+        );
+
+        let symbols = document_symbols(&workspace, &uri);
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].range.end.line, 2);
+
+        let tokens = semantic_tokens(&workspace, &uri);
+        assert_eq!(tokens.len(), 10);
+        assert_eq!((tokens[1].delta_line, tokens[1].delta_start), (1, 2));
+        assert_eq!((tokens[2].delta_line, tokens[2].delta_start), (0, 4));
+    }
+}
@@ -0,0 +1,22 @@
+mod analysis;
+mod features;
+mod server;
+mod workspace;
+
+extern crate lsp_server;
+extern crate lsp_types;
+extern crate rezasm_core;
+
+use lsp_server::Connection;
+
+use crate::server::ServerError;
+
+/// Runs the language server over standard input and output, as editors expect.
+fn main() -> Result<(), ServerError> {
+    let (connection, io_threads) = Connection::stdio();
+    server::run(&connection)?;
+    // the writer thread only finishes once the connection is gone
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
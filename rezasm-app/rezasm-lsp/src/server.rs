@@ -0,0 +1,197 @@
+use std::error::Error;
+
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, References, Request as _,
+    SemanticTokensFullRequest,
+};
+use lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, Diagnostic,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse,
+    HoverParams, HoverProviderCapability, OneOf, PublishDiagnosticsParams, ReferenceParams,
+    SemanticTokens, SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensParams,
+    SemanticTokensResult, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Url,
+};
+use serde::de::DeserializeOwned;
+
+use crate::features;
+use crate::workspace::Workspace;
+
+pub type ServerError = Box<dyn Error + Sync + Send>;
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["$".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        semantic_tokens_provider: Some(
+            SemanticTokensOptions {
+                legend: features::semantic_tokens_legend(),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..SemanticTokensOptions::default()
+            }
+            .into(),
+        ),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Answers requests from the editor until it asks the server to shut down.
+pub fn run(connection: &Connection) -> Result<(), ServerError> {
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    let mut workspace = Workspace::new();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = handle_request(&workspace, request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if handle_notification(connection, &mut workspace, notification)? {
+                    publish_diagnostics(connection, &workspace)?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(workspace: &Workspace, request: Request) -> Response {
+    let id = request.id.clone();
+    let result = match request.method.as_str() {
+        HoverRequest::METHOD => respond(request, |params: HoverParams| {
+            let position = params.text_document_position_params;
+            features::hover(workspace, &position.text_document.uri, position.position)
+        }),
+        Completion::METHOD => respond(request, |params: CompletionParams| {
+            let position = params.text_document_position;
+            CompletionResponse::Array(features::completion(
+                workspace,
+                &position.text_document.uri,
+                position.position,
+            ))
+        }),
+        GotoDefinition::METHOD => respond(request, |params: GotoDefinitionParams| {
+            let position = params.text_document_position_params;
+            GotoDefinitionResponse::Array(features::definition(
+                workspace,
+                &position.text_document.uri,
+                position.position,
+            ))
+        }),
+        References::METHOD => respond(request, |params: ReferenceParams| {
+            let position = params.text_document_position;
+            features::references(
+                workspace,
+                &position.text_document.uri,
+                position.position,
+                params.context.include_declaration,
+            )
+        }),
+        DocumentSymbolRequest::METHOD => respond(request, |params: DocumentSymbolParams| {
+            DocumentSymbolResponse::Nested(features::document_symbols(
+                workspace,
+                &params.text_document.uri,
+            ))
+        }),
+        SemanticTokensFullRequest::METHOD => respond(request, |params: SemanticTokensParams| {
+            SemanticTokensResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: features::semantic_tokens(workspace, &params.text_document.uri),
+            })
+        }),
+        method => {
+            return Response::new_err(
+                id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported request `{}`", method),
+            )
+        }
+    };
+    match result {
+        Ok(response) => response,
+        Err(error) => Response::new_err(
+            id,
+            lsp_server::ErrorCode::InvalidParams as i32,
+            error.to_string(),
+        ),
+    }
+}
+
+/// Reads the parameters of a request and answers it.
+fn respond<P: DeserializeOwned, R: serde::Serialize>(
+    request: Request,
+    handler: impl FnOnce(P) -> R,
+) -> Result<Response, serde_json::Error> {
+    let params: P = serde_json::from_value(request.params)?;
+    Ok(Response::new_ok(request.id, handler(params)))
+}
+
+/// Updates the workspace, returning whether any file changed.
+fn handle_notification(
+    connection: &Connection,
+    workspace: &mut Workspace,
+    notification: Notification,
+) -> Result<bool, ServerError> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+            workspace.open(params.text_document.uri, params.text_document.text);
+        }
+        DidChangeTextDocument::METHOD => {
+            let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+            // changes are always sent as the full text, see `capabilities`
+            if let Some(change) = params.content_changes.into_iter().last() {
+                workspace.open(params.text_document.uri, change.text);
+            }
+        }
+        DidCloseTextDocument::METHOD => {
+            let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+            workspace.close(&params.text_document.uri);
+            publish(connection, params.text_document.uri, Vec::new())?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Sends the diagnostics of every open file, since a change to one file can affect the labels of
+/// the others in its project.
+fn publish_diagnostics(connection: &Connection, workspace: &Workspace) -> Result<(), ServerError> {
+    for uri in workspace.get_open_documents() {
+        let diagnostics = workspace.get_diagnostics(&uri);
+        publish(connection, uri, diagnostics)?;
+    }
+    Ok(())
+}
+
+fn publish(
+    connection: &Connection,
+    uri: Url,
+    diagnostics: Vec<Diagnostic>,
+) -> Result<(), ServerError> {
+    let notification = Notification::new(
+        PublishDiagnostics::METHOD.to_string(),
+        PublishDiagnosticsParams::new(uri, diagnostics, None),
+    );
+    connection
+        .sender
+        .send(Message::Notification(notification))?;
+    Ok(())
+}
@@ -0,0 +1,426 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use lsp_types::{Diagnostic, Location, Url};
+use rezasm_core::instructions::instruction_set::InstructionSet;
+use rezasm_core::parser::lexer;
+use rezasm_core::util::error::SimulatorError;
+
+use crate::analysis::{error_diagnostic, Analysis, TokenKind};
+
+/// A file of code, either open in the editor or read from disk because an open file imports it.
+#[derive(Debug)]
+pub struct Document {
+    text: String,
+    analysis: Analysis,
+    open: bool,
+}
+
+impl Document {
+    pub fn get_text(&self) -> &String {
+        &self.text
+    }
+
+    pub fn get_analysis(&self) -> &Analysis {
+        &self.analysis
+    }
+}
+
+/// The files the editor knows about, along with the files they import.
+///
+/// Files which are connected through imports form a project. Each file has labels of its own, and
+/// can refer to the labels which other files of its project export.
+#[derive(Debug)]
+pub struct Workspace {
+    instructions: InstructionSet,
+    documents: HashMap<Url, Document>,
+}
+
+impl Workspace {
+    pub fn new() -> Workspace {
+        Workspace {
+            instructions: InstructionSet::standard(),
+            documents: HashMap::new(),
+        }
+    }
+
+    pub fn get_instructions(&self) -> &InstructionSet {
+        &self.instructions
+    }
+
+    pub fn get_document(&self, uri: &Url) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    /// Opens or changes a file, reading any files it imports which are not yet known.
+    pub fn open(&mut self, uri: Url, text: String) {
+        self.insert(uri.clone(), text, true);
+        self.load_imports(&uri);
+    }
+
+    /// Closes a file, keeping it as it is on disk if an open file still imports it.
+    pub fn close(&mut self, uri: &Url) {
+        if let Some(document) = self.documents.get_mut(uri) {
+            document.open = false;
+        }
+        let mut reachable = HashSet::new();
+        let open: Vec<Url> = self.get_open_documents();
+        for uri in &open {
+            self.collect_imports(uri, &mut reachable);
+        }
+        self.documents.retain(|uri, _| reachable.contains(uri));
+        if self.documents.contains_key(uri) {
+            match read(uri) {
+                Some(text) => self.insert(uri.clone(), text, false),
+                None => {
+                    self.documents.remove(uri);
+                }
+            }
+        }
+    }
+
+    pub fn get_open_documents(&self) -> Vec<Url> {
+        let mut open: Vec<Url> = self
+            .documents
+            .iter()
+            .filter(|(_, document)| document.open)
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        open.sort();
+        open
+    }
+
+    /// Gets the location of the file an `import` line names, which is relative to the importing
+    /// file.
+    pub fn resolve_import(&self, uri: &Url, file: &str) -> Option<Url> {
+        uri.join(file).ok()
+    }
+
+    /// Gets every file which is connected to a file through imports in either direction, in a
+    /// stable order, starting with the file itself.
+    pub fn get_project(&self, uri: &Url) -> Vec<Url> {
+        let mut project = vec![uri.clone()];
+        let mut index = 0;
+        while index < project.len() {
+            let current = project[index].clone();
+            for (other, document) in &self.documents {
+                if project.contains(other) {
+                    continue;
+                }
+                let imports_current = self.imports(other, document).contains(&current);
+                let imported_by_current =
+                    self.documents
+                        .get(&current)
+                        .is_some_and(|current_document| {
+                            self.imports(&current, current_document).contains(other)
+                        });
+                if imports_current || imported_by_current {
+                    project.push(other.clone());
+                }
+            }
+            project[index + 1..].sort();
+            index += 1;
+        }
+        project
+    }
+
+    /// Gets the locations where a label which a line of a file refers to is defined, looking in the
+    /// file itself before the labels the rest of its project exports, as the simulator does; a
+    /// correct program has exactly one.
+    pub fn find_definitions(&self, uri: &Url, label: &str, line: u32) -> Vec<Location> {
+        if let Some((module, name)) = label.split_once("::") {
+            return self
+                .get_project(uri)
+                .iter()
+                .filter(|project_uri| file_stem(project_uri) == module)
+                .flat_map(|project_uri| self.definitions_in(project_uri, name))
+                .collect();
+        }
+        let local = self
+            .documents
+            .get(uri)
+            .and_then(|document| document.analysis.find_definition(label, line));
+        match local {
+            Some(token) => vec![Location::new(uri.clone(), token.range)],
+            None => self.find_exported_definitions(uri, label),
+        }
+    }
+
+    /// Gets the locations where other files of the project of a file define and export a label.
+    fn find_exported_definitions(&self, uri: &Url, label: &str) -> Vec<Location> {
+        self.get_project(uri)
+            .iter()
+            .filter(|project_uri| *project_uri != uri)
+            .filter(|project_uri| {
+                self.documents.get(project_uri).is_some_and(|document| {
+                    document
+                        .analysis
+                        .get_exports()
+                        .iter()
+                        .any(|export| export == label)
+                })
+            })
+            .flat_map(|project_uri| self.definitions_in(project_uri, label))
+            .collect()
+    }
+
+    fn definitions_in(&self, uri: &Url, label: &str) -> Vec<Location> {
+        let Some(document) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        document
+            .analysis
+            .label_tokens(label)
+            .filter(|token| token.kind == TokenKind::LabelDefinition)
+            .map(|token| Location::new(uri.clone(), token.range))
+            .collect()
+    }
+
+    /// Gets the locations where a label is defined or used in the project of a file.
+    pub fn find_label_tokens(
+        &self,
+        uri: &Url,
+        label: &str,
+        only_definitions: bool,
+    ) -> Vec<Location> {
+        let mut locations = Vec::new();
+        for project_uri in self.get_project(uri) {
+            let Some(document) = self.documents.get(&project_uri) else {
+                continue;
+            };
+            for token in document.analysis.label_tokens(label) {
+                if !only_definitions || token.kind == TokenKind::LabelDefinition {
+                    locations.push(Location::new(project_uri.clone(), token.range));
+                }
+            }
+        }
+        locations
+    }
+
+    /// Gets every label defined in the project of a file.
+    pub fn get_labels(&self, uri: &Url) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .get_project(uri)
+            .iter()
+            .filter_map(|uri| self.documents.get(uri))
+            .flat_map(|document| document.analysis.label_definitions())
+            .filter_map(|token| token.get_label().map(|label| label.to_string()))
+            .collect();
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+
+    /// Gets the problems with a file: lines which do not parse, imports which cannot be read, labels
+    /// which are undefined, and labels which are defined more than once in the file or exported by
+    /// more than one file of its project.
+    pub fn get_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let Some(document) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        let analysis = &document.analysis;
+        let mut diagnostics = analysis.get_diagnostics().clone();
+
+        for import in analysis.get_imports() {
+            let loaded = self
+                .resolve_import(uri, &import.file)
+                .is_some_and(|imported| self.documents.contains_key(&imported));
+            if !loaded {
+                diagnostics.push(error_diagnostic(
+                    import.range,
+                    format!("could not read the imported file `{}`", import.file),
+                ));
+            }
+        }
+
+        for token in analysis.get_tokens() {
+            let Some(label) = token.get_label() else {
+                continue;
+            };
+            let line = token.range.start.line;
+            let error = match token.kind {
+                // numeric labels can be defined any number of times
+                TokenKind::LabelDefinition if !lexer::is_numeric_label(label) => {
+                    // a dot label only conflicts with those belonging to the same global label
+                    let scope = analysis.resolve_label(label, line + 1);
+                    let mut conflicts: Vec<Location> = self
+                        .definitions_in(uri, label)
+                        .into_iter()
+                        .filter(|other| {
+                            analysis.resolve_label(label, other.range.start.line + 1) == scope
+                        })
+                        .collect();
+                    if analysis.get_exports().iter().any(|export| export == label) {
+                        conflicts.extend(self.find_exported_definitions(uri, label));
+                    }
+                    let here = Location::new(uri.clone(), token.range);
+                    match conflicts.into_iter().find(|other| *other != here) {
+                        Some(other) => SimulatorError::LabelInUseError(
+                            label.to_string(),
+                            describe_location(&here),
+                            describe_location(&other),
+                        ),
+                        None => continue,
+                    }
+                }
+                TokenKind::LabelReference if self.find_definitions(uri, label, line).is_empty() => {
+                    SimulatorError::NonExistentLabelError(label.to_string())
+                }
+                _ => continue,
+            };
+            diagnostics.push(error_diagnostic(token.range, error.to_string()));
+        }
+        diagnostics
+    }
+
+    fn insert(&mut self, uri: Url, text: String, open: bool) {
+        let analysis = Analysis::new(&text, &self.instructions);
+        self.documents.insert(
+            uri,
+            Document {
+                text,
+                analysis,
+                open,
+            },
+        );
+    }
+
+    fn imports(&self, uri: &Url, document: &Document) -> Vec<Url> {
+        document
+            .analysis
+            .get_imports()
+            .iter()
+            .filter_map(|import| self.resolve_import(uri, &import.file))
+            .collect()
+    }
+
+    /// Reads the files a file imports from disk, and the files they import, unless they are
+    /// already known.
+    fn load_imports(&mut self, uri: &Url) {
+        let mut pending = vec![uri.clone()];
+        while let Some(current) = pending.pop() {
+            let Some(document) = self.documents.get(&current) else {
+                continue;
+            };
+            for imported in self.imports(&current, document) {
+                if self.documents.contains_key(&imported) {
+                    continue;
+                }
+                if let Some(text) = read(&imported) {
+                    self.insert(imported.clone(), text, false);
+                    pending.push(imported);
+                }
+            }
+        }
+    }
+
+    fn collect_imports(&self, uri: &Url, reachable: &mut HashSet<Url>) {
+        if !reachable.insert(uri.clone()) {
+            return;
+        }
+        if let Some(document) = self.documents.get(uri) {
+            for imported in self.imports(uri, document) {
+                self.collect_imports(&imported, reachable);
+            }
+        }
+    }
+}
+
+fn read(uri: &Url) -> Option<String> {
+    fs::read_to_string(uri.to_file_path().ok()?).ok()
+}
+
+pub fn file_name(uri: &Url) -> String {
+    uri.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Gets the name a file is referred to by in qualified labels, e.g. `math` for `lib/math.ez`.
+fn file_stem(uri: &Url) -> String {
+    let name = file_name(uri);
+    match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => name,
+    }
+}
+
+fn describe_location(location: &Location) -> String {
+    format!(
+        "line {} of `{}`",
+        location.range.start.line + 1,
+        file_name(&location.uri)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace() {
+        let main = Url::parse("file:///project/main.ez").unwrap();
+        let library = Url::parse("file:///project/lib/math.ez").unwrap();
+        let mut workspace = Workspace::new();
+        workspace.open(
+            library.clone(),
+            ".global square\nsquare:\n  mul $r0 $a0 $a0\n  return\nsquare:".to_string(),
+        );
+        workspace.open(
+            main.clone(),
+            "import \"lib/math.ez\"\nimport \"missing.ez\"\ncall square\njump end".to_string(),
+        );
+
+        assert_eq!(
+            workspace.resolve_import(&main, "lib/math.ez"),
+            Some(library.clone())
+        );
+        assert_eq!(
+            workspace.get_project(&main),
+            vec![main.clone(), library.clone()]
+        );
+        assert_eq!(
+            workspace.get_project(&library),
+            vec![library.clone(), main.clone()]
+        );
+        assert_eq!(workspace.find_definitions(&main, "square", 2).len(), 2);
+        assert_eq!(
+            workspace.find_definitions(&main, "math::square", 2).len(),
+            2
+        );
+        assert_eq!(
+            workspace.find_label_tokens(&library, "square", false).len(),
+            4
+        );
+        assert_eq!(workspace.get_labels(&main), vec!["square".to_string()]);
+
+        let messages: Vec<String> = workspace
+            .get_diagnostics(&main)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "could not read the imported file `missing.ez`".to_string(),
+                "label `end` does not exist".to_string(),
+            ]
+        );
+        let messages: Vec<String> = workspace
+            .get_diagnostics(&library)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(
+            messages[0],
+            "label `square` at line 2 of `math.ez` is already defined at line 5 of `math.ez`"
+        );
+        assert_eq!(messages.len(), 2);
+
+        workspace.close(&library);
+        assert!(workspace.get_document(&library).is_none());
+        workspace.close(&main);
+        assert!(workspace.get_open_documents().is_empty());
+    }
+}
@@ -8,12 +8,17 @@ extern crate lazy_static;
 extern crate tauri;
 
 use lazy_static::lazy_static;
-use rezasm_core::instructions::implementation::register_instructions;
+use rezasm_core::instructions::reference::InstructionReference;
+use rezasm_core::simulation::call_stack::Backtrace;
+use rezasm_core::simulation::explanation::Explanation;
 use rezasm_core::simulation::reader_cell::ReaderCell;
 use rezasm_web_core::{
-    get_exit_status, get_memory_bounds, get_memory_slice, get_register_names, get_register_value,
-    get_register_values, get_simulator_mut, get_word_size, initialize_simulator, is_completed,
-    load, reset, step, step_back, stop,
+    get_backtrace, get_endianness, get_exit_status, get_explanation, get_instruction_completions,
+    get_instruction_hover, get_memory_bounds, get_memory_bytes, get_memory_slice,
+    get_register_names, get_register_value, get_register_values, get_simulator_mut, get_word_size,
+    initialize_simulator, is_completed, load_linked, reset, run_steps,
+    set_check_calling_convention, set_explain, set_instruction_policy, set_rounding_mode,
+    set_trap_overflow, set_undo_enabled, step, step_back, step_out, step_over, stop,
 };
 use tauri::{Manager, Window};
 use tauri_reader::TauriReader;
@@ -54,9 +59,11 @@ fn tauri_reset() {
     reset()
 }
 
+/// Loads a program along with the files it imports, which are found relative to the path of the
+/// program if it is given, or else to the working directory.
 #[tauri::command]
-fn tauri_load(lines: &str) -> Result<(), String> {
-    load(lines)
+fn tauri_load(lines: &str, path: Option<String>) -> Result<(), String> {
+    load_linked(path.as_deref().unwrap_or_default(), lines)
 }
 
 #[tauri::command()]
@@ -64,6 +71,61 @@ fn tauri_step() -> Result<(), String> {
     step()
 }
 
+#[tauri::command()]
+fn tauri_step_over(max_steps: usize) -> Result<usize, String> {
+    step_over(max_steps)
+}
+
+#[tauri::command()]
+fn tauri_step_out(max_steps: usize) -> Result<usize, String> {
+    step_out(max_steps)
+}
+
+#[tauri::command()]
+fn tauri_run_steps(max_steps: usize) -> Result<usize, String> {
+    run_steps(max_steps)
+}
+
+#[tauri::command]
+fn tauri_set_undo_enabled(enabled: bool) {
+    set_undo_enabled(enabled)
+}
+
+#[tauri::command]
+fn tauri_set_trap_overflow(enabled: bool) {
+    set_trap_overflow(enabled)
+}
+
+#[tauri::command]
+fn tauri_set_check_calling_convention(enabled: bool) {
+    set_check_calling_convention(enabled)
+}
+
+#[tauri::command]
+fn tauri_set_rounding_mode(mode: &str) -> Result<(), String> {
+    set_rounding_mode(mode)
+}
+
+#[tauri::command]
+fn tauri_set_explain(enabled: bool) {
+    set_explain(enabled);
+}
+
+#[tauri::command]
+fn tauri_get_explanation() -> Option<Explanation> {
+    get_explanation()
+}
+
+#[tauri::command]
+fn tauri_get_backtrace() -> Backtrace {
+    get_backtrace()
+}
+
+#[tauri::command]
+fn tauri_set_instruction_policy(policy: &str) -> Result<(), String> {
+    set_instruction_policy(policy)
+}
+
 #[tauri::command()]
 fn tauri_step_back() -> Result<(), String> {
     step_back()
@@ -104,11 +166,31 @@ fn tauri_get_memory_slice(address: usize, length: usize) -> Result<Vec<i64>, Str
     get_memory_slice(address, length)
 }
 
+#[tauri::command]
+fn tauri_get_memory_bytes(address: usize, length: usize) -> Result<Vec<u8>, String> {
+    get_memory_bytes(address, length)
+}
+
 #[tauri::command]
 fn tauri_get_word_size() -> usize {
     get_word_size()
 }
 
+#[tauri::command]
+fn tauri_get_endianness() -> String {
+    get_endianness()
+}
+
+#[tauri::command]
+fn tauri_get_instruction_completions(prefix: &str) -> Vec<InstructionReference> {
+    get_instruction_completions(prefix)
+}
+
+#[tauri::command]
+fn tauri_get_instruction_hover(name: &str) -> Option<String> {
+    get_instruction_hover(name)
+}
+
 #[tauri::command]
 fn tauri_receive_input(data: &str) {
     let mut simulator = get_simulator_mut();
@@ -118,7 +200,6 @@ fn tauri_receive_input(data: &str) {
 }
 
 fn main() {
-    register_instructions();
     initialize_simulator(
         Some(ReaderCell::new(TauriReader::new())),
         Some(Box::new(TauriWriter::new())),
@@ -130,6 +211,17 @@ fn main() {
             tauri_load,
             tauri_reset,
             tauri_step,
+            tauri_run_steps,
+            tauri_step_over,
+            tauri_step_out,
+            tauri_set_undo_enabled,
+            tauri_set_trap_overflow,
+            tauri_set_check_calling_convention,
+            tauri_set_rounding_mode,
+            tauri_set_explain,
+            tauri_get_explanation,
+            tauri_get_backtrace,
+            tauri_set_instruction_policy,
             tauri_step_back,
             tauri_stop,
             tauri_is_completed,
@@ -139,7 +231,11 @@ fn main() {
             tauri_get_register_values,
             tauri_get_memory_bounds,
             tauri_get_memory_slice,
+            tauri_get_memory_bytes,
             tauri_get_word_size,
+            tauri_get_endianness,
+            tauri_get_instruction_completions,
+            tauri_get_instruction_hover,
             tauri_receive_input,
         ])
         .run(tauri::generate_context!())
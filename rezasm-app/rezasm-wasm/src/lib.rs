@@ -6,14 +6,16 @@ extern crate serde_wasm_bindgen;
 extern crate wasm_bindgen;
 
 use crate::wasm_writer::WasmWriter;
-use rezasm_core::instructions::implementation::register_instructions;
 use rezasm_core::simulation::reader::DummyReader;
 use rezasm_core::simulation::reader_cell::ReaderCell;
 use rezasm_core::util::as_any::AsAny;
 use rezasm_web_core::{
-    get_exit_status, get_memory_bounds, get_memory_slice, get_register_names, get_register_value,
-    get_register_values, get_simulator_mut, get_word_size, initialize_simulator, is_completed,
-    load, reset, step, step_back, stop,
+    get_backtrace, get_endianness, get_exit_status, get_explanation, get_instruction_completions,
+    get_instruction_hover, get_memory_bounds, get_memory_bytes, get_memory_slice,
+    get_register_names, get_register_value, get_register_values, get_simulator_mut, get_word_size,
+    initialize_simulator, is_completed, load, reset, run_steps, set_check_calling_convention,
+    set_explain, set_instruction_policy, set_rounding_mode, set_trap_overflow, set_undo_enabled,
+    step, step_back, step_out, step_over, stop,
 };
 use wasm_bindgen::prelude::*;
 
@@ -37,6 +39,61 @@ pub fn wasm_step() -> Result<(), String> {
     step()
 }
 
+#[wasm_bindgen]
+pub fn wasm_step_over(max_steps: usize) -> Result<usize, String> {
+    step_over(max_steps)
+}
+
+#[wasm_bindgen]
+pub fn wasm_step_out(max_steps: usize) -> Result<usize, String> {
+    step_out(max_steps)
+}
+
+#[wasm_bindgen]
+pub fn wasm_run_steps(max_steps: usize) -> Result<usize, String> {
+    run_steps(max_steps)
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_undo_enabled(enabled: bool) {
+    set_undo_enabled(enabled)
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_trap_overflow(enabled: bool) {
+    set_trap_overflow(enabled)
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_check_calling_convention(enabled: bool) {
+    set_check_calling_convention(enabled)
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_rounding_mode(mode: &str) -> Result<(), String> {
+    set_rounding_mode(mode)
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_explain(enabled: bool) {
+    set_explain(enabled);
+}
+
+#[wasm_bindgen]
+pub fn wasm_get_explanation() -> JsValue {
+    serde_wasm_bindgen::to_value(&get_explanation()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn wasm_get_backtrace() -> JsValue {
+    serde_wasm_bindgen::to_value(&get_backtrace()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn wasm_set_instruction_policy(policy: &str) -> Result<(), String> {
+    set_instruction_policy(policy)
+}
+
 #[wasm_bindgen]
 pub fn wasm_step_back() -> Result<(), String> {
     step_back()
@@ -77,11 +134,31 @@ pub fn wasm_get_memory_slice(address: usize, length: usize) -> Result<Vec<i64>,
     get_memory_slice(address, length)
 }
 
+#[wasm_bindgen]
+pub fn wasm_get_memory_bytes(address: usize, length: usize) -> Result<Vec<u8>, String> {
+    get_memory_bytes(address, length)
+}
+
 #[wasm_bindgen]
 pub fn wasm_get_word_size() -> usize {
     get_word_size()
 }
 
+#[wasm_bindgen]
+pub fn wasm_get_endianness() -> String {
+    get_endianness()
+}
+
+#[wasm_bindgen]
+pub fn wasm_get_instruction_completions(prefix: &str) -> JsValue {
+    serde_wasm_bindgen::to_value(&get_instruction_completions(prefix)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn wasm_get_instruction_hover(name: &str) -> Option<String> {
+    get_instruction_hover(name)
+}
+
 #[wasm_bindgen]
 pub fn wasm_receive_input(data: &str) {
     let mut simulator = get_simulator_mut();
@@ -93,7 +170,6 @@ pub fn wasm_receive_input(data: &str) {
 
 #[wasm_bindgen(start)]
 pub fn wasm_initialize_backend() {
-    register_instructions();
     initialize_simulator(
         Some(ReaderCell::new(DummyReader::new())),
         Some(Box::new(WasmWriter::new())),
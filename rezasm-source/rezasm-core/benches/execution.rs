@@ -0,0 +1,91 @@
+//! Measures how many instructions per second the simulator runs.
+//!
+//! Run with `cargo bench -p rezasm-core`; pass a number of iterations to change the size of the
+//! workload, e.g. `cargo bench -p rezasm-core -- 5000000`.
+
+use std::time::{Duration, Instant};
+
+use rezasm_core::parser::lexer::parse_lines;
+use rezasm_core::simulation::memory;
+use rezasm_core::simulation::memory::Memory;
+use rezasm_core::simulation::memory_backend::MemoryBackendKind;
+use rezasm_core::simulation::reader::DummyReader;
+use rezasm_core::simulation::reader_cell::ReaderCell;
+use rezasm_core::simulation::simulator::Simulator;
+use rezasm_core::simulation::writer::DummyWriter;
+use rezasm_core::util::endianness::DEFAULT_ENDIANNESS;
+use rezasm_core::util::word_size::DEFAULT_WORD_SIZE;
+
+const DEFAULT_ITERATIONS: i64 = 50_000;
+
+/// A loop mixing arithmetic, memory accesses, and branches.
+fn workload(iterations: i64) -> String {
+    format!(
+        "
+        alloc $s0 64
+        move $t0 0
+        move $t1 1
+        move $s1 {iterations}
+        loop:
+        add $t2 $t0 $t1
+        move $t0 $t1
+        move $t1 $t2
+        store $t2 $s0
+        load $t3 $s0
+        push $t3
+        pop $t3
+        sub $s1 $s1 1
+        bgt $s1 0 loop"
+    )
+}
+
+fn measure(iterations: i64, backend: MemoryBackendKind, can_undo: bool) -> (usize, Duration) {
+    let memory = Memory::new_custom(
+        &DEFAULT_WORD_SIZE,
+        memory::DEFAULT_MEMORY_WORDS,
+        backend,
+        DEFAULT_ENDIANNESS,
+    );
+    let mut simulator = Simulator::new_with_memory(
+        memory,
+        ReaderCell::new(DummyReader::new()),
+        Box::new(DummyWriter::new()),
+    );
+    simulator.set_can_undo(can_undo);
+    let lines = parse_lines(
+        &workload(iterations),
+        simulator.get_word_size(),
+        simulator.get_instruction_set(),
+    )
+    .unwrap();
+    simulator.add_lines(lines, "bench".to_string()).unwrap();
+
+    let start = Instant::now();
+    let steps = simulator.run(usize::MAX).unwrap();
+    (steps, start.elapsed())
+}
+
+fn main() {
+    let iterations = std::env::args()
+        .skip(1)
+        .find_map(|argument| argument.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+
+    println!(
+        "{:<10}{:<8}{:>14}{:>12}{:>16}",
+        "memory", "undo", "instructions", "seconds", "per second"
+    );
+    for backend in [MemoryBackendKind::Dense, MemoryBackendKind::Sparse] {
+        for can_undo in [true, false] {
+            let (steps, elapsed) = measure(iterations, backend, can_undo);
+            println!(
+                "{:<10}{:<8}{:>14}{:>12.3}{:>16.0}",
+                format!("{:?}", backend).to_lowercase(),
+                if can_undo { "on" } else { "off" },
+                steps,
+                elapsed.as_secs_f64(),
+                steps as f64 / elapsed.as_secs_f64()
+            );
+        }
+    }
+}
@@ -4,13 +4,24 @@ use crate::parser::lexer::Token;
 use crate::util::error::{InternalError, ParserError};
 use crate::util::raw_data::RawData;
 use crate::util::word_size::WordSize;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ArgumentType {
     InputOutput(InputOutputTarget),
     Input(InputTarget),
 }
 
+impl Display for ArgumentType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentType::InputOutput(target) => target.fmt(f),
+            ArgumentType::Input(target) => target.fmt(f),
+        }
+    }
+}
+
 impl TryInto<InputOutputTarget> for ArgumentType {
     type Error = InternalError;
 
@@ -33,6 +44,28 @@ impl TryInto<InputTarget> for ArgumentType {
     }
 }
 
+impl TryFrom<&ArgumentType> for InputOutputTarget {
+    type Error = InternalError;
+
+    fn try_from(argument: &ArgumentType) -> Result<InputOutputTarget, Self::Error> {
+        match argument {
+            ArgumentType::InputOutput(target) => Ok(*target),
+            ArgumentType::Input(_) => Err(InternalError::MismatchedTryIntoError),
+        }
+    }
+}
+
+impl TryFrom<&ArgumentType> for InputTarget {
+    type Error = InternalError;
+
+    fn try_from(argument: &ArgumentType) -> Result<InputTarget, Self::Error> {
+        match argument {
+            ArgumentType::InputOutput(_) => Err(InternalError::MismatchedTryIntoError),
+            ArgumentType::Input(target) => Ok(target.clone()),
+        }
+    }
+}
+
 impl Token {
     pub fn get_input_target(&self, word_size: &WordSize) -> Result<ArgumentType, ParserError> {
         Ok(ArgumentType::Input(match self {
@@ -41,6 +74,9 @@ impl Token {
                 InputTarget::new_immediate(RawData::from_float(f.clone(), word_size))
             }
             Token::NumericalImmediate(crate::parser::lexer::EZNumber::Integer(i)) => {
+                if !word_size.can_hold(*i) {
+                    return Err(ParserError::ImmediateRangeError(*i, word_size.value()));
+                }
                 InputTarget::new_immediate(RawData::from_int(i.clone(), word_size))
             }
             Token::StringImmediate(s) => InputTarget::new_string(s),
@@ -0,0 +1,104 @@
+//! Everything needed to define and register instructions from outside of this crate.
+//!
+//! Instructions are defined with the `instruction!` macro and added to the `InstructionSet` of a
+//! simulator, one at a time or as an `InstructionGroup`. Programs parsed for that simulator can
+//! then use them like any other instruction. The
+//! helpers in this module build the transformations for the most common results, so that an
+//! instruction body rarely needs to construct a `Transformation` itself. Instructions which only
+//! give a value or decide whether to branch can use the shorter forms of `instruction!` instead,
+//! which also let them run without building transformations while undo is disabled.
+//!
+//! # Example
+//!
+//! ```
+//! use rezasm_core::instruction;
+//! use rezasm_core::instructions::extension::*;
+//! use rezasm_core::parser::lexer::parse_lines;
+//!
+//! let clamp = instruction!(clamp, |simulator: Simulator,
+//!                                  output: InputOutputTarget,
+//!                                  input: InputTarget,
+//!                                  low: InputTarget,
+//!                                  high: InputTarget| {
+//!     let value = input.get(simulator)?.int_value();
+//!     let low = low.get(simulator)?.int_value();
+//!     let high = high.get(simulator)?.int_value();
+//!     write_int(simulator, output, value.max(low).min(high))
+//! });
+//!
+//! let mut simulator = Simulator::new();
+//! simulator.get_instruction_set_mut().add_instruction(clamp).unwrap();
+//! let lines = parse_lines(
+//!     "clamp $t0 15 0 10",
+//!     simulator.get_word_size(),
+//!     simulator.get_instruction_set(),
+//! )
+//! .unwrap();
+//! simulator.add_lines(lines, "".to_string()).unwrap();
+//! simulator.run(usize::MAX).unwrap();
+//! let t0 = simulator.get_registers().get_register("$t0").unwrap();
+//! assert_eq!(t0.get_data().int_value(), 10);
+//! ```
+
+pub use crate::instructions::argument_type::ArgumentType;
+pub use crate::instructions::instruction::Instruction;
+pub use crate::instructions::instruction_set::{InstructionGroup, InstructionSet};
+pub use crate::instructions::targets::input_output_target::InputOutputTarget;
+pub use crate::instructions::targets::input_target::{Input, InputTarget};
+pub use crate::instructions::targets::output_target::Output;
+pub use crate::simulation::registry;
+pub use crate::simulation::simulator::Simulator;
+pub use crate::simulation::transform::transformable::Transformable;
+pub use crate::simulation::transform::transformation::Transformation;
+pub use crate::simulation::transform::transformation_sequence::TransformationSequence;
+pub use crate::util::error::{ParserError, SimulatorError};
+pub use crate::util::raw_data::RawData;
+pub use crate::util::word_size::WordSize;
+
+/// Creates the transformations which store `data` in `output`.
+pub fn write_output(
+    simulator: &Simulator,
+    output: InputOutputTarget,
+    data: RawData,
+) -> Result<TransformationSequence, SimulatorError> {
+    Ok(TransformationSequence::new_single(Transformation::new(
+        Transformable::InputOutputTransformable(output),
+        output.get(simulator)?,
+        data,
+    )))
+}
+
+/// Creates the transformations which store an integer in `output`, truncated to the word size.
+pub fn write_int(
+    simulator: &Simulator,
+    output: InputOutputTarget,
+    value: i64,
+) -> Result<TransformationSequence, SimulatorError> {
+    write_output(
+        simulator,
+        output,
+        RawData::from_int(value, simulator.get_word_size()),
+    )
+}
+
+/// Creates the transformations which store a float in `output`, at the precision of the word size.
+pub fn write_float(
+    simulator: &Simulator,
+    output: InputOutputTarget,
+    value: f64,
+) -> Result<TransformationSequence, SimulatorError> {
+    write_output(
+        simulator,
+        output,
+        RawData::from_float(value, simulator.get_word_size()),
+    )
+}
+
+/// Creates the transformations which continue the program at `target`, e.g. a label.
+pub fn jump(
+    simulator: &Simulator,
+    target: InputTarget,
+) -> Result<TransformationSequence, SimulatorError> {
+    let pc = InputOutputTarget::RegisterInputOutput(registry::PC_NUMBER);
+    write_output(simulator, pc, target.get(simulator)?)
+}
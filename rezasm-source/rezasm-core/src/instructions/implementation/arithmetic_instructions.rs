@@ -2,16 +2,14 @@ use lazy_static::lazy_static;
 
 use crate::instructions::instruction::instruction;
 use crate::instructions::instruction::Instruction;
-use crate::instructions::instruction_registry::register_instruction;
+use crate::instructions::instruction_set::InstructionGroup;
 
 use crate::instructions::targets::input_output_target::InputOutputTarget;
 use crate::instructions::targets::input_target::Input;
 use crate::instructions::targets::input_target::InputTarget;
 use crate::simulation::register::Register;
 use crate::simulation::registry;
-use crate::simulation::transform::transformable::Transformable;
-use crate::simulation::transform::transformation::Transformation;
-use crate::simulation::transform::transformation_sequence::TransformationSequence;
+use crate::simulation::simulator::Simulator;
 use crate::util::error::SimulatorError;
 use crate::util::raw_data::RawData;
 use crate::util::word_size::WordSize;
@@ -21,42 +19,50 @@ lazy_static! {
         instruction!(add, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            let k = value1 + value2;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            let k = signed_result(
+                simulator,
+                "add",
+                value1,
+                value2,
+                value1 as i128 + value2 as i128,
+            )?;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SUB: Instruction =
         instruction!(sub, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            let k = value1 - value2;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            let k = signed_result(
+                simulator,
+                "sub",
+                value1,
+                value2,
+                value1 as i128 - value2 as i128,
+            )?;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref MUL: Instruction =
         instruction!(mul, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            let result: i128 = value1 as i128 * value2 as i128;
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            let k = signed_result(
+                simulator,
+                "mul",
+                value1,
+                value2,
+                value1 as i128 * value2 as i128,
+            )?;
 
-            let word_size = simulator.get_word_size().clone();
+            let word_size = *simulator.get_word_size();
 
             let hi: &mut Register = simulator
                 .get_registers_mut()
@@ -65,6 +71,7 @@ lazy_static! {
 
             hi.set_data(RawData::from_int(
                 match word_size {
+                    WordSize::Two => (i128::abs(full) >> 16) as i64,
                     WordSize::Four => (i128::abs(full) >> 32) as i64,
                     WordSize::Eight => (i128::abs(full) >> 64) as i64,
                 },
@@ -76,118 +83,181 @@ lazy_static! {
                 .get_register_mut(&registry::LO.to_string())?;
             lo.set_data(RawData::from_int(full as i64, &word_size));
 
-            let k = value1 * value2;
-
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref DIV: Instruction =
         instruction!(div, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             if value2 == 0 {
                 return Err(SimulatorError::DivideByZeroError);
             } else {
-                let k = value1 / value2;
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    RawData::from_int(k, simulator.get_word_size()),
-                );
-                return Ok(TransformationSequence::new_single(transformation));
+                let k = signed_result(
+                    simulator,
+                    "div",
+                    value1,
+                    value2,
+                    value1 as i128 / value2 as i128,
+                )?;
+                return Ok(RawData::from_int(k, simulator.get_word_size()));
             }
         });
+    pub static ref ADDU: Instruction =
+        instruction!(addu, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            let k = unsigned_result(
+                simulator,
+                "addu",
+                value1,
+                value2,
+                value1 as u128 + value2 as u128,
+            )?;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref SUBU: Instruction =
+        instruction!(subu, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            let k = unsigned_result(
+                simulator,
+                "subu",
+                value1,
+                value2,
+                (value1 as u128).wrapping_sub(value2 as u128),
+            )?;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref MULU: Instruction =
+        instruction!(mulu, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            let k = unsigned_result(
+                simulator,
+                "mulu",
+                value1,
+                value2,
+                value1 as u128 * value2 as u128,
+            )?;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref DIVU: Instruction =
+        instruction!(divu, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            let k = value1
+                .checked_div(value2)
+                .ok_or(SimulatorError::DivideByZeroError)? as i64;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref MODU: Instruction =
+        instruction!(modu, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            let k = value1
+                .checked_rem(value2)
+                .ok_or(SimulatorError::DivideByZeroError)? as i64;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref MULH: Instruction =
+        instruction!(mulh, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            let bits = simulator.get_word_size().bits();
+            let k = ((value1 as i128 * value2 as i128) >> bits) as i64;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref MULHU: Instruction =
+        instruction!(mulhu, |simulator: Simulator,
+                             output: InputOutputTarget,
+                             input1: InputTarget,
+                             input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            let bits = simulator.get_word_size().bits();
+            let k = ((value1 as u128 * value2 as u128) >> bits) as i64;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
     pub static ref AND: Instruction =
         instruction!(and, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = value1 & value2;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref OR: Instruction =
         instruction!(or, |simulator: Simulator,
                           output: InputOutputTarget,
                           input1: InputTarget,
-                          input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                          input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = value1 | value2;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref XOR: Instruction =
         instruction!(xor, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = value1 ^ value2;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref NOT: Instruction =
         instruction!(not, |simulator: Simulator,
                            output: InputOutputTarget,
-                           input1: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
+                           input1: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
             let k = !value1;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref MOD: Instruction =
         instruction!(_mod, |simulator: Simulator,
                             output: InputOutputTarget,
                             input1: InputTarget,
-                            input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             if value2 == 0 {
                 return Err(SimulatorError::DivideByZeroError);
             } else {
-                let k = value1 % value2;
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    RawData::from_int(k, simulator.get_word_size()),
-                );
-                return Ok(TransformationSequence::new_single(transformation));
+                let k = (value1 as i128 % value2 as i128) as i64;
+                return Ok(RawData::from_int(k, simulator.get_word_size()));
             }
         });
     pub static ref SLL: Instruction =
         instruction!(sll, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value = input1.get(&simulator)?.int_value();
-            let shift = input2.get(&simulator)?.int_value() as u64;
+                           input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.int_value();
+            let shift = input2.get(simulator)?.int_value() as u64;
             let word_size = simulator.get_word_size();
 
             let k = if shift >= (word_size.value() as u64 * 8) {
@@ -196,45 +266,36 @@ lazy_static! {
                 value << shift
             };
 
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SRL: Instruction =
         instruction!(srl, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value = input1.get(&simulator)?.int_value();
-            let shift = input2.get(&simulator)?.int_value() as u64;
+                           input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.int_value();
+            let shift = input2.get(simulator)?.int_value() as u64;
             let word_size = simulator.get_word_size();
 
             let k = if shift >= (word_size.value() as u64 * 8) {
                 0
             } else {
                 match word_size {
+                    WordSize::Two => (value as u16 >> shift) as i64,
                     WordSize::Four => (value as u32 >> shift) as i64,
                     WordSize::Eight => (value as u64 >> shift) as i64,
                 }
             };
 
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SRA: Instruction =
         instruction!(sra, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value = input1.get(&simulator)?.int_value();
-            let shift = input2.get(&simulator)?.int_value() as u64;
+                           input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.int_value();
+            let shift = input2.get(simulator)?.int_value() as u64;
             let word_size = simulator.get_word_size();
 
             let k = if shift >= (word_size.value() as u64 * 8) {
@@ -243,50 +304,168 @@ lazy_static! {
                 value >> shift
             };
 
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref INC: Instruction =
-        instruction!(inc, |simulator: Simulator, output: InputOutputTarget| {
-            let value = output.get(&simulator)?.int_value();
-            let k = value + 1;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+        instruction!(inc, |simulator: Simulator, output: InputOutputTarget| -> output {
+            let value = output.get(simulator)?.int_value();
+            let k = signed_result(simulator, "inc", value, 1, value as i128 + 1)?;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref DEC: Instruction =
-        instruction!(dec, |simulator: Simulator, output: InputOutputTarget| {
-            let value = output.get(&simulator)?.int_value();
-            let k = value - 1;
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+        instruction!(dec, |simulator: Simulator, output: InputOutputTarget| -> output {
+            let value = output.get(simulator)?.int_value();
+            let k = signed_result(simulator, "dec", value, 1, value as i128 - 1)?;
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
 }
 
-pub fn register_instructions() {
-    register_instruction(&ADD);
-    register_instruction(&SUB);
-    register_instruction(&MUL);
-    register_instruction(&DIV);
-    register_instruction(&AND);
-    register_instruction(&OR);
-    register_instruction(&XOR);
-    register_instruction(&MOD);
-    register_instruction(&NOT);
-    register_instruction(&SLL);
-    register_instruction(&SRL);
-    register_instruction(&SRA);
-    register_instruction(&INC);
-    register_instruction(&DEC);
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("arithmetic")
+        .with(&ADD.describe(
+            "Adds two integers.",
+            "output = input1 + input2",
+            "add $t0 $t1 5",
+        ))
+        .with(&SUB.describe(
+            "Subtracts one integer from another.",
+            "output = input1 - input2",
+            "sub $t0 $t1 $t2",
+        ))
+        .with(&MUL.describe(
+            "Multiplies two integers, also storing the full product in `$hi` and `$lo`.",
+            "output = input1 * input2",
+            "mul $t0 $t1 3",
+        ))
+        .with(&DIV.describe(
+            "Divides one integer by another, rounding towards zero.",
+            "output = input1 / input2",
+            "div $t0 $t1 2",
+        ))
+        .with(&AND.describe(
+            "Takes the bitwise and of two integers.",
+            "output = input1 & input2",
+            "and $t0 $t1 0xff",
+        ))
+        .with(&OR.describe(
+            "Takes the bitwise or of two integers.",
+            "output = input1 | input2",
+            "or $t0 $t1 1",
+        ))
+        .with(&XOR.describe(
+            "Takes the bitwise exclusive or of two integers.",
+            "output = input1 ^ input2",
+            "xor $t0 $t1 $t2",
+        ))
+        .with(&MOD.describe(
+            "Takes the remainder of dividing one integer by another, with the sign of the dividend.",
+            "output = input1 % input2",
+            "mod $t0 $t1 10",
+        ))
+        .with(&NOT.describe(
+            "Inverts every bit of an integer.",
+            "output = ~input1",
+            "not $t0 $t1",
+        ))
+        .with(&SLL.describe(
+            "Shifts an integer left, filling with zeros.",
+            "output = input1 << input2",
+            "sll $t0 $t1 2",
+        ))
+        .with(&SRL.describe(
+            "Shifts an integer right, filling with zeros.",
+            "output = input1 >>> input2",
+            "srl $t0 $t1 2",
+        ))
+        .with(&SRA.describe(
+            "Shifts an integer right, filling with copies of the sign bit.",
+            "output = input1 >> input2",
+            "sra $t0 $t1 2",
+        ))
+        .with(&INC.describe(
+            "Adds one to an integer.",
+            "output = output + 1",
+            "inc $t0",
+        ))
+        .with(&DEC.describe(
+            "Subtracts one from an integer.",
+            "output = output - 1",
+            "dec $t0",
+        ))
+        .with(&ADDU.describe(
+            "Adds two unsigned integers.",
+            "output = input1 + input2 (unsigned)",
+            "addu $t0 $t1 $t2",
+        ))
+        .with(&SUBU.describe(
+            "Subtracts one unsigned integer from another.",
+            "output = input1 - input2 (unsigned)",
+            "subu $t0 $t1 $t2",
+        ))
+        .with(&MULU.describe(
+            "Multiplies two unsigned integers.",
+            "output = input1 * input2 (unsigned)",
+            "mulu $t0 $t1 $t2",
+        ))
+        .with(&DIVU.describe(
+            "Divides one unsigned integer by another.",
+            "output = input1 / input2 (unsigned)",
+            "divu $t0 $t1 $t2",
+        ))
+        .with(&MODU.describe(
+            "Takes the remainder of dividing one unsigned integer by another.",
+            "output = input1 % input2 (unsigned)",
+            "modu $t0 $t1 $t2",
+        ))
+        .with(&MULH.describe(
+            "Multiplies two integers and keeps the upper word of the full product.",
+            "output = (input1 * input2) >> word bits",
+            "mulh $t0 $t1 $t2",
+        ))
+        .with(&MULHU.describe(
+            "Multiplies two unsigned integers and keeps the upper word of the full product.",
+            "output = (input1 * input2) >> word bits (unsigned)",
+            "mulhu $t0 $t1 $t2",
+        ))
+}
+
+/// Gets the result of a signed operation as it is stored in a word.
+///
+/// A result which does not fit in a word is truncated, unless the simulator traps on overflow.
+fn signed_result(
+    simulator: &Simulator,
+    name: &str,
+    value1: i64,
+    value2: i64,
+    result: i128,
+) -> Result<i64, SimulatorError> {
+    if simulator.traps_overflow() && !simulator.get_word_size().fits_signed(result) {
+        return Err(SimulatorError::IntegerOverflow(
+            name.to_string(),
+            value1 as i128,
+            value2 as i128,
+        ));
+    }
+    Ok(result as i64)
+}
+
+/// Gets the result of an unsigned operation as it is stored in a word.
+///
+/// A result which does not fit in a word, including one which borrowed below zero, is truncated,
+/// unless the simulator traps on overflow.
+fn unsigned_result(
+    simulator: &Simulator,
+    name: &str,
+    value1: u64,
+    value2: u64,
+    result: u128,
+) -> Result<i64, SimulatorError> {
+    if simulator.traps_overflow() && !simulator.get_word_size().fits_unsigned(result) {
+        return Err(SimulatorError::IntegerOverflow(
+            name.to_string(),
+            value1 as i128,
+            value2 as i128,
+        ));
+    }
+    Ok(result as i64)
 }
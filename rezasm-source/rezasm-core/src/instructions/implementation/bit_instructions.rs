@@ -0,0 +1,217 @@
+use lazy_static::lazy_static;
+
+use crate::instructions::instruction::instruction;
+use crate::instructions::instruction::Instruction;
+use crate::instructions::instruction_set::InstructionGroup;
+
+use crate::instructions::targets::input_output_target::InputOutputTarget;
+use crate::instructions::targets::input_target::Input;
+use crate::instructions::targets::input_target::InputTarget;
+use crate::simulation::simulator::Simulator;
+use crate::util::error::SimulatorError;
+use crate::util::raw_data::RawData;
+
+lazy_static! {
+    pub static ref ROL: Instruction =
+        instruction!(rol, |simulator: Simulator,
+                           output: InputOutputTarget,
+                           input1: InputTarget,
+                           input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bits = simulator.get_word_size().bits();
+            let shift = input2.get(simulator)?.int_value().rem_euclid(bits as i64) as u32;
+            let k = rotate_left(value, shift, bits);
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref ROR: Instruction =
+        instruction!(ror, |simulator: Simulator,
+                           output: InputOutputTarget,
+                           input1: InputTarget,
+                           input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bits = simulator.get_word_size().bits();
+            let shift = input2.get(simulator)?.int_value().rem_euclid(bits as i64) as u32;
+            let k = rotate_left(value, (bits - shift) % bits, bits);
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref POPCNT: Instruction =
+        instruction!(popcnt, |simulator: Simulator,
+                              output: InputOutputTarget,
+                              input1: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let k = value.count_ones();
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref CLZ: Instruction =
+        instruction!(clz, |simulator: Simulator,
+                           output: InputOutputTarget,
+                           input1: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bits = simulator.get_word_size().bits();
+            let k = value.leading_zeros() - (64 - bits);
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref CTZ: Instruction =
+        instruction!(ctz, |simulator: Simulator,
+                           output: InputOutputTarget,
+                           input1: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bits = simulator.get_word_size().bits();
+            let k = value.trailing_zeros().min(bits);
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref BEXT: Instruction =
+        instruction!(bext, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            start: InputTarget,
+                            length: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let start = start.get(simulator)?.int_value();
+            let length = length.get(simulator)?.int_value();
+            let field = bit_field(simulator, start, length)?;
+            let k = (value & field).checked_shr(start as u32).unwrap_or(0);
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref BINS: Instruction =
+        instruction!(bins, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            start: InputTarget,
+                            length: InputTarget| -> output {
+            let current = output.get(simulator)?.unsigned_value();
+            let value = input1.get(simulator)?.unsigned_value();
+            let start = start.get(simulator)?.int_value();
+            let length = length.get(simulator)?.int_value();
+            let field = bit_field(simulator, start, length)?;
+            let k = (current & !field) | (value.checked_shl(start as u32).unwrap_or(0) & field);
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref BSWAP: Instruction =
+        instruction!(bswap, |simulator: Simulator,
+                             output: InputOutputTarget,
+                             input1: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bits = simulator.get_word_size().bits();
+            let k = value.swap_bytes() >> (64 - bits);
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref BSET: Instruction =
+        instruction!(bset, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bit = bit_field(simulator, input2.get(simulator)?.int_value(), 1)?;
+            let k = value | bit;
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref BCLR: Instruction =
+        instruction!(bclr, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bit = bit_field(simulator, input2.get(simulator)?.int_value(), 1)?;
+            let k = value & !bit;
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+    pub static ref BTST: Instruction =
+        instruction!(btst, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value = input1.get(simulator)?.unsigned_value();
+            let bit = bit_field(simulator, input2.get(simulator)?.int_value(), 1)?;
+            let k = match value & bit {
+                0 => 0,
+                _ => 1,
+            };
+            return Ok(RawData::from_int(k as i64, simulator.get_word_size()));
+        });
+}
+
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("bit")
+        .with(&ROL.describe(
+            "Rotates the bits of a word left.",
+            "output = input1 rotated left by input2",
+            "rol $t0 $t1 8",
+        ))
+        .with(&ROR.describe(
+            "Rotates the bits of a word right.",
+            "output = input1 rotated right by input2",
+            "ror $t0 $t1 8",
+        ))
+        .with(&POPCNT.describe(
+            "Counts the bits of a word which are set.",
+            "output = number of ones in input1",
+            "popcnt $t0 $t1",
+        ))
+        .with(&CLZ.describe(
+            "Counts the zero bits above the most significant set bit of a word.",
+            "output = leading zeros of input1",
+            "clz $t0 $t1",
+        ))
+        .with(&CTZ.describe(
+            "Counts the zero bits below the least significant set bit of a word.",
+            "output = trailing zeros of input1",
+            "ctz $t0 $t1",
+        ))
+        .with(&BEXT.describe(
+            "Extracts a field of bits from a word.",
+            "output = (input1 >> start) & ((1 << length) - 1)",
+            "bext $t0 $t1 4 8",
+        ))
+        .with(&BINS.describe(
+            "Inserts the low bits of a value into a field of bits of the output.",
+            "output[start .. start + length] = input1",
+            "bins $t0 $t1 4 8",
+        ))
+        .with(&BSWAP.describe(
+            "Reverses the order of the bytes of a word.",
+            "output = input1 with its bytes reversed",
+            "bswap $t0 $t1",
+        ))
+        .with(&BSET.describe(
+            "Sets one bit of a word.",
+            "output = input1 | (1 << input2)",
+            "bset $t0 $t1 3",
+        ))
+        .with(&BCLR.describe(
+            "Clears one bit of a word.",
+            "output = input1 & ~(1 << input2)",
+            "bclr $t0 $t1 3",
+        ))
+        .with(&BTST.describe(
+            "Tests whether one bit of a word is set.",
+            "output = (input1 >> input2) & 1",
+            "btst $t0 $t1 3",
+        ))
+}
+
+/// Rotates the lowest `bits` bits of a value left.
+fn rotate_left(value: u64, shift: u32, bits: u32) -> u64 {
+    if shift == 0 {
+        return value;
+    }
+    let mask = u64::MAX >> (64 - bits);
+    ((value << shift) | (value >> (bits - shift))) & mask
+}
+
+/// Gets a mask of the `length` bits starting at bit `start`, counting from the least significant
+/// bit, or fails if they do not all fit in a word.
+fn bit_field(simulator: &Simulator, start: i64, length: i64) -> Result<u64, SimulatorError> {
+    let bits = simulator.get_word_size().bits() as i64;
+    if start < 0 || length < 0 || start + length > bits {
+        return Err(SimulatorError::InvalidBitFieldError(
+            start,
+            length,
+            bits as u32,
+        ));
+    }
+    if length == 0 {
+        return Ok(0);
+    }
+    Ok((u64::MAX >> (64 - length)) << start)
+}
@@ -1,146 +1,127 @@
 use lazy_static::lazy_static;
 
-use crate::simulation::registry;
-
 use crate::instructions::instruction::instruction;
 use crate::instructions::instruction::Instruction;
-use crate::instructions::instruction_registry::register_instruction;
+use crate::instructions::instruction_set::InstructionGroup;
 
-use crate::instructions::targets::input_output_target::InputOutputTarget;
 use crate::instructions::targets::input_target::Input;
 use crate::instructions::targets::input_target::InputTarget;
-use crate::simulation::transform::transformable::Transformable;
-use crate::simulation::transform::transformation::Transformation;
-use crate::simulation::transform::transformation_sequence::TransformationSequence;
 
 lazy_static! {
     pub static ref BEQ: Instruction =
         instruction!(beq, |simulator: Simulator,
                            input1: InputTarget,
                            input2: InputTarget,
-                           label: InputTarget| {
-            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
-            let output = InputOutputTarget::RegisterInputOutput(pc_num);
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            if value1 == value2 {
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    label.get(simulator)?,
-                );
-                return Ok(TransformationSequence::new_single(transformation));
-            } else {
-                return Ok(TransformationSequence::new_empty());
-            }
+                           label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            return Ok(value1 == value2);
         });
     pub static ref BNE: Instruction =
         instruction!(bne, |simulator: Simulator,
                            input1: InputTarget,
                            input2: InputTarget,
-                           label: InputTarget| {
-            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
-            let output = InputOutputTarget::RegisterInputOutput(pc_num);
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            if value1 != value2 {
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    label.get(simulator)?,
-                );
-                return Ok(TransformationSequence::new_single(transformation));
-            } else {
-                return Ok(TransformationSequence::new_empty());
-            }
+                           label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            return Ok(value1 != value2);
         });
     pub static ref BLT: Instruction =
         instruction!(blt, |simulator: Simulator,
                            input1: InputTarget,
                            input2: InputTarget,
-                           label: InputTarget| {
-            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
-            let output = InputOutputTarget::RegisterInputOutput(pc_num);
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            if value1 < value2 {
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    label.get(simulator)?,
-                );
-                return Ok(TransformationSequence::new_single(transformation));
-            } else {
-                return Ok(TransformationSequence::new_empty());
-            }
+                           label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            return Ok(value1 < value2);
         });
     pub static ref BLE: Instruction =
         instruction!(ble, |simulator: Simulator,
                            input1: InputTarget,
                            input2: InputTarget,
-                           label: InputTarget| {
-            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
-            let output = InputOutputTarget::RegisterInputOutput(pc_num);
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            if value1 <= value2 {
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    label.get(simulator)?,
-                );
-                return Ok(TransformationSequence::new_single(transformation));
-            } else {
-                return Ok(TransformationSequence::new_empty());
-            }
+                           label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            return Ok(value1 <= value2);
         });
     pub static ref BGT: Instruction =
         instruction!(bgt, |simulator: Simulator,
                            input1: InputTarget,
                            input2: InputTarget,
-                           label: InputTarget| {
-            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
-            let output = InputOutputTarget::RegisterInputOutput(pc_num);
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            if value1 > value2 {
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    label.get(simulator)?,
-                );
-                return Ok(TransformationSequence::new_single(transformation));
-            } else {
-                return Ok(TransformationSequence::new_empty());
-            }
+                           label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            return Ok(value1 > value2);
         });
     pub static ref BGE: Instruction =
         instruction!(bge, |simulator: Simulator,
                            input1: InputTarget,
                            input2: InputTarget,
-                           label: InputTarget| {
-            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
-            let output = InputOutputTarget::RegisterInputOutput(pc_num);
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
-            if value1 >= value2 {
-                let transformation = Transformation::new(
-                    Transformable::InputOutputTransformable(output),
-                    output.get(simulator)?,
-                    label.get(simulator)?,
-                );
-                return Ok(TransformationSequence::new_single(transformation));
-            } else {
-                return Ok(TransformationSequence::new_empty());
-            }
+                           label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
+            return Ok(value1 >= value2);
+        });
+    pub static ref BLTU: Instruction =
+        instruction!(bltu, |simulator: Simulator,
+                            input1: InputTarget,
+                            input2: InputTarget,
+                            label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            return Ok(value1 < value2);
+        });
+    pub static ref BGEU: Instruction =
+        instruction!(bgeu, |simulator: Simulator,
+                            input1: InputTarget,
+                            input2: InputTarget,
+                            label: InputTarget| -> branch label {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            return Ok(value1 >= value2);
         });
 }
 
-pub fn register_instructions() {
-    register_instruction(&BEQ);
-    register_instruction(&BNE);
-    register_instruction(&BLT);
-    register_instruction(&BLE);
-    register_instruction(&BGT);
-    register_instruction(&BGE);
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("branch")
+        .with(&BEQ.describe(
+            "Jumps to a label if two integers are equal.",
+            "if input1 == input2: goto label",
+            "beq $t0 0 done",
+        ))
+        .with(&BNE.describe(
+            "Jumps to a label if two integers are not equal.",
+            "if input1 != input2: goto label",
+            "bne $t0 $t1 loop",
+        ))
+        .with(&BLT.describe(
+            "Jumps to a label if one integer is less than another.",
+            "if input1 < input2: goto label",
+            "blt $t0 10 loop",
+        ))
+        .with(&BLE.describe(
+            "Jumps to a label if one integer is at most another.",
+            "if input1 <= input2: goto label",
+            "ble $t0 10 loop",
+        ))
+        .with(&BGT.describe(
+            "Jumps to a label if one integer is greater than another.",
+            "if input1 > input2: goto label",
+            "bgt $t0 0 loop",
+        ))
+        .with(&BGE.describe(
+            "Jumps to a label if one integer is at least another.",
+            "if input1 >= input2: goto label",
+            "bge $t0 0 loop",
+        ))
+        .with(&BLTU.describe(
+            "Jumps to a label if one unsigned integer is less than another.",
+            "if input1 < input2 (unsigned): goto label",
+            "bltu $t0 $t1 loop",
+        ))
+        .with(&BGEU.describe(
+            "Jumps to a label if one unsigned integer is at least another.",
+            "if input1 >= input2 (unsigned): goto label",
+            "bgeu $t0 $t1 done",
+        ))
 }
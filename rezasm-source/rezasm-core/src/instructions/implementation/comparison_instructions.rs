@@ -2,14 +2,11 @@ use lazy_static::lazy_static;
 
 use crate::instructions::instruction::instruction;
 use crate::instructions::instruction::Instruction;
-use crate::instructions::instruction_registry::register_instruction;
+use crate::instructions::instruction_set::InstructionGroup;
 
 use crate::instructions::targets::input_output_target::InputOutputTarget;
 use crate::instructions::targets::input_target::Input;
 use crate::instructions::targets::input_target::InputTarget;
-use crate::simulation::transform::transformable::Transformable;
-use crate::simulation::transform::transformation::Transformation;
-use crate::simulation::transform::transformation_sequence::TransformationSequence;
 use crate::util::raw_data::RawData;
 
 lazy_static! {
@@ -17,117 +14,130 @@ lazy_static! {
         instruction!(seq, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = match value1 == value2 {
                 true => 1,
                 false => 0,
             };
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SNE: Instruction =
         instruction!(sne, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = match value1 != value2 {
                 true => 1,
                 false => 0,
             };
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SLT: Instruction =
         instruction!(slt, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = match value1 < value2 {
                 true => 1,
                 false => 0,
             };
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref SLTU: Instruction =
+        instruction!(sltu, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.unsigned_value();
+            let value2 = input2.get(simulator)?.unsigned_value();
+            let k = match value1 < value2 {
+                true => 1,
+                false => 0,
+            };
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SLE: Instruction =
         instruction!(sle, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = match value1 <= value2 {
                 true => 1,
                 false => 0,
             };
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SGT: Instruction =
         instruction!(sgt, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = match value1 > value2 {
                 true => 1,
                 false => 0,
             };
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
     pub static ref SGE: Instruction =
         instruction!(sge, |simulator: Simulator,
                            output: InputOutputTarget,
                            input1: InputTarget,
-                           input2: InputTarget| {
-            let value1 = input1.get(&simulator)?.int_value();
-            let value2 = input2.get(&simulator)?.int_value();
+                           input2: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.int_value();
+            let value2 = input2.get(simulator)?.int_value();
             let k = match value1 >= value2 {
                 true => 1,
                 false => 0,
             };
-            let transformation = Transformation::new(
-                Transformable::InputOutputTransformable(output),
-                output.get(simulator)?,
-                RawData::from_int(k, simulator.get_word_size()),
-            );
-            return Ok(TransformationSequence::new_single(transformation));
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
         });
 }
 
-pub fn register_instructions() {
-    register_instruction(&SEQ);
-    register_instruction(&SNE);
-    register_instruction(&SLT);
-    register_instruction(&SLE);
-    register_instruction(&SGT);
-    register_instruction(&SGE);
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("comparison")
+        .with(&SEQ.describe(
+            "Sets the output to 1 if two integers are equal, and 0 otherwise.",
+            "output = input1 == input2",
+            "seq $t0 $t1 $t2",
+        ))
+        .with(&SNE.describe(
+            "Sets the output to 1 if two integers are not equal, and 0 otherwise.",
+            "output = input1 != input2",
+            "sne $t0 $t1 $t2",
+        ))
+        .with(&SLT.describe(
+            "Sets the output to 1 if one integer is less than another, and 0 otherwise.",
+            "output = input1 < input2",
+            "slt $t0 $t1 $t2",
+        ))
+        .with(&SLE.describe(
+            "Sets the output to 1 if one integer is at most another, and 0 otherwise.",
+            "output = input1 <= input2",
+            "sle $t0 $t1 $t2",
+        ))
+        .with(&SGT.describe(
+            "Sets the output to 1 if one integer is greater than another, and 0 otherwise.",
+            "output = input1 > input2",
+            "sgt $t0 $t1 $t2",
+        ))
+        .with(&SGE.describe(
+            "Sets the output to 1 if one integer is at least another, and 0 otherwise.",
+            "output = input1 >= input2",
+            "sge $t0 $t1 $t2",
+        ))
+        .with(&SLTU.describe(
+            "Sets the output to 1 if one unsigned integer is less than another, and 0 otherwise.",
+            "output = input1 < input2 (unsigned)",
+            "sltu $t0 $t1 $t2",
+        ))
 }
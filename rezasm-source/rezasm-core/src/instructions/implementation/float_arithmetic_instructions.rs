@@ -2,107 +2,88 @@ use lazy_static::lazy_static;
 
 use crate::instructions::instruction::instruction;
 use crate::instructions::instruction::Instruction;
-use crate::instructions::instruction_registry::register_instruction;
+use crate::instructions::instruction_set::InstructionGroup;
 
 use crate::instructions::targets::input_output_target::InputOutputTarget;
 use crate::instructions::targets::input_target::Input;
 use crate::instructions::targets::input_target::InputTarget;
+use crate::simulation::registry;
+use crate::simulation::simulator::Simulator;
 use crate::simulation::transform::transformable::Transformable;
 use crate::simulation::transform::transformation::Transformation;
 use crate::simulation::transform::transformation_sequence::TransformationSequence;
 use crate::util::error::SimulatorError;
+use crate::util::floating_point::{
+    add_with_error, div_with_error, mul_with_error, round_to_word, sqrt_with_error,
+};
 use crate::util::raw_data::RawData;
 
 lazy_static! {
 pub static ref DECF: Instruction =
     instruction!(decf, |simulator: Simulator,
                        output: InputOutputTarget| {
-        let value1 = output.get(&simulator)?.float_value();
-        let k = value1 - 1.0;
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        let value1 = output.get(simulator)?.float_value();
+        let (k, error) = add_with_error(value1, -1.0);
+        return float_result(simulator, output, k, error, &[value1]);
     });
 pub static ref INCF: Instruction =
     instruction!(incf, |simulator: Simulator,
                        output: InputOutputTarget| {
-        let value1 = output.get(&simulator)?.float_value();
-        let k = value1 + 1.0;
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        let value1 = output.get(simulator)?.float_value();
+        let (k, error) = add_with_error(value1, 1.0);
+        return float_result(simulator, output, k, error, &[value1]);
     });
 pub static ref ADDF: Instruction =
     instruction!(addf, |simulator: Simulator,
                        output: InputOutputTarget,
                        input1: InputTarget,
                        input2: InputTarget| {
-        let value1 = input1.get(&simulator)?.float_value();
-        let value2 = input2.get(&simulator)?.float_value();
-        let k = value1 + value2 ;
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        let value1 = input1.get(simulator)?.float_value();
+        let value2 = input2.get(simulator)?.float_value();
+        let (k, error) = add_with_error(value1, value2);
+        return float_result(simulator, output, k, error, &[value1, value2]);
     });
 pub static ref SUBF: Instruction =
     instruction!(subf, |simulator: Simulator,
                        output: InputOutputTarget,
                        input1: InputTarget,
                        input2: InputTarget| {
-        let value1 = input1.get(&simulator)?.float_value();
-        let value2 = input2.get(&simulator)?.float_value();
-        let k = value1 - value2 ;
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        let value1 = input1.get(simulator)?.float_value();
+        let value2 = input2.get(simulator)?.float_value();
+        let (k, error) = add_with_error(value1, -value2);
+        return float_result(simulator, output, k, error, &[value1, value2]);
     });
 pub static ref MULF: Instruction =
     instruction!(mulf, |simulator: Simulator,
                        output: InputOutputTarget,
                        input1: InputTarget,
                        input2: InputTarget| {
-        let value1 = input1.get(&simulator)?.float_value();
-        let value2 = input2.get(&simulator)?.float_value();
-        let k = value1 * value2 ;
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        let value1 = input1.get(simulator)?.float_value();
+        let value2 = input2.get(simulator)?.float_value();
+        let (k, error) = mul_with_error(value1, value2);
+        return float_result(simulator, output, k, error, &[value1, value2]);
     });
 pub static ref DIVF: Instruction =
     instruction!(divf, |simulator: Simulator,
                        output: InputOutputTarget,
                        input1: InputTarget,
                        input2: InputTarget| {
-        let value1 = input1.get(&simulator)?.float_value();
-        let value2 = input2.get(&simulator)?.float_value();
+        let value1 = input1.get(simulator)?.float_value();
+        let value2 = input2.get(simulator)?.float_value();
 
         if value2 == 0f64 && value1 != 0f64 {
             return Err(SimulatorError::DivideByZeroError);
         }
-        let k = value1 / value2 ;
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        let (k, error) = div_with_error(value1, value2);
+        return float_result(simulator, output, k, error, &[value1, value2]);
     });
 pub static ref MODF: Instruction =
     instruction!(modf, |simulator: Simulator,
                        output: InputOutputTarget,
                        input1: InputTarget,
                        input2: InputTarget| {
-        let value1 = input1.get(&simulator)?.float_value();
-        let value2 = input2.get(&simulator)?.float_value();
+        let value1 = input1.get(simulator)?.float_value();
+        let value2 = input2.get(simulator)?.float_value();
 
         if value2 == 0f64 && value1 != 0f64 {
             return Err(SimulatorError::DivideByZeroError);
@@ -113,31 +94,86 @@ pub static ref MODF: Instruction =
 
         let k = divvalue1 - f64::floor(divvalue1/divvalue2) * divvalue2;
 
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        return float_result(simulator, output, k, 0.0, &[value1, value2]);
+    });
+pub static ref SQRTF: Instruction =
+    instruction!(sqrtf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        let (k, error) = sqrt_with_error(value1);
+        return float_result(simulator, output, k, error, &[value1]);
+    });
+pub static ref ABSF: Instruction =
+    instruction!(absf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        return float_result(simulator, output, value1.abs(), 0.0, &[value1]);
+    });
+pub static ref NEGF: Instruction =
+    instruction!(negf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        return float_result(simulator, output, -value1, 0.0, &[value1]);
+    });
+pub static ref MINF: Instruction =
+    instruction!(minf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget,
+                       input2: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        let value2 = input2.get(simulator)?.float_value();
+        return float_result(simulator, output, value1.min(value2), 0.0, &[value1, value2]);
+    });
+pub static ref MAXF: Instruction =
+    instruction!(maxf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget,
+                       input2: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        let value2 = input2.get(simulator)?.float_value();
+        return float_result(simulator, output, value1.max(value2), 0.0, &[value1, value2]);
+    });
+pub static ref FLOORF: Instruction =
+    instruction!(floorf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        return float_result(simulator, output, value1.floor(), 0.0, &[value1]);
+    });
+pub static ref CEILF: Instruction =
+    instruction!(ceilf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        return float_result(simulator, output, value1.ceil(), 0.0, &[value1]);
+    });
+pub static ref ROUNDF: Instruction =
+    instruction!(roundf, |simulator: Simulator,
+                       output: InputOutputTarget,
+                       input1: InputTarget| {
+        let value1 = input1.get(simulator)?.float_value();
+        let k = simulator.get_rounding_mode().round_to_integer(value1);
+        return float_result(simulator, output, k, 0.0, &[value1]);
     });
 pub static ref ITOF: Instruction =
     instruction!(itof, |simulator: Simulator,
                        output: InputOutputTarget,
                        input1: InputTarget| {
-        let value1 = input1.get(&simulator)?.int_value();
+        let value1 = input1.get(simulator)?.int_value();
 
         let k = value1 as f64;
+        let error = (value1 as i128 - k as i128) as f64;
 
-        let transformation = Transformation::new(
-            Transformable::InputOutputTransformable(output),
-            output.get(simulator)?,
-            RawData::from_float(k, simulator.get_word_size()));
-        return Ok(TransformationSequence::new_single(transformation));
+        return float_result(simulator, output, k, error, &[]);
     });
 pub static ref FTOI: Instruction =
     instruction!(ftoi, |simulator: Simulator,
                        output: InputOutputTarget,
                        input1: InputTarget| {
-        let value1 = input1.get(&simulator)?.float_value();
+        let value1 = input1.get(simulator)?.float_value();
 
         // This has a "feature" due to lack of precision in float representations for some floats... This is
         // not a particularly fixable thing without getting too complicated.
@@ -155,14 +191,129 @@ pub static ref FTOI: Instruction =
     });
 }
 
-pub fn register_instructions() {
-    register_instruction(&ADDF);
-    register_instruction(&SUBF);
-    register_instruction(&MULF);
-    register_instruction(&DIVF);
-    register_instruction(&MODF);
-    register_instruction(&DECF);
-    register_instruction(&INCF);
-    register_instruction(&ITOF);
-    register_instruction(&FTOI);
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("float-arithmetic")
+        .with(&ADDF.describe(
+            "Adds two floats.",
+            "output = input1 + input2",
+            "addf $t0 $t1 1.5",
+        ))
+        .with(&SUBF.describe(
+            "Subtracts one float from another.",
+            "output = input1 - input2",
+            "subf $t0 $t1 $t2",
+        ))
+        .with(&MULF.describe(
+            "Multiplies two floats.",
+            "output = input1 * input2",
+            "mulf $t0 $t1 0.5",
+        ))
+        .with(&DIVF.describe(
+            "Divides one float by another.",
+            "output = input1 / input2",
+            "divf $t0 $t1 3.0",
+        ))
+        .with(&MODF.describe(
+            "Takes the remainder of dividing the magnitudes of two floats.",
+            "output = |input1| mod |input2|",
+            "modf $t0 $t1 2.5",
+        ))
+        .with(&DECF.describe(
+            "Subtracts one from a float.",
+            "output = output - 1.0",
+            "decf $t0",
+        ))
+        .with(&INCF.describe("Adds one to a float.", "output = output + 1.0", "incf $t0"))
+        .with(&ITOF.describe(
+            "Converts an integer to a float.",
+            "output = float(input1)",
+            "itof $t0 $t1",
+        ))
+        .with(&FTOI.describe(
+            "Converts a float to an integer, rounding towards zero.",
+            "output = int(input1)",
+            "ftoi $t0 $t1",
+        ))
+        .with(&SQRTF.describe(
+            "Takes the square root of a float.",
+            "output = sqrt(input1)",
+            "sqrtf $t0 $t1",
+        ))
+        .with(&ABSF.describe(
+            "Takes the absolute value of a float.",
+            "output = |input1|",
+            "absf $t0 $t1",
+        ))
+        .with(&NEGF.describe("Negates a float.", "output = -input1", "negf $t0 $t1"))
+        .with(&MINF.describe(
+            "Takes the smaller of two floats.",
+            "output = min(input1, input2)",
+            "minf $t0 $t1 $t2",
+        ))
+        .with(&MAXF.describe(
+            "Takes the larger of two floats.",
+            "output = max(input1, input2)",
+            "maxf $t0 $t1 $t2",
+        ))
+        .with(&FLOORF.describe(
+            "Rounds a float down to an integral value.",
+            "output = floor(input1)",
+            "floorf $t0 $t1",
+        ))
+        .with(&CEILF.describe(
+            "Rounds a float up to an integral value.",
+            "output = ceil(input1)",
+            "ceilf $t0 $t1",
+        ))
+        .with(&ROUNDF.describe(
+            "Rounds a float to an integral value with the current rounding mode.",
+            "output = round(input1)",
+            "roundf $t0 $t1",
+        ))
+}
+
+/// Stores the result of a floating point operation in `output`, rounded to the precision of a word
+/// with the simulator's rounding mode, and raises any exception flags the operation caused.
+///
+/// See `round_to_word` for the meaning of the arguments.
+fn float_result(
+    simulator: &Simulator,
+    output: InputOutputTarget,
+    value: f64,
+    error: f64,
+    operands: &[f64],
+) -> Result<TransformationSequence, SimulatorError> {
+    let (data, flags) = round_to_word(
+        value,
+        error,
+        operands,
+        simulator.get_rounding_mode(),
+        simulator.get_word_size(),
+    );
+    let mut transformations = vec![Transformation::new(
+        Transformable::InputOutputTransformable(output),
+        output.get(simulator)?,
+        data,
+    )];
+    transformations.extend(raise_flags(simulator, flags)?);
+    Ok(TransformationSequence::new(transformations))
+}
+
+/// Creates the transformation which adds exception flags to `$fflags`, or nothing if they have all
+/// been raised already.
+pub(super) fn raise_flags(
+    simulator: &Simulator,
+    flags: i64,
+) -> Result<Option<Transformation>, SimulatorError> {
+    let target = InputOutputTarget::RegisterInputOutput(registry::FFLAGS_NUMBER);
+    let current = target.get(simulator)?;
+    let raised = current.int_value() | flags;
+    if raised == current.int_value() {
+        return Ok(None);
+    }
+    Ok(Some(Transformation::new(
+        Transformable::InputOutputTransformable(target),
+        current,
+        RawData::from_int(raised, simulator.get_word_size()),
+    )))
 }
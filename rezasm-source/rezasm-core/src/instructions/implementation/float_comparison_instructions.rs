@@ -0,0 +1,229 @@
+use lazy_static::lazy_static;
+
+use crate::instructions::instruction::instruction;
+use crate::instructions::instruction::Instruction;
+use crate::instructions::instruction_set::InstructionGroup;
+
+use crate::instructions::targets::input_output_target::InputOutputTarget;
+use crate::instructions::targets::input_target::Input;
+use crate::instructions::targets::input_target::InputTarget;
+use crate::simulation::registry;
+use crate::simulation::simulator::Simulator;
+use crate::simulation::transform::transformable::Transformable;
+use crate::simulation::transform::transformation::Transformation;
+use crate::simulation::transform::transformation_sequence::TransformationSequence;
+use crate::util::error::SimulatorError;
+use crate::util::floating_point::FLAG_INVALID;
+use crate::util::raw_data::RawData;
+
+use super::float_arithmetic_instructions::raise_flags;
+
+lazy_static! {
+    pub static ref SLTF: Instruction =
+        instruction!(sltf, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| {
+            let value1 = input1.get(simulator)?.float_value();
+            let value2 = input2.get(simulator)?.float_value();
+            let k = match value1 < value2 {
+                true => 1,
+                false => 0,
+            };
+            return comparison_result(simulator, output, k, value1, value2, true);
+        });
+    pub static ref SEQF: Instruction =
+        instruction!(seqf, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| {
+            let value1 = input1.get(simulator)?.float_value();
+            let value2 = input2.get(simulator)?.float_value();
+            let k = match value1 == value2 {
+                true => 1,
+                false => 0,
+            };
+            return comparison_result(simulator, output, k, value1, value2, false);
+        });
+    pub static ref SLEF: Instruction =
+        instruction!(slef, |simulator: Simulator,
+                            output: InputOutputTarget,
+                            input1: InputTarget,
+                            input2: InputTarget| {
+            let value1 = input1.get(simulator)?.float_value();
+            let value2 = input2.get(simulator)?.float_value();
+            let k = match value1 <= value2 {
+                true => 1,
+                false => 0,
+            };
+            return comparison_result(simulator, output, k, value1, value2, true);
+        });
+    pub static ref BLTF: Instruction =
+        instruction!(bltf, |simulator: Simulator,
+                            input1: InputTarget,
+                            input2: InputTarget,
+                            label: InputTarget| {
+            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
+            let output = InputOutputTarget::RegisterInputOutput(pc_num);
+            let value1 = input1.get(simulator)?.float_value();
+            let value2 = input2.get(simulator)?.float_value();
+            let mut transformations = Vec::new();
+            if value1 < value2 {
+                transformations.push(Transformation::new(
+                    Transformable::InputOutputTransformable(output),
+                    output.get(simulator)?,
+                    label.get(simulator)?,
+                ));
+            }
+            transformations.extend(raise_flags(
+                simulator,
+                comparison_flags(value1, value2, true),
+            )?);
+            return Ok(TransformationSequence::new(transformations));
+        });
+    pub static ref BEQF: Instruction =
+        instruction!(beqf, |simulator: Simulator,
+                            input1: InputTarget,
+                            input2: InputTarget,
+                            label: InputTarget| {
+            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
+            let output = InputOutputTarget::RegisterInputOutput(pc_num);
+            let value1 = input1.get(simulator)?.float_value();
+            let value2 = input2.get(simulator)?.float_value();
+            let mut transformations = Vec::new();
+            if value1 == value2 {
+                transformations.push(Transformation::new(
+                    Transformable::InputOutputTransformable(output),
+                    output.get(simulator)?,
+                    label.get(simulator)?,
+                ));
+            }
+            transformations.extend(raise_flags(
+                simulator,
+                comparison_flags(value1, value2, false),
+            )?);
+            return Ok(TransformationSequence::new(transformations));
+        });
+    pub static ref BGEF: Instruction =
+        instruction!(bgef, |simulator: Simulator,
+                            input1: InputTarget,
+                            input2: InputTarget,
+                            label: InputTarget| {
+            let pc_num = registry::get_register_number(&registry::PC.to_string()).unwrap();
+            let output = InputOutputTarget::RegisterInputOutput(pc_num);
+            let value1 = input1.get(simulator)?.float_value();
+            let value2 = input2.get(simulator)?.float_value();
+            let mut transformations = Vec::new();
+            if value1 >= value2 {
+                transformations.push(Transformation::new(
+                    Transformable::InputOutputTransformable(output),
+                    output.get(simulator)?,
+                    label.get(simulator)?,
+                ));
+            }
+            transformations.extend(raise_flags(
+                simulator,
+                comparison_flags(value1, value2, true),
+            )?);
+            return Ok(TransformationSequence::new(transformations));
+        });
+    pub static ref ISNANF: Instruction =
+        instruction!(isnanf, |simulator: Simulator,
+                              output: InputOutputTarget,
+                              input1: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.float_value();
+            let k = match value1.is_nan() {
+                true => 1,
+                false => 0,
+            };
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+    pub static ref ISINFF: Instruction =
+        instruction!(isinff, |simulator: Simulator,
+                              output: InputOutputTarget,
+                              input1: InputTarget| -> output {
+            let value1 = input1.get(simulator)?.float_value();
+            let k = match value1.is_infinite() {
+                true => 1,
+                false => 0,
+            };
+            return Ok(RawData::from_int(k, simulator.get_word_size()));
+        });
+}
+
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("float-comparison")
+        .with(&SLTF.describe(
+            "Sets the output to 1 if one float is less than another, and 0 otherwise.",
+            "output = input1 < input2",
+            "sltf $t0 $t1 $t2",
+        ))
+        .with(&SEQF.describe(
+            "Sets the output to 1 if two floats are equal, and 0 otherwise.",
+            "output = input1 == input2",
+            "seqf $t0 $t1 $t2",
+        ))
+        .with(&SLEF.describe(
+            "Sets the output to 1 if one float is at most another, and 0 otherwise.",
+            "output = input1 <= input2",
+            "slef $t0 $t1 $t2",
+        ))
+        .with(&BLTF.describe(
+            "Jumps to a label if one float is less than another.",
+            "if input1 < input2: goto label",
+            "bltf $t0 1.0 loop",
+        ))
+        .with(&BEQF.describe(
+            "Jumps to a label if two floats are equal.",
+            "if input1 == input2: goto label",
+            "beqf $t0 0.0 done",
+        ))
+        .with(&BGEF.describe(
+            "Jumps to a label if one float is at least another.",
+            "if input1 >= input2: goto label",
+            "bgef $t0 1.0 done",
+        ))
+        .with(&ISNANF.describe(
+            "Sets the output to 1 if a float is NaN, and 0 otherwise.",
+            "output = isnan(input1)",
+            "isnanf $t0 $t1",
+        ))
+        .with(&ISINFF.describe(
+            "Sets the output to 1 if a float is infinite, and 0 otherwise.",
+            "output = isinf(input1)",
+            "isinff $t0 $t1",
+        ))
+}
+
+/// Gets the exception flags raised by comparing two floats.
+///
+/// Every comparison with NaN is false, but only ordered comparisons such as `<` are signaling and
+/// raise the invalid flag; equality is quiet.
+fn comparison_flags(value1: f64, value2: f64, signaling: bool) -> i64 {
+    if signaling && (value1.is_nan() || value2.is_nan()) {
+        FLAG_INVALID
+    } else {
+        0
+    }
+}
+
+/// Stores the result of a comparison in `output` and raises any exception flags it caused.
+fn comparison_result(
+    simulator: &Simulator,
+    output: InputOutputTarget,
+    k: i64,
+    value1: f64,
+    value2: f64,
+    signaling: bool,
+) -> Result<TransformationSequence, SimulatorError> {
+    let mut transformations = vec![Transformation::new(
+        Transformable::InputOutputTransformable(output),
+        output.get(simulator)?,
+        RawData::from_int(k, simulator.get_word_size()),
+    )];
+    transformations.extend(raise_flags(
+        simulator,
+        comparison_flags(value1, value2, signaling),
+    )?);
+    Ok(TransformationSequence::new(transformations))
+}
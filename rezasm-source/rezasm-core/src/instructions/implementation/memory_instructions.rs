@@ -2,7 +2,7 @@ use lazy_static::lazy_static;
 
 use crate::instructions::instruction::instruction;
 use crate::instructions::instruction::Instruction;
-use crate::instructions::instruction_registry::register_instruction;
+use crate::instructions::instruction_set::InstructionGroup;
 
 use crate::instructions::targets::input_output_target::InputOutputTarget;
 use crate::instructions::targets::input_target::Input;
@@ -46,19 +46,8 @@ pub fn consecutive_pop(
     let offset = times * simulator.get_word_size().value() as i64;
     let sp_target = InputOutputTarget::new_register(&registry::SP_NUMBER)?;
     let io = Transformable::InputOutputTransformable(output);
-    let sp =
-        sp_target.get(simulator)?.int_value() - simulator.get_word_size().value() as i64 + offset;
-    let t1 = io.create_transformation(
-        simulator,
-        RawData::from_int(
-            simulator
-                .get_memory()
-                .read(sp_target.get(simulator)?.int_value() as usize)?
-                .int_value()
-                + offset,
-            simulator.get_word_size(),
-        ),
-    )?;
+    let address = sp_target.get(simulator)?.int_value() + offset;
+    let t1 = io.create_transformation(simulator, simulator.get_memory().read(address as usize)?)?;
     let t2 = Transformable::InputOutputTransformable(sp_target).create_transformation(
         simulator,
         RawData::from_int(
@@ -83,13 +72,10 @@ lazy_static! {
     pub static ref LOAD: Instruction =
         instruction!(load, |simulator: Simulator,
                             output: InputOutputTarget,
-                            input: InputTarget| {
+                            input: InputTarget| -> output {
             let memory = simulator.get_memory();
             let word = memory.read(input.get(simulator)?.int_value() as usize)?;
-            let out_transformable = Transformable::InputOutputTransformable(output);
-            Ok(TransformationSequence::new_single(
-                out_transformable.create_transformation(simulator, input.get(simulator)?)?,
-            ))
+            input.get(simulator)
         });
     pub static ref STORE: Instruction =
         instruction!(store, |simulator: Simulator,
@@ -116,25 +102,43 @@ lazy_static! {
                     simulator.get_word_size(),
                 ),
             );
-            let t2 =
-                output_transformable.create_transformation(simulator, t1.get_from().clone())?;
+            let t2 = output_transformable.create_transformation(simulator, *t1.get_from())?;
             Ok(TransformationSequence::new(vec![t1, t2]))
         });
     pub static ref MOVE: Instruction =
         instruction!(_move, |simulator: Simulator,
                              output: InputOutputTarget,
-                             input: InputTarget| {
-            let output_transformable = Transformable::InputOutputTransformable(output)
-                .create_transformation(simulator, input.get(simulator)?)?;
-            Ok(TransformationSequence::new_single(output_transformable))
+                             input: InputTarget| -> output {
+            input.get(simulator)
         });
 }
 
-pub fn register_instructions() {
-    register_instruction(&PUSH);
-    register_instruction(&POP);
-    register_instruction(&LOAD);
-    register_instruction(&STORE);
-    register_instruction(&ALLOC);
-    register_instruction(&MOVE);
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("memory")
+        .with(&PUSH.describe(
+            "Pushes a word onto the stack.",
+            "$sp = $sp - word; memory[$sp] = input",
+            "push $t0",
+        ))
+        .with(&POP.describe(
+            "Pops a word off of the stack.",
+            "output = memory[$sp]; $sp = $sp + word",
+            "pop $t0",
+        ))
+        .with(&LOAD.describe(
+            "Loads a word from memory.",
+            "output = memory[input]",
+            "load $t0 $s0",
+        ))
+        .with(&STORE.describe(
+            "Stores a word in memory.",
+            "memory[input2] = input1",
+            "store $t0 $s0",
+        ))
+        .with(&ALLOC.describe(
+            "Allocates bytes on the heap and gives the address of the first one.",
+            "output = $hp; $hp = $hp + input",
+            "alloc $s0 16",
+        ))
+        .with(&MOVE.describe("Copies a value.", "output = input", "move $t0 $t1"))
 }
@@ -1,21 +1,30 @@
 mod arithmetic_instructions;
+mod bit_instructions;
 mod branch_instructions;
 mod comparison_instructions;
 mod float_arithmetic_instructions;
+mod float_comparison_instructions;
 mod function_instructions;
 mod memory_instructions;
 mod terminal_input_instructions;
 mod terminal_output_instructions;
 
-pub fn register_instructions() {
-    arithmetic_instructions::register_instructions();
-    branch_instructions::register_instructions();
-    comparison_instructions::register_instructions();
-    float_arithmetic_instructions::register_instructions();
-    terminal_input_instructions::register_instructions();
-    function_instructions::register_instructions();
-    memory_instructions::register_instructions();
-    terminal_output_instructions::register_instructions();
+use crate::instructions::instruction_set::InstructionGroup;
+
+/// Gets the groups of built-in instructions, which together make up the standard instruction set.
+pub fn standard_groups() -> Vec<InstructionGroup> {
+    vec![
+        arithmetic_instructions::group(),
+        bit_instructions::group(),
+        branch_instructions::group(),
+        comparison_instructions::group(),
+        float_arithmetic_instructions::group(),
+        float_comparison_instructions::group(),
+        terminal_input_instructions::group(),
+        function_instructions::group(),
+        memory_instructions::group(),
+        terminal_output_instructions::group(),
+    ]
 }
 
 #[cfg(test)]
@@ -29,17 +38,23 @@ mod tests {
     use crate::instructions::argument_type::ArgumentType;
     use crate::instructions::targets::input_output_target::InputOutputTarget;
     use crate::instructions::targets::input_target::InputTarget;
-    use crate::parser::lexer::parse_lines;
+    use crate::parser::lexer::{parse_lines, parse_numbered_lines};
+    use crate::simulation::memory::DEFAULT_MEMORY_WORDS;
+    use crate::simulation::reader::DummyReader;
+    use crate::simulation::reader_cell::ReaderCell;
     use crate::simulation::simulator::Simulator;
+    use crate::simulation::writer::DummyWriter;
     use crate::test_utils::{workspace_root, TestWriter};
+    use crate::util::error::SimulatorError;
+    use crate::util::floating_point::{RoundingMode, FLAG_INEXACT, FLAG_INVALID};
     use crate::util::io::RezasmFileReader;
     use crate::util::raw_data::RawData;
+    use crate::util::word_size::WordSize;
 
     use super::*;
 
     #[test]
     pub fn test_macro() {
-        register_instructions();
         let mut simulator: Simulator = Simulator::new();
         let word_size = simulator.get_word_size();
 
@@ -70,7 +85,6 @@ mod tests {
 
     #[test]
     pub fn test_print_instructions() {
-        register_instructions();
         let writer = Box::new(TestWriter::new());
         let mut simulator: Simulator = Simulator::new_writer(writer);
         let program = "
@@ -83,7 +97,12 @@ mod tests {
         printf $t1
         printc $s1
         prints $s2";
-        let lines = parse_lines(program, simulator.get_word_size()).unwrap();
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
         simulator.add_lines(lines, "".to_string()).unwrap();
         while !simulator.is_done() {
             simulator.run_line_from_pc().unwrap();
@@ -98,11 +117,364 @@ mod tests {
         assert_eq!(output.as_str(), "3\n1.5\nPrint Instructions Work!\n");
     }
 
+    #[test]
+    pub fn test_unsigned_arithmetic() {
+        let mut simulator: Simulator = Simulator::new();
+        let program = "
+        addu $t0 -1 1
+        divu $t1 -2 2
+        mulhu $t2 -1 -1
+        mulh $t3 -1 -1
+        sltu $t4 1 -1
+        bltu -1 1 skip
+        add $t5 1 0
+        skip:
+        bgeu -1 1 end
+        add $t6 1 0
+        end:";
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "".to_string()).unwrap();
+        simulator.run(usize::MAX).unwrap();
+        let value = |register: &str| {
+            simulator
+                .get_registers()
+                .get_register(register)
+                .unwrap()
+                .get_data()
+                .unsigned_value()
+        };
+        assert_eq!(value("T0"), 0);
+        assert_eq!(value("T1"), 0x7fff_ffff);
+        assert_eq!(value("T2"), 0xffff_fffe);
+        assert_eq!(value("T3"), 0);
+        assert_eq!(value("T4"), 1);
+        assert_eq!(value("T5"), 1);
+        assert_eq!(value("T6"), 0);
+    }
+
+    #[test]
+    pub fn test_overflow_trap() {
+        let mut simulator: Simulator = Simulator::new();
+        simulator.set_trap_overflow(true);
+        let program = "
+        addu $t0 -2 1
+        add $t1 2147483647 1";
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "".to_string()).unwrap();
+        simulator.run_line_from_pc().unwrap();
+        assert!(matches!(
+            simulator.run_line_from_pc(),
+            Err(SimulatorError::IntegerOverflow(name, 2147483647, 1)) if name == "add"
+        ));
+
+        for line in [
+            "subu $t0 0 1",
+            "mul $t0 65536 65536",
+            "div $t0 -2147483648 -1",
+        ] {
+            simulator.reset();
+            let lines = parse_lines(
+                line,
+                simulator.get_word_size(),
+                simulator.get_instruction_set(),
+            )
+            .unwrap();
+            simulator.add_lines(lines, "".to_string()).unwrap();
+            assert!(matches!(
+                simulator.run_line_from_pc(),
+                Err(SimulatorError::IntegerOverflow(..))
+            ));
+        }
+    }
+
+    #[test]
+    pub fn test_calling_convention() {
+        let mut simulator: Simulator = Simulator::new();
+        simulator.set_check_calling_convention(true);
+        let program = "
+        move $s0 1
+        call keep
+        call clobber
+        exit
+        keep:
+        push $s0
+        move $s0 2
+        pop $s0
+        return
+        clobber:
+        move $s0 3
+        return";
+        let lines = parse_numbered_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator
+            .add_numbered_lines(lines, "main.ez".to_string())
+            .unwrap();
+        let error = simulator.run(usize::MAX).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "function `clobber` called from line 4 of `main.ez` returned without restoring $s0 (was 1, now 3)"
+        );
+        // the error names the same line as the backtrace
+        assert_eq!(simulator.backtrace().frames[1].line, 4);
+
+        simulator.reset();
+        simulator.set_check_calling_convention(false);
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "main.ez".to_string()).unwrap();
+        simulator.run(usize::MAX).unwrap();
+        assert!(simulator.is_done());
+    }
+
+    #[test]
+    pub fn test_nested_return() {
+        let mut simulator: Simulator = Simulator::new();
+        let program = "
+        call outer
+        move $t1 1
+        exit
+        outer:
+        move $t0 $ra
+        call inner
+        move $t2 $ra
+        return
+        inner:
+        return";
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "main.ez".to_string()).unwrap();
+        simulator.run(usize::MAX).unwrap();
+        assert!(simulator.is_done());
+
+        let register = |name: &str| {
+            simulator
+                .get_registers()
+                .get_register(name)
+                .unwrap()
+                .get_data()
+                .int_value()
+        };
+        // returning from `inner` restores the return address `outer` was called with
+        assert_eq!(register("t2"), register("t0"));
+        assert_eq!(register("t1"), 1);
+    }
+
+    #[test]
+    pub fn test_local_labels() {
+        let mut simulator: Simulator = Simulator::new();
+        // both functions count $a0 down in a loop of their own, adding to $r0 each time
+        let program = "
+        move $a0 3
+        call add_ones
+        move $a0 2
+        call add_tens
+        jump 1f
+        move $r0 0
+        1:
+        exit
+        add_ones:
+        .loop:
+        beq $a0 0 .done
+        add $r0 $r0 1
+        sub $a0 $a0 1
+        jump .loop
+        .done:
+        return
+        add_tens:
+        .loop:
+        beq $a0 0 1f
+        add $r0 $r0 10
+        sub $a0 $a0 1
+        jump .loop
+        1:
+        return";
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "main.ez".to_string()).unwrap();
+        simulator.run(usize::MAX).unwrap();
+        assert!(simulator.is_done());
+        assert_eq!(
+            simulator
+                .get_registers()
+                .get_register("R0")
+                .unwrap()
+                .get_data()
+                .int_value(),
+            23
+        );
+    }
+
+    #[test]
+    pub fn test_float_instructions() {
+        let mut simulator: Simulator = Simulator::new();
+        let program = "
+        sqrtf $t0 2.25
+        negf $t1 $t0
+        absf $t2 $t1
+        maxf $t3 $t1 $t0
+        floorf $t4 $t1
+        roundf $t5 2.5
+        sltf $t6 $t1 $t0
+        beqf $t0 1.5 skip
+        add $t7 1 0
+        skip:
+        divf $s0 0.0 0.0
+        isnanf $s1 $s0
+        slef $s2 $s0 1.0";
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "".to_string()).unwrap();
+        simulator.run(usize::MAX).unwrap();
+        let data = |simulator: &Simulator, register: &str| {
+            *simulator
+                .get_registers()
+                .get_register(register)
+                .unwrap()
+                .get_data()
+        };
+        assert_eq!(data(&simulator, "T0").float_value(), 1.5);
+        assert_eq!(data(&simulator, "T1").float_value(), -1.5);
+        assert_eq!(data(&simulator, "T2").float_value(), 1.5);
+        assert_eq!(data(&simulator, "T3").float_value(), 1.5);
+        assert_eq!(data(&simulator, "T4").float_value(), -2.0);
+        assert_eq!(data(&simulator, "T5").float_value(), 2.0);
+        assert_eq!(data(&simulator, "T6").int_value(), 1);
+        assert_eq!(data(&simulator, "T7").int_value(), 0);
+        assert!(data(&simulator, "S0").float_value().is_nan());
+        assert_eq!(data(&simulator, "S1").int_value(), 1);
+        assert_eq!(data(&simulator, "S2").int_value(), 0);
+        assert_eq!(data(&simulator, "FFLAGS").int_value(), FLAG_INVALID);
+
+        simulator.reset();
+        simulator.set_rounding_mode(RoundingMode::Up);
+        let lines = parse_lines(
+            "divf $t0 1.0 3.0\nroundf $t1 2.5",
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        );
+        simulator.add_lines(lines.unwrap(), "".to_string()).unwrap();
+        simulator.run(usize::MAX).unwrap();
+        assert_eq!(data(&simulator, "T0").float_value() as f32, 1.0f32 / 3.0);
+        assert_eq!(data(&simulator, "T1").float_value(), 3.0);
+        assert_eq!(data(&simulator, "FFLAGS").int_value(), FLAG_INEXACT);
+
+        simulator.undo_last_transformation().unwrap();
+        simulator.undo_last_transformation().unwrap();
+        assert_eq!(data(&simulator, "FFLAGS").int_value(), 0);
+    }
+
+    #[test]
+    pub fn test_bit_instructions() {
+        let program = "
+        rol $t0 0x80000001 1
+        ror $t1 0x80000001 1
+        popcnt $t2 -1
+        clz $t3 1
+        ctz $t4 0
+        bext $t5 0x12345678 8 8
+        bins $t6 0xff 4 4
+        bswap $t7 0x12345678
+        bset $t8 0 31
+        bclr $t9 -1 0
+        btst $s0 4 2";
+        let expected: [(WordSize, [u64; 11]); 2] = [
+            (
+                WordSize::Four,
+                [
+                    0x3,
+                    0xc000_0000,
+                    32,
+                    31,
+                    32,
+                    0x56,
+                    0xf0,
+                    0x7856_3412,
+                    0x8000_0000,
+                    0xffff_fffe,
+                    1,
+                ],
+            ),
+            (
+                WordSize::Eight,
+                [
+                    0x1_0000_0002,
+                    0x8000_0000_4000_0000,
+                    64,
+                    63,
+                    64,
+                    0x56,
+                    0xf0,
+                    0x7856_3412_0000_0000,
+                    0x8000_0000,
+                    0xffff_ffff_ffff_fffe,
+                    1,
+                ],
+            ),
+        ];
+        for (word_size, values) in expected {
+            let mut simulator = Simulator::new_custom(
+                &word_size,
+                DEFAULT_MEMORY_WORDS,
+                ReaderCell::new(DummyReader::new()),
+                Box::new(DummyWriter::new()),
+            );
+            let lines = parse_lines(program, &word_size, simulator.get_instruction_set()).unwrap();
+            simulator.add_lines(lines, "".to_string()).unwrap();
+            simulator.run(usize::MAX).unwrap();
+            for (register, value) in [
+                "T0", "T1", "T2", "T3", "T4", "T5", "T6", "T7", "T8", "T9", "S0",
+            ]
+            .iter()
+            .zip(values)
+            {
+                let data = simulator.get_registers().get_register(register).unwrap();
+                assert_eq!(data.get_data().unsigned_value(), value, "{}", register);
+            }
+
+            simulator.reset();
+            let lines =
+                parse_lines("bset $t0 0 64", &word_size, simulator.get_instruction_set()).unwrap();
+            simulator.add_lines(lines, "".to_string()).unwrap();
+            assert!(matches!(
+                simulator.run_line_from_pc(),
+                Err(SimulatorError::InvalidBitFieldError(64, 1, _))
+            ));
+        }
+    }
+
     // FIXME: need to move this test elsewhere; it covers the parser I/O, not simulator I/O
     #[test]
     pub fn test_io() {
-        register_instructions();
-
         // Read into rezasm file
         let file_path = workspace_root().join("example").join("arithmetic_fib.ez");
         let mut rezasmfile = RezasmFileReader::new(file_path.clone()).expect("failed to read file");
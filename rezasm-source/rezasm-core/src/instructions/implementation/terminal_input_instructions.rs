@@ -1,12 +1,13 @@
 use crate::instruction;
 use crate::instructions::instruction::Instruction;
-use crate::instructions::instruction_registry::register_instruction;
+use crate::instructions::instruction_set::InstructionGroup;
 use crate::instructions::targets::input_target::InputTarget;
 use crate::instructions::targets::{input_output_target::InputOutputTarget, input_target::Input};
 use crate::simulation::reader::Reader;
+use crate::simulation::simulator::Simulator;
 use crate::simulation::transform::transformable::Transformable;
 use crate::simulation::transform::transformation_sequence::TransformationSequence;
-use crate::util::error::IoError;
+use crate::util::error::{IoError, SimulatorError};
 use crate::util::raw_data::RawData;
 use lazy_static::lazy_static;
 
@@ -83,16 +84,8 @@ lazy_static! {
                 return Ok(TransformationSequence::new_nullop(simulator)?);
             };
 
-            let mut words = pad_bytes(&bytes[0..read_count], simulator.get_word_size().value());
-            words.append(&mut vec![0u8; 4]);
-
             let address = input1.get(simulator)?.int_value() as usize;
-            let word_size = simulator.get_word_size().value();
-
-            let transformation = Transformable::MemoryTransformable(address)
-                .create_transformation(simulator, RawData::new(&words))?;
-
-            Ok(TransformationSequence::new_single(transformation))
+            write_string(simulator, address, &bytes[0..read_count], true)
         }
     );
 
@@ -105,15 +98,8 @@ lazy_static! {
                 return Ok(TransformationSequence::new_nullop(simulator)?);
             };
 
-            let words = pad_bytes(input.as_bytes(), simulator.get_word_size().value());
-
             let address = input1.get(simulator)?.int_value() as usize;
-            let word_size = simulator.get_word_size().value();
-
-            let transformation = Transformable::MemoryTransformable(address)
-                .create_transformation(simulator, RawData::new(&words))?;
-
-            Ok(TransformationSequence::new_single(transformation))
+            write_string(simulator, address, input.as_bytes(), false)
         });
 
     pub static ref READLN: Instruction = instruction!(
@@ -132,16 +118,8 @@ lazy_static! {
                 return Ok(TransformationSequence::new_nullop(simulator)?);
             };
 
-            let mut words = pad_bytes(&bytes[0..read_count], simulator.get_word_size().value());
-            words.append(&mut vec![0u8; 4]);
-
             let address = input1.get(simulator)?.int_value() as usize;
-            let word_size = simulator.get_word_size().value();
-
-            let transformation = Transformable::MemoryTransformable(address)
-                .create_transformation(simulator, RawData::new(&words))?;
-
-            Ok(TransformationSequence::new_single(transformation))
+            write_string(simulator, address, &bytes[0..read_count], true)
         }
     );
 
@@ -153,30 +131,37 @@ lazy_static! {
                 return Ok(TransformationSequence::new_nullop(simulator)?);
             };
 
-            let mut words = pad_bytes(input.as_bytes(), simulator.get_word_size().value());
-            words.append(&mut vec![0u8; 4]);
-
             let address = input1.get(simulator)?.int_value() as usize;
-            let word_size = simulator.get_word_size().value();
-
-            let transformation = Transformable::MemoryTransformable(address)
-                .create_transformation(simulator, RawData::new(&words))?;
-
-            Ok(TransformationSequence::new_single(transformation))
+            write_string(simulator, address, input.as_bytes(), true)
         });
 }
 
-fn pad_bytes(bytes: &[u8], word_size: usize) -> Vec<u8> {
-    let pad_buffer = vec![0u8; word_size - 1];
-    bytes
+/// Creates the transformations which write a string to memory, one character per word.
+///
+/// # Arguments
+///
+/// * `simulator` - the simulator whose memory is written to.
+/// * `address` - the address of the first character.
+/// * `bytes` - the characters of the string.
+/// * `terminate` - whether a zero word is written after the string.
+fn write_string(
+    simulator: &Simulator,
+    address: usize,
+    bytes: &[u8],
+    terminate: bool,
+) -> Result<TransformationSequence, SimulatorError> {
+    let word_size = simulator.get_word_size();
+    let terminator: &[u8] = if terminate { &[0] } else { &[] };
+    let transformations = bytes
         .iter()
-        .map(|byte| {
-            let mut new_bytes = pad_buffer.clone();
-            new_bytes.push(*byte);
-            return new_bytes;
+        .chain(terminator)
+        .enumerate()
+        .map(|(index, byte)| {
+            Transformable::MemoryTransformable(address + index * word_size.value())
+                .create_transformation(simulator, RawData::from_int(*byte as i64, word_size))
         })
-        .flatten()
-        .collect()
+        .collect::<Result<Vec<_>, SimulatorError>>()?;
+    Ok(TransformationSequence::new(transformations))
 }
 
 /// Uses a boxed custom reader to read until whitespace or a size is reached.
@@ -213,12 +198,41 @@ fn read_to_sized<R: Reader>(
 }
 
 /// Registers the instructions found in this file
-pub fn register_instructions() {
-    register_instruction(&READI);
-    register_instruction(&READF);
-    register_instruction(&READC);
-    register_instruction(&READS);
-    register_instruction(&READS_UNSIZED);
-    register_instruction(&READLN);
-    register_instruction(&READLN_UNSIZED);
+pub fn group() -> InstructionGroup {
+    InstructionGroup::new("terminal-input")
+        .with(&READI.describe(
+            "Reads an integer from the input.",
+            "output = read integer",
+            "readi $t0",
+        ))
+        .with(&READF.describe(
+            "Reads a float from the input.",
+            "output = read float",
+            "readf $t0",
+        ))
+        .with(&READC.describe(
+            "Reads a character from the input, skipping whitespace.",
+            "output = read character",
+            "readc $t0",
+        ))
+        .with(&READS.describe(
+            "Reads a word of text into memory, storing at most `input2 - 1` characters and a terminator.",
+            "memory[input1 ..] = read word",
+            "reads $s0 32",
+        ))
+        .with(&READS_UNSIZED.describe(
+            "Reads a word of text into memory.",
+            "memory[input1 ..] = read word",
+            "reads $s0",
+        ))
+        .with(&READLN.describe(
+            "Reads a line of text into memory, storing at most `input2 - 1` characters and a terminator.",
+            "memory[input1 ..] = read line",
+            "readln $s0 80",
+        ))
+        .with(&READLN_UNSIZED.describe(
+            "Reads a line of text into memory.",
+            "memory[input1 ..] = read line",
+            "readln $s0",
+        ))
 }
@@ -1,24 +1,70 @@
 use std::any::TypeId;
 use std::fmt::{Debug, Formatter};
 
+use serde::Serialize;
+
 use crate::instructions::argument_type::ArgumentType;
+use crate::instructions::targets::input_target::InputTarget;
 use crate::simulation::simulator::Simulator;
 use crate::simulation::transform::transformation_sequence::TransformationSequence;
 use crate::util::error::SimulatorError;
 
-pub type TInstructionFunction = fn(
-    &mut Simulator,
-    &Vec<TypeId>,
-    &Vec<ArgumentType>,
-) -> Result<TransformationSequence, SimulatorError>;
+pub type TInstructionFunction =
+    fn(&mut Simulator, &[ArgumentType]) -> Result<TransformationSequence, SimulatorError>;
+
+/// Runs an instruction by writing its results straight to the simulator, rather than building the
+/// transformations which would let it be undone.
+pub type TDirectFunction = fn(&mut Simulator, &[ArgumentType]) -> Result<(), SimulatorError>;
 
-#[derive(Clone, PartialEq, Eq)]
+/// How an instruction uses one of its operands.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OperandRole {
+    /// The operand is only read, so it may be an immediate, a register, a dereference or a label.
+    Input,
+    /// The operand is written to, so it must be a register or a dereference.
+    Output,
+}
+
+/// A named operand of an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Operand {
+    pub name: String,
+    pub role: OperandRole,
+}
+
+/// The documentation of an instruction, which front ends show as help.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct InstructionInfo {
+    /// A one-sentence description of what the instruction does.
+    pub summary: String,
+    /// What the instruction does in terms of its operands, e.g. `output = input1 + input2`.
+    pub semantics: String,
+    /// A line of code which uses the instruction.
+    pub example: String,
+}
+
+#[derive(Clone)]
 pub struct Instruction {
     name: String,
     types: Vec<TypeId>,
     function: TInstructionFunction,
+    direct_function: Option<TDirectFunction>,
+    operand_names: Vec<String>,
+    info: InstructionInfo,
+}
+
+/// Instructions are the same when they have the same name and operand types, whatever function
+/// runs them or documentation is attached to them, since function pointers can't be reliably
+/// compared.
+impl PartialEq for Instruction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.types == other.types
+    }
 }
 
+impl Eq for Instruction {}
+
 impl Debug for Instruction {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str(std::format!("{:?}", self.types).as_str())
@@ -27,12 +73,79 @@ impl Debug for Instruction {
 
 impl Instruction {
     pub fn new(name: String, types: Vec<TypeId>, function: TInstructionFunction) -> Self {
+        let operand_names = (1..=types.len())
+            .map(|index| format!("operand{}", index))
+            .collect();
         Instruction {
             name,
             types,
             function,
+            direct_function: None,
+            operand_names,
+            info: InstructionInfo::default(),
+        }
+    }
+
+    /// Gives the instruction a way to run without being undone, which the simulator uses instead of
+    /// its function while undo is disabled; the `instruction!` macro adds one for instructions
+    /// whose body gives a value or a condition to branch on.
+    pub fn with_direct_function(mut self, direct_function: TDirectFunction) -> Self {
+        self.direct_function = Some(direct_function);
+        self
+    }
+
+    /// Names the operands, in order; the `instruction!` macro uses the names of its parameters.
+    pub fn with_operand_names(mut self, names: Vec<&str>) -> Self {
+        self.operand_names = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Creates a copy of the instruction with documentation attached.
+    ///
+    /// # Arguments
+    ///
+    /// * `summary` - a one-sentence description of what the instruction does.
+    /// * `semantics` - what the instruction does in terms of its operands.
+    /// * `example` - a line of code which uses the instruction.
+    pub fn describe(&self, summary: &str, semantics: &str, example: &str) -> Instruction {
+        let mut instruction = self.clone();
+        instruction.info = InstructionInfo {
+            summary: summary.to_string(),
+            semantics: semantics.to_string(),
+            example: example.to_string(),
+        };
+        instruction
+    }
+
+    pub fn get_info(&self) -> &InstructionInfo {
+        &self.info
+    }
+
+    pub fn get_operands(&self) -> Vec<Operand> {
+        self.operand_names
+            .iter()
+            .zip(&self.types)
+            .map(|(name, type_id)| Operand {
+                name: name.to_string(),
+                role: if *type_id == TypeId::of::<&mut InputTarget>() {
+                    OperandRole::Input
+                } else {
+                    OperandRole::Output
+                },
+            })
+            .collect()
+    }
+
+    /// Gets how the instruction is written, e.g. `add output input1 input2`.
+    pub fn get_signature(&self) -> String {
+        let mut signature = self.name.to_string();
+        for name in &self.operand_names {
+            signature.push(' ');
+            signature.push_str(name);
         }
+        signature
     }
+
     pub fn get_types(&self) -> &Vec<TypeId> {
         &self.types
     }
@@ -41,6 +154,10 @@ impl Instruction {
         &self.function
     }
 
+    pub fn get_direct_function(&self) -> Option<TDirectFunction> {
+        self.direct_function
+    }
+
     pub fn get_name(&self) -> &String {
         &self.name
     }
@@ -48,35 +165,125 @@ impl Instruction {
     pub fn call_function(
         &self,
         simulator: &mut Simulator,
-        arguments: &Vec<ArgumentType>,
+        arguments: &[ArgumentType],
     ) -> Result<TransformationSequence, SimulatorError> {
-        (self.function)(simulator, &self.types, arguments)
+        (self.function)(simulator, arguments)
     }
 }
 
+/// Defines an instruction from a function body with typed operands.
+///
+/// Each operand is declared as either an `InputTarget` or an `InputOutputTarget`, and the body
+/// returns the `TransformationSequence` which carries out the instruction. The name of the
+/// instruction is the name given first, without any leading underscores, so that instructions can
+/// share a name with Rust keywords, e.g. `_mod`.
+///
+/// Two shorter forms cover most instructions, and also let them run without building
+/// transformations while undo is disabled, see `Instruction::with_direct_function`:
+///
+/// * `|simulator: Simulator, output: InputOutputTarget, ...| -> output { ... }`, where the body
+///   returns the `RawData` to store in the named operand.
+/// * `|simulator: Simulator, ..., label: InputTarget| -> branch label { ... }`, where the body
+///   returns whether to continue the program at the named operand.
+///
+/// The macro can be used from other crates; see `instructions::extension` for an example.
 #[macro_export]
 macro_rules! instruction {
+    ($name:ident, |$simulator_name:ident: Simulator, $($names:ident: $types:ty),*| -> branch $label:ident $func:tt) =>
+    ({
+        fn branches(
+            $simulator_name: &mut $crate::simulation::simulator::Simulator,
+            $(#[allow(unused_mut, unused_variables)] mut $names: $types),*
+        ) -> Result<bool, $crate::util::error::SimulatorError> $func
+        fn $name(
+            simulator: &mut $crate::simulation::simulator::Simulator,
+            arguments: &[$crate::instructions::argument_type::ArgumentType],
+        ) -> Result<$crate::simulation::transform::transformation_sequence::TransformationSequence, $crate::util::error::SimulatorError> {
+            $crate::instruction!(@operands arguments, $($names: $types),*);
+            let label = $label.clone();
+            if !branches(simulator, $($names),*)? {
+                return Ok($crate::simulation::transform::transformation_sequence::TransformationSequence::new_empty());
+            }
+            let destination = $crate::instructions::targets::input_target::Input::get(&label, simulator)?;
+            let pc = $crate::simulation::transform::transformable::Transformable::InputOutputTransformable(
+                $crate::instructions::targets::input_output_target::InputOutputTarget::RegisterInputOutput($crate::simulation::registry::PC_NUMBER),
+            );
+            Ok($crate::simulation::transform::transformation_sequence::TransformationSequence::new_single(
+                pc.create_transformation(simulator, destination)?,
+            ))
+        }
+        fn run_directly(
+            simulator: &mut $crate::simulation::simulator::Simulator,
+            arguments: &[$crate::instructions::argument_type::ArgumentType],
+        ) -> Result<(), $crate::util::error::SimulatorError> {
+            $crate::instruction!(@operands arguments, $($names: $types),*);
+            let label = $label.clone();
+            if branches(simulator, $($names),*)? {
+                let destination = $crate::instructions::targets::input_target::Input::get(&label, simulator)?;
+                simulator.get_registers_mut().get_pc_mut().set_data(destination);
+            }
+            Ok(())
+        }
+        $crate::instruction!(@new $name, $($names: $types),*).with_direct_function(run_directly)
+    });
+    ($name:ident, |$simulator_name:ident: Simulator, $($names:ident: $types:ty),*| -> $output:ident $func:tt) =>
+    ({
+        fn value(
+            $simulator_name: &mut $crate::simulation::simulator::Simulator,
+            $(#[allow(unused_mut, unused_variables)] mut $names: $types),*
+        ) -> Result<$crate::util::raw_data::RawData, $crate::util::error::SimulatorError> $func
+        fn $name(
+            simulator: &mut $crate::simulation::simulator::Simulator,
+            arguments: &[$crate::instructions::argument_type::ArgumentType],
+        ) -> Result<$crate::simulation::transform::transformation_sequence::TransformationSequence, $crate::util::error::SimulatorError> {
+            $crate::instruction!(@operands arguments, $($names: $types),*);
+            let output = $crate::simulation::transform::transformable::Transformable::InputOutputTransformable($output);
+            let data = value(simulator, $($names),*)?;
+            Ok($crate::simulation::transform::transformation_sequence::TransformationSequence::new_single(
+                output.create_transformation(simulator, data)?,
+            ))
+        }
+        fn run_directly(
+            simulator: &mut $crate::simulation::simulator::Simulator,
+            arguments: &[$crate::instructions::argument_type::ArgumentType],
+        ) -> Result<(), $crate::util::error::SimulatorError> {
+            $crate::instruction!(@operands arguments, $($names: $types),*);
+            let output = $output;
+            let data = value(simulator, $($names),*)?;
+            $crate::instructions::targets::output_target::Output::set(&output, simulator, data)
+        }
+        $crate::instruction!(@new $name, $($names: $types),*).with_direct_function(run_directly)
+    });
     ($name:ident, |$simulator_name:ident: Simulator, $($names:ident: $types:ty),*| $func:tt) =>
     ({
-        #[allow(unused_mut)]
-        let mut v: Vec<std::any::TypeId> = Vec::new();
-        $(v.push(std::any::TypeId::of::<&mut $types>());)*
-        fn $name($simulator_name: &mut crate::simulation::simulator::Simulator, types: &Vec<std::any::TypeId>, arguments: &Vec<crate::instructions::argument_type::ArgumentType>) -> Result<crate::simulation::transform::transformation_sequence::TransformationSequence, crate::util::error::SimulatorError> {
-            let mut _counter: usize = 0;
-            $(
-                #[allow(unused_mut)]
-                let mut $names: $types = match arguments[_counter].clone().try_into() {
-                    Ok(value) => value,
-                    Err(error) => return Err(error.into()),
-                };
-                _counter = _counter + 1;
-            )*
+        fn $name(
+            $simulator_name: &mut $crate::simulation::simulator::Simulator,
+            arguments: &[$crate::instructions::argument_type::ArgumentType],
+        ) -> Result<$crate::simulation::transform::transformation_sequence::TransformationSequence, $crate::util::error::SimulatorError> {
+            $crate::instruction!(@operands arguments, $($names: $types),*);
             #[allow(unused_braces)]
             $func
         }
-        let mut instruction_name = std::stringify!($name);
-        instruction_name = instruction_name.trim_start_matches('_');
-        Instruction::new( instruction_name.to_string(), v, $name )
+        $crate::instruction!(@new $name, $($names: $types),*)
     });
+    (@operands $arguments:ident, $($names:ident: $types:ty),*) => {
+        let mut _index: usize = 0;
+        $(
+            #[allow(unused_mut)]
+            let mut $names: $types = match (&$arguments[_index]).try_into() {
+                Ok(value) => value,
+                Err(error) => return Err($crate::util::error::InternalError::into(error)),
+            };
+            _index += 1;
+        )*
+    };
+    (@new $name:ident, $($names:ident: $types:ty),*) => {
+        $crate::instructions::instruction::Instruction::new(
+            std::stringify!($name).trim_start_matches('_').to_string(),
+            vec![$(std::any::TypeId::of::<&mut $types>()),*],
+            $name,
+        )
+        .with_operand_names(vec![$(std::stringify!($names)),*])
+    };
 }
 pub use instruction;
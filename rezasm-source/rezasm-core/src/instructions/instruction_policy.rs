@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::instructions::instruction_set::InstructionSet;
+use crate::parser::line::Line;
+use crate::parser::linker::LinkedFile;
+use crate::util::error::ParserError;
+
+/// Restricts which instructions a program may use, e.g. for an assignment which asks students to
+/// implement multiplication without `mul`.
+///
+/// Every entry is either the name of an instruction or the name of a category, which is the group
+/// the instruction was added to its instruction set with (such as `arithmetic` or `branch`). A
+/// policy is usually loaded from a JSON file of the form
+///
+/// ```json
+/// { "allow": ["arithmetic", "branch", "call"], "forbid": ["mul"], "limits": { "call": 3 } }
+/// ```
+///
+/// where every field is optional. The default policy allows everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InstructionPolicy {
+    /// If present, the only instructions and categories which may be used.
+    allow: Option<Vec<String>>,
+    /// Instructions and categories which may not be used, even if they are allowed.
+    forbid: Vec<String>,
+    /// The most times an instruction, or all of the instructions in a category, may be used.
+    limits: HashMap<String, usize>,
+}
+
+impl InstructionPolicy {
+    /// Creates a policy which allows every instruction.
+    pub fn new() -> InstructionPolicy {
+        InstructionPolicy::default()
+    }
+
+    pub fn from_json(json: &str) -> Result<InstructionPolicy, ParserError> {
+        serde_json::from_str(json).map_err(|e| ParserError::InvalidPolicyError(e.to_string()))
+    }
+
+    /// Reads a policy from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<InstructionPolicy, ParserError> {
+        let path_string = path.as_ref().to_string_lossy().to_string();
+        let json = fs::read_to_string(path).map_err(|e| {
+            ParserError::InvalidPolicyError(format!("could not read `{}`: {}", path_string, e))
+        })?;
+        InstructionPolicy::from_json(&json)
+    }
+
+    /// Allows an instruction or category; once anything is allowed, everything else is forbidden.
+    pub fn allow(&mut self, name: &str) {
+        self.allow
+            .get_or_insert_with(Vec::new)
+            .push(name.to_string());
+    }
+
+    pub fn forbid(&mut self, name: &str) {
+        self.forbid.push(name.to_string());
+    }
+
+    /// Limits how many times an instruction, or all of the instructions in a category, may be used.
+    pub fn limit(&mut self, name: &str, count: usize) {
+        self.limits.insert(name.to_string(), count);
+    }
+
+    /// Adds a limit written as `name=count`, as it is given on the command line.
+    pub fn parse_limit(&mut self, limit: &str) -> Result<(), ParserError> {
+        let (name, count) = limit
+            .split_once('=')
+            .and_then(|(name, count)| Some((name.trim(), count.trim().parse().ok()?)))
+            .ok_or(ParserError::InvalidPolicyError(format!(
+                "limit `{}` must be written as `name=count`",
+                limit
+            )))?;
+        self.limit(name, count);
+        Ok(())
+    }
+
+    /// Whether this policy allows every instruction.
+    pub fn is_unrestricted(&self) -> bool {
+        self.allow.is_none() && self.forbid.is_empty() && self.limits.is_empty()
+    }
+
+    /// Checks that every entry names an instruction or category of an instruction set, so that a
+    /// misspelled entry does not silently allow or forbid nothing.
+    pub fn validate(&self, instructions: &InstructionSet) -> Result<(), ParserError> {
+        let entries = self
+            .allow
+            .iter()
+            .flatten()
+            .chain(self.forbid.iter())
+            .chain(self.limits.keys());
+        for entry in entries {
+            if !instructions.contains_name(entry) && !instructions.contains_category(entry) {
+                return Err(ParserError::InvalidPolicyError(format!(
+                    "`{}` is neither an instruction nor a category",
+                    entry
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a program may use an instruction.
+    pub fn is_allowed(&self, name: &str, instructions: &InstructionSet) -> bool {
+        let category = instructions.get_category(name);
+        let matches = |entry: &String| entry == name || Some(entry) == category;
+        let allowed = match &self.allow {
+            None => true,
+            Some(allow) => allow.iter().any(matches),
+        };
+        allowed && !self.forbid.iter().any(matches)
+    }
+
+    /// Checks the lines of a program against this policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - the lines of the program, each with the line number it appears on.
+    /// * `instructions` - the instruction set the lines were parsed with.
+    ///
+    /// # Returns
+    ///
+    /// `ForbiddenInstructionError` or `InstructionLimitError` for the first line which breaks the
+    /// policy.
+    pub fn check<'a>(
+        &self,
+        lines: impl IntoIterator<Item = (usize, &'a Line)>,
+        instructions: &InstructionSet,
+    ) -> Result<(), ParserError> {
+        if self.is_unrestricted() {
+            return Ok(());
+        }
+        let mut counts: HashMap<&String, usize> = HashMap::new();
+        for (line_number, line) in lines {
+            self.check_line(&mut counts, line_number, line, instructions)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the files of a linked program against this policy like `check`, counting the uses of
+    /// instructions across every file, so that a limit cannot be avoided by splitting a program up.
+    ///
+    /// A violation in any file but the first, which is the main file, is reported as an
+    /// `ImportedFileError` naming the file.
+    pub fn check_files(
+        &self,
+        files: &[LinkedFile],
+        instructions: &InstructionSet,
+    ) -> Result<(), ParserError> {
+        if self.is_unrestricted() {
+            return Ok(());
+        }
+        let mut counts: HashMap<&String, usize> = HashMap::new();
+        for (index, file) in files.iter().enumerate() {
+            for (line_number, line) in &file.lines {
+                self.check_line(&mut counts, *line_number, line, instructions)
+                    .map_err(|error| match index {
+                        0 => error,
+                        _ => ParserError::ImportedFileError(file.name.to_string(), Box::new(error)),
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a line against this policy, adding its instruction to the uses counted so far.
+    fn check_line<'a>(
+        &'a self,
+        counts: &mut HashMap<&'a String, usize>,
+        line_number: usize,
+        line: &Line,
+        instructions: &InstructionSet,
+    ) -> Result<(), ParserError> {
+        let Line::Instruction(instruction, _) = line else {
+            return Ok(());
+        };
+        let name = instruction.get_name();
+        if !self.is_allowed(name, instructions) {
+            return Err(ParserError::ForbiddenInstructionError(
+                name.to_string(),
+                line_number,
+            ));
+        }
+        let category = instructions.get_category(name);
+        for (entry, limit) in &self.limits {
+            if entry != name && Some(entry) != category {
+                continue;
+            }
+            let count = counts.entry(entry).or_default();
+            *count += 1;
+            if *count > *limit {
+                return Err(ParserError::InstructionLimitError(
+                    name.to_string(),
+                    line_number,
+                    entry.to_string(),
+                    *limit,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::lexer::parse_lines;
+    use crate::util::word_size::WordSize;
+
+    #[test]
+    fn test_instruction_policy() {
+        let word_size = WordSize::default();
+        let program = "
+        add $t0 1 2
+        # comments and blank lines still count towards line numbers
+
+        mul $t1 $t0 3
+        call f
+        call f
+        f:";
+
+        let mut instructions = InstructionSet::standard();
+        let policy = InstructionPolicy::from_json(
+            r#"{ "allow": ["arithmetic", "function"], "forbid": ["mul"] }"#,
+        )
+        .unwrap();
+        instructions.set_policy(policy).unwrap();
+        assert!(matches!(
+            parse_lines(program, &word_size, &instructions),
+            Err(ParserError::ForbiddenInstructionError(name, 5)) if name == "mul"
+        ));
+        assert!(matches!(
+            parse_lines("printi $t0", &word_size, &instructions),
+            Err(ParserError::ForbiddenInstructionError(name, 1)) if name == "printi"
+        ));
+
+        let mut policy = InstructionPolicy::new();
+        policy.parse_limit("call=1").unwrap();
+        instructions.set_policy(policy).unwrap();
+        assert!(matches!(
+            parse_lines(program, &word_size, &instructions),
+            Err(ParserError::InstructionLimitError(name, 7, _, 1)) if name == "call"
+        ));
+
+        let mut policy = InstructionPolicy::new();
+        policy.limit("function", 2);
+        instructions.set_policy(policy).unwrap();
+        assert!(parse_lines(program, &word_size, &instructions).is_ok());
+
+        let mut policy = InstructionPolicy::new();
+        policy.forbid("mull");
+        assert!(instructions.set_policy(policy).is_err());
+        assert!(InstructionPolicy::from_json(r#"{ "deny": ["mul"] }"#).is_err());
+        assert!(InstructionPolicy::new().parse_limit("call").is_err());
+    }
+}
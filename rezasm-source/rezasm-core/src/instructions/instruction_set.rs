@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+
+use crate::instructions::implementation::standard_groups;
+use crate::instructions::instruction::Instruction;
+use crate::instructions::instruction_policy::InstructionPolicy;
+use crate::instructions::reference::InstructionReference;
+use crate::util::error::ParserError;
+
+/// A named collection of instructions which are added to an `InstructionSet` together, e.g. all of
+/// the arithmetic instructions, or the custom instructions of a course.
+#[derive(Debug, Clone)]
+pub struct InstructionGroup {
+    name: String,
+    instructions: Vec<Arc<Instruction>>,
+}
+
+impl InstructionGroup {
+    pub fn new(name: &str) -> InstructionGroup {
+        InstructionGroup {
+            name: name.to_string(),
+            instructions: Vec::new(),
+        }
+    }
+
+    /// Adds an instruction to the group.
+    pub fn with(mut self, instruction: &Instruction) -> InstructionGroup {
+        self.instructions.push(Arc::new(instruction.clone()));
+        self
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_instructions(&self) -> &Vec<Arc<Instruction>> {
+        &self.instructions
+    }
+}
+
+lazy_static! {
+    static ref STANDARD: InstructionSet = {
+        let mut instruction_set = InstructionSet::new();
+        for group in standard_groups() {
+            instruction_set
+                .add_group(&group)
+                .expect("the standard instruction groups must not conflict");
+        }
+        instruction_set
+    };
+}
+
+/// The instructions which can be used in a program.
+///
+/// Each simulator owns an instruction set, which is used to parse the lines added to it, so
+/// simulators can run different dialects side by side. An instruction is identified by its name
+/// and its number of arguments, so the same name can be used for instructions which take a
+/// different number of arguments.
+///
+/// An instruction set can also carry an `InstructionPolicy`, which restricts the instructions that
+/// `parse_lines` accepts without removing them from the set.
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    instructions: HashMap<String, Vec<Arc<Instruction>>>,
+    groups: Vec<String>,
+    categories: HashMap<String, String>,
+    policy: InstructionPolicy,
+}
+
+impl InstructionSet {
+    /// Creates an instruction set without any instructions.
+    pub fn new() -> InstructionSet {
+        InstructionSet::default()
+    }
+
+    /// Creates an instruction set with all of the built-in instruction groups.
+    pub fn standard() -> InstructionSet {
+        STANDARD.clone()
+    }
+
+    /// Adds every instruction of a group, or none of them if any would conflict.
+    ///
+    /// # Returns
+    ///
+    /// `InstructionConflictError` if the group has an instruction with the same name and number of
+    /// arguments as one which is already in the set, but different operand types.
+    pub fn add_group(&mut self, group: &InstructionGroup) -> Result<(), ParserError> {
+        for instruction in group.get_instructions() {
+            self.check_conflict(instruction)?;
+        }
+        for instruction in group.get_instructions() {
+            self.categories
+                .entry(instruction.get_name().to_string())
+                .or_insert_with(|| group.get_name().to_string());
+            self.insert(instruction.clone());
+        }
+        if !self.groups.contains(group.get_name()) {
+            self.groups.push(group.get_name().to_string());
+        }
+        Ok(())
+    }
+
+    /// Adds a single instruction. Adding an instruction which is already in the set, with the same
+    /// name and operand types, does nothing.
+    ///
+    /// # Returns
+    ///
+    /// `InstructionConflictError` if an instruction with the same name and number of arguments, but
+    /// different operand types, is already in the set.
+    pub fn add_instruction(&mut self, instruction: Instruction) -> Result<(), ParserError> {
+        let instruction = Arc::new(instruction);
+        self.check_conflict(&instruction)?;
+        self.insert(instruction);
+        Ok(())
+    }
+
+    /// Gets the names of the groups which have been added, in the order they were added.
+    pub fn get_group_names(&self) -> &Vec<String> {
+        &self.groups
+    }
+
+    pub fn get_instruction(
+        &self,
+        name: &str,
+        argc: usize,
+    ) -> Result<&Arc<Instruction>, ParserError> {
+        match self.instructions.get(name) {
+            None => Err(ParserError::InvalidInstructionError(name.to_string())),
+            Some(group) => group
+                .iter()
+                .find(|attempt| attempt.get_types().len() == argc)
+                .ok_or(ParserError::InvalidArgumentsCountError(
+                    name.to_string(),
+                    argc,
+                )),
+        }
+    }
+
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.instructions.contains_key(name)
+    }
+
+    /// Gets the references of every instruction, ordered by the group they were added with and
+    /// then by name and number of operands; instructions added on their own come last.
+    pub fn get_references(&self) -> Vec<InstructionReference> {
+        let mut references: Vec<InstructionReference> = self
+            .instructions
+            .values()
+            .flatten()
+            .map(|instruction| {
+                InstructionReference::new(instruction, self.get_category(instruction.get_name()))
+            })
+            .collect();
+        let group_index = |reference: &InstructionReference| {
+            reference
+                .category
+                .as_ref()
+                .and_then(|category| self.groups.iter().position(|group| group == category))
+                .unwrap_or(self.groups.len())
+        };
+        references.sort_by(|a, b| {
+            group_index(a)
+                .cmp(&group_index(b))
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.operands.len().cmp(&b.operands.len()))
+        });
+        references
+    }
+
+    /// Gets the references of every instruction with a name, one for each number of operands.
+    pub fn find_references(&self, name: &str) -> Vec<InstructionReference> {
+        let mut references: Vec<InstructionReference> = self
+            .instructions
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|instruction| InstructionReference::new(instruction, self.get_category(name)))
+            .collect();
+        references.sort_by_key(|reference| reference.operands.len());
+        references
+    }
+
+    /// Gets the category of an instruction, which is the name of the first group it was added
+    /// with; instructions added on their own have no category.
+    pub fn get_category(&self, name: &str) -> Option<&String> {
+        self.categories.get(name)
+    }
+
+    pub fn contains_category(&self, category: &str) -> bool {
+        self.groups.iter().any(|group| group == category)
+    }
+
+    pub fn get_policy(&self) -> &InstructionPolicy {
+        &self.policy
+    }
+
+    /// Restricts the instructions which programs parsed with this set may use.
+    ///
+    /// # Returns
+    ///
+    /// `InvalidPolicyError` if the policy names something which is neither an instruction nor a
+    /// category of this set.
+    pub fn set_policy(&mut self, policy: InstructionPolicy) -> Result<(), ParserError> {
+        policy.validate(self)?;
+        self.policy = policy;
+        Ok(())
+    }
+
+    fn check_conflict(&self, instruction: &Instruction) -> Result<(), ParserError> {
+        let conflict = self
+            .instructions
+            .get(instruction.get_name())
+            .into_iter()
+            .flatten()
+            .any(|existing| {
+                existing.get_types().len() == instruction.get_types().len()
+                    && existing.as_ref() != instruction
+            });
+        if conflict {
+            Err(ParserError::InstructionConflictError(
+                instruction.get_name().to_string(),
+                instruction.get_types().len(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn insert(&mut self, instruction: Arc<Instruction>) {
+        let group = self
+            .instructions
+            .entry(instruction.get_name().to_string())
+            .or_default();
+        if !group.contains(&instruction) {
+            group.push(instruction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::instruction::instruction;
+    use crate::instructions::targets::input_output_target::InputOutputTarget;
+    use crate::parser::lexer::parse_lines;
+    use crate::simulation::transform::transformation_sequence::TransformationSequence;
+    use crate::util::word_size::WordSize;
+
+    #[test]
+    fn test_instruction_set() {
+        let mut instruction_set = InstructionSet::standard();
+        assert_eq!(
+            instruction_set
+                .get_instruction("add", 3)
+                .unwrap()
+                .get_name(),
+            "add"
+        );
+        assert!(instruction_set
+            .get_group_names()
+            .contains(&"arithmetic".to_string()));
+
+        let add = instruction!(add, |simulator: Simulator, output: InputOutputTarget| {
+            Ok(TransformationSequence::new_empty())
+        });
+        let group = InstructionGroup::new("custom").with(&add);
+        assert!(instruction_set.add_group(&group).is_ok());
+        assert!(instruction_set.add_group(&group).is_ok());
+        assert_eq!(
+            instruction_set
+                .get_instruction("add", 1)
+                .unwrap()
+                .get_types()
+                .len(),
+            1
+        );
+        assert!(InstructionSet::standard()
+            .get_instruction("add", 1)
+            .is_err());
+
+        let conflict = instruction!(
+            add,
+            |simulator: Simulator,
+             output: InputOutputTarget,
+             input1: InputOutputTarget,
+             input2: InputOutputTarget| { Ok(TransformationSequence::new_empty()) }
+        );
+        // documentation does not make an instruction a different one
+        let redescribed = instruction_set.get_instruction("add", 3).unwrap().describe(
+            "Adds.",
+            "output = input1 + input2",
+            "add $t0 $t1 1",
+        );
+        assert!(instruction_set.add_instruction(redescribed).is_ok());
+        assert!(matches!(
+            instruction_set.add_instruction(conflict),
+            Err(ParserError::InstructionConflictError(name, 3)) if name == "add"
+        ));
+
+        let empty = InstructionSet::new();
+        assert!(!empty.contains_name("add"));
+
+        let mut arithmetic = InstructionSet::new();
+        arithmetic.add_group(&standard_groups()[0]).unwrap();
+        let word_size = WordSize::default();
+        assert!(parse_lines("add $t0 1 2", &word_size, &arithmetic).is_ok());
+        assert!(matches!(
+            parse_lines("printi $t0", &word_size, &arithmetic),
+            Err(ParserError::InvalidInstructionError(name)) if name == "printi"
+        ));
+        assert!(parse_lines("printi $t0", &word_size, &InstructionSet::standard()).is_ok());
+    }
+}
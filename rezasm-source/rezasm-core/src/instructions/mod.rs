@@ -1,5 +1,8 @@
 pub mod argument_type;
+pub mod extension;
 pub mod implementation;
 pub mod instruction;
-pub mod instruction_registry;
+pub mod instruction_policy;
+pub mod instruction_set;
+pub mod reference;
 pub mod targets;
@@ -0,0 +1,196 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::instructions::instruction::{Instruction, Operand, OperandRole};
+
+/// Everything there is to know about one instruction of an instruction set, as shown in the
+/// instruction reference, command line help and editor hovers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstructionReference {
+    pub name: String,
+    pub category: Option<String>,
+    pub signature: String,
+    pub operands: Vec<Operand>,
+    pub summary: String,
+    pub semantics: String,
+    pub example: String,
+}
+
+impl InstructionReference {
+    pub fn new(instruction: &Instruction, category: Option<&String>) -> InstructionReference {
+        let info = instruction.get_info();
+        InstructionReference {
+            name: instruction.get_name().to_string(),
+            category: category.cloned(),
+            signature: instruction.get_signature(),
+            operands: instruction.get_operands(),
+            summary: info.summary.to_string(),
+            semantics: info.semantics.to_string(),
+            example: info.example.to_string(),
+        }
+    }
+
+    /// Renders the reference as a Markdown section, which is also what editors show on hover.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("### `{}`\n\n", self.signature);
+        if !self.summary.is_empty() {
+            let _ = write!(markdown, "{}\n\n", self.summary);
+        }
+        for operand in &self.operands {
+            let _ = writeln!(
+                markdown,
+                "- `{}`: {}",
+                operand.name,
+                role_name(operand.role)
+            );
+        }
+        if !self.operands.is_empty() {
+            markdown.push('\n');
+        }
+        if !self.semantics.is_empty() {
+            let _ = write!(markdown, "Semantics: `{}`\n\n", self.semantics);
+        }
+        if !self.example.is_empty() {
+            let _ = write!(markdown, "```\n{}\n```\n\n", self.example);
+        }
+        markdown
+    }
+}
+
+fn role_name(role: OperandRole) -> &'static str {
+    match role {
+        OperandRole::Input => "input",
+        OperandRole::Output => "output",
+    }
+}
+
+fn category_name(reference: &InstructionReference) -> &str {
+    reference.category.as_deref().unwrap_or("other")
+}
+
+/// Renders references as a plain text listing, one instruction per line.
+pub fn to_text(references: &[InstructionReference]) -> String {
+    let width = references
+        .iter()
+        .map(|reference| reference.signature.len())
+        .max()
+        .unwrap_or_default();
+    let mut text = String::new();
+    let mut category = None;
+    for reference in references {
+        if category != Some(category_name(reference)) {
+            category = Some(category_name(reference));
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            let _ = writeln!(text, "{}:", category_name(reference));
+        }
+        let _ = writeln!(
+            text,
+            "  {:<width$}  {}",
+            reference.signature,
+            reference.summary,
+            width = width
+        );
+    }
+    text
+}
+
+/// Renders references as a Markdown document, with a section for each category.
+pub fn to_markdown(references: &[InstructionReference]) -> String {
+    let mut markdown = String::from("# Instruction reference\n\n");
+    let mut category = None;
+    for reference in references {
+        if category != Some(category_name(reference)) {
+            category = Some(category_name(reference));
+            let _ = write!(markdown, "## {}\n\n", category_name(reference));
+        }
+        markdown.push_str(&reference.to_markdown());
+    }
+    markdown
+}
+
+/// Renders references as a standalone HTML document, with a section for each category.
+pub fn to_html(references: &[InstructionReference]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Instruction reference</title>\n</head>\n<body>\n<h1>Instruction reference</h1>\n",
+    );
+    let mut category = None;
+    for reference in references {
+        if category != Some(category_name(reference)) {
+            category = Some(category_name(reference));
+            let _ = writeln!(html, "<h2>{}</h2>", escape(category_name(reference)));
+        }
+        let _ = writeln!(
+            html,
+            "<h3 id=\"{}-{}\"><code>{}</code></h3>",
+            escape(&reference.name),
+            reference.operands.len(),
+            escape(&reference.signature)
+        );
+        if !reference.summary.is_empty() {
+            let _ = writeln!(html, "<p>{}</p>", escape(&reference.summary));
+        }
+        if !reference.operands.is_empty() {
+            html.push_str("<ul>\n");
+            for operand in &reference.operands {
+                let _ = writeln!(
+                    html,
+                    "<li><code>{}</code>: {}</li>",
+                    escape(&operand.name),
+                    role_name(operand.role)
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+        if !reference.semantics.is_empty() {
+            let _ = writeln!(
+                html,
+                "<p>Semantics: <code>{}</code></p>",
+                escape(&reference.semantics)
+            );
+        }
+        if !reference.example.is_empty() {
+            let _ = writeln!(html, "<pre>{}</pre>", escape(&reference.example));
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::instruction_set::InstructionSet;
+
+    #[test]
+    fn test_instruction_reference() {
+        let instructions = InstructionSet::standard();
+        let references = instructions.get_references();
+        assert!(references
+            .iter()
+            .all(|reference| !reference.summary.is_empty() && !reference.example.is_empty()));
+
+        let add = instructions.find_references("add");
+        assert_eq!(add.len(), 1);
+        assert_eq!(add[0].signature, "add output input1 input2");
+        assert_eq!(add[0].category.as_deref(), Some("arithmetic"));
+        assert_eq!(add[0].operands[0].role, OperandRole::Output);
+        assert_eq!(add[0].operands[1].role, OperandRole::Input);
+        assert_eq!(instructions.find_references("exit").len(), 2);
+
+        assert!(to_text(&references).starts_with("arithmetic:\n"));
+        assert!(to_markdown(&references).contains("### `add output input1 input2`"));
+        assert!(to_html(&references).contains("<h2>branch</h2>"));
+        assert_eq!(escape("a < b"), "a &lt; b");
+    }
+}
@@ -4,10 +4,12 @@ use crate::simulation::registry;
 use crate::simulation::simulator::Simulator;
 use crate::util::error::{ParserError, SimulatorError};
 use crate::util::raw_data::RawData;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 
 pub trait InputOutput: Input + Output {}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputOutputTarget {
     DereferenceInputOutput(usize, i64),
     RegisterInputOutput(usize),
@@ -67,7 +69,7 @@ impl InputOutputTarget {
             }
         };
         match register {
-            Ok(r) => Ok(r.get_data().clone()),
+            Ok(r) => Ok(*r.get_data()),
             Err(error) => Err(error),
         }
     }
@@ -87,3 +89,19 @@ impl InputOutputTarget {
         Ok(InputOutputTarget::RegisterInputOutput(register.clone()))
     }
 }
+
+impl Display for InputOutputTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputOutputTarget::DereferenceInputOutput(r, 0) => {
+                write!(f, "({})", registry::get_register_display_name(*r))
+            }
+            InputOutputTarget::DereferenceInputOutput(r, offset) => {
+                write!(f, "{}({})", offset, registry::get_register_display_name(*r))
+            }
+            InputOutputTarget::RegisterInputOutput(r) => {
+                f.write_str(&registry::get_register_display_name(*r))
+            }
+        }
+    }
+}
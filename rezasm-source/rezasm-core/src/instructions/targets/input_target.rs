@@ -1,4 +1,7 @@
 use std::any::Any;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
 
 use crate::simulation::registry;
 use crate::simulation::simulator::Simulator;
@@ -15,7 +18,7 @@ impl<T: Input> Target for T {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputTarget {
     ImmediateInput(RawData),
     RegisterInput(usize),
@@ -64,7 +67,7 @@ impl InputTarget {
         };
 
         match register {
-            Ok(r) => Ok(r.get_data().clone()),
+            Ok(r) => Ok(*r.get_data()),
             Err(error) => Err(error),
         }
     }
@@ -73,7 +76,7 @@ impl InputTarget {
 impl Input for InputTarget {
     fn get(&self, simulator: &Simulator) -> Result<RawData, SimulatorError> {
         match self {
-            InputTarget::ImmediateInput(x) => Ok(x.clone()),
+            InputTarget::ImmediateInput(x) => Ok(*x),
             InputTarget::LabelReferenceInput(s) => {
                 let line = simulator.get_label_line_number(s)?;
                 Ok(RawData::from_int(line, simulator.get_word_size()))
@@ -81,7 +84,7 @@ impl Input for InputTarget {
             InputTarget::StringInput(s) => simulator
                 .get_memory()
                 .get_string_immediate_address(s)
-                .map(|x| x.clone()),
+                .copied(),
             InputTarget::DereferenceInput(r, offset) => {
                 let data = self.register_data(simulator);
                 let address = match data {
@@ -102,3 +105,20 @@ impl Input for InputTarget {
         }
     }
 }
+
+impl Display for InputTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputTarget::ImmediateInput(data) => write!(f, "{}", data.int_value()),
+            InputTarget::RegisterInput(r) => f.write_str(&registry::get_register_display_name(*r)),
+            InputTarget::DereferenceInput(r, 0) => {
+                write!(f, "({})", registry::get_register_display_name(*r))
+            }
+            InputTarget::DereferenceInput(r, offset) => {
+                write!(f, "{}({})", offset, registry::get_register_display_name(*r))
+            }
+            InputTarget::LabelReferenceInput(label) => f.write_str(label),
+            InputTarget::StringInput(string) => write!(f, "{:?}", string),
+        }
+    }
+}
@@ -21,7 +21,6 @@ mod tests {
     use std::{fs, io};
 
     use super::*;
-    use crate::instructions::implementation::register_instructions;
     use crate::simulation::reader::DummyReader;
     use crate::simulation::reader_cell::ReaderCell;
     use parser::lexer::parse_lines;
@@ -31,7 +30,6 @@ mod tests {
 
     #[test]
     fn test_fibo() {
-        register_instructions();
         let word_size = WordSize::Eight;
         let file_name = "MainFile".to_string();
 
@@ -50,7 +48,8 @@ mod tests {
         let path = format!("{workspace}/example/fibonacci_jump.ez");
 
         let code = fs::read_to_string(&path).expect(format!("File {}: read failed", path).as_str());
-        let lines = parse_lines(&code, &word_size).expect("Lexing failed");
+        let lines =
+            parse_lines(&code, &word_size, simulator.get_instruction_set()).expect("Lexing failed");
         lines.into_iter().for_each(|line| {
             simulator
                 .add_line(line, file_name.clone())
@@ -0,0 +1,237 @@
+use crate::parser::lexer;
+
+/// The number of spaces instructions are indented by unless otherwise chosen.
+pub const DEFAULT_INDENT: usize = 4;
+
+/// A line of code split into what the formatter needs to lay it out again.
+#[derive(Debug)]
+enum SourceLine {
+    Blank,
+    /// A line with only a comment, which is indented like an instruction unless it started at the
+    /// start of the line.
+    Comment(Comment),
+    Label(Vec<String>, Option<Comment>),
+    Instruction(Vec<String>, Option<Comment>),
+}
+
+/// A comment at the end of a line, along with the column it started at.
+#[derive(Debug)]
+struct Comment {
+    column: usize,
+    text: String,
+}
+
+/// Rewrites code in a canonical form without changing what it means.
+///
+/// Labels are placed at the start of their lines, instructions are indented beneath them, and the
+/// mnemonics and operands of consecutive instructions are aligned into columns. Register names are
+/// written in lower case and operands are separated by spaces rather than commas. Comments at the
+/// end of consecutive instructions are aligned to the furthest column any of them was written at,
+/// or just past the longest of the instructions if that reaches further.
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    indent: usize,
+}
+
+impl Formatter {
+    pub fn new() -> Formatter {
+        Formatter::new_custom(DEFAULT_INDENT)
+    }
+
+    /// Creates a formatter which indents instructions by `indent` spaces.
+    pub fn new_custom(indent: usize) -> Formatter {
+        Formatter { indent }
+    }
+
+    /// Formats code, which does not need to parse, ending it with a single newline.
+    pub fn format(&self, code: &str) -> String {
+        let lines: Vec<SourceLine> = code.lines().map(split_line).collect();
+        let mut output: Vec<String> = Vec::with_capacity(lines.len());
+        let mut index = 0;
+        while index < lines.len() {
+            match &lines[index] {
+                SourceLine::Blank => output.push(String::new()),
+                SourceLine::Comment(comment) if comment.column == 0 => {
+                    output.push(comment.text.to_string())
+                }
+                SourceLine::Comment(comment) => {
+                    output.push(format!("{}{}", " ".repeat(self.indent), comment.text))
+                }
+                SourceLine::Label(tokens, comment) => {
+                    output.push(with_comment(tokens.join(" "), comment));
+                }
+                SourceLine::Instruction(..) => {
+                    let block_end = lines[index..]
+                        .iter()
+                        .position(|line| !matches!(line, SourceLine::Instruction(..)))
+                        .map_or(lines.len(), |offset| index + offset);
+                    self.format_block(&lines[index..block_end], &mut output);
+                    index = block_end;
+                    continue;
+                }
+            }
+            index += 1;
+        }
+
+        while output.last().is_some_and(|line| line.is_empty()) {
+            output.pop();
+        }
+        let mut formatted = output.join("\n");
+        formatted.push('\n');
+        formatted
+    }
+
+    /// Checks whether code is already in the form `format` would give it.
+    pub fn is_formatted(&self, code: &str) -> bool {
+        self.format(code) == code
+    }
+
+    /// Lays out consecutive instructions, padding each column but the last of every line to the
+    /// width of the widest entry in that column.
+    fn format_block(&self, block: &[SourceLine], output: &mut Vec<String>) {
+        let mut widths: Vec<usize> = Vec::new();
+        for line in block {
+            let SourceLine::Instruction(tokens, _) = line else {
+                continue;
+            };
+            for (column, token) in tokens.iter().enumerate().take(tokens.len() - 1) {
+                if widths.len() <= column {
+                    widths.push(0);
+                }
+                widths[column] = widths[column].max(token.chars().count());
+            }
+        }
+
+        let mut lines: Vec<(String, &Option<Comment>)> = Vec::with_capacity(block.len());
+        for line in block {
+            let SourceLine::Instruction(tokens, comment) = line else {
+                continue;
+            };
+            let mut code = " ".repeat(self.indent);
+            for (column, token) in tokens.iter().enumerate() {
+                code.push_str(token);
+                if column + 1 < tokens.len() {
+                    let padding = widths[column] - token.chars().count() + 1;
+                    code.push_str(&" ".repeat(padding));
+                }
+            }
+            lines.push((code, comment));
+        }
+
+        // comments share the furthest column any of them was written at, past the end of the code
+        let comment_column = lines
+            .iter()
+            .filter_map(|(code, comment)| {
+                let comment = comment.as_ref()?;
+                Some(comment.column.max(code.chars().count() + 1))
+            })
+            .max()
+            .unwrap_or(0);
+        for (code, comment) in lines {
+            output.push(match comment {
+                Some(comment) => {
+                    let padding = comment_column - code.chars().count();
+                    format!("{}{}{}", code, " ".repeat(padding), comment.text)
+                }
+                None => code,
+            });
+        }
+    }
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Formatter::new()
+    }
+}
+
+fn split_line(line: &str) -> SourceLine {
+    let (tokens, comment) = lexer::tokenize_line_with_comment(line);
+    let comment = comment.map(|(start, text)| Comment {
+        column: line[..start].chars().count(),
+        text: text.trim_end().to_string(),
+    });
+    let tokens: Vec<String> = tokens
+        .into_iter()
+        .map(|(_, token)| normalize_token(token))
+        .collect();
+    match (tokens.first(), comment) {
+        (None, None) => SourceLine::Blank,
+        (None, Some(comment)) => SourceLine::Comment(comment),
+        (Some(first), comment) if lexer::looks_like_label(first) => {
+            SourceLine::Label(tokens, comment)
+        }
+        (Some(_), comment) => SourceLine::Instruction(tokens, comment),
+    }
+}
+
+/// Writes the registers of a token in lower case, e.g. `$T0` as `$t0` and `8($SP)` as `8($sp)`.
+fn normalize_token(token: String) -> String {
+    if lexer::is_register(&token) {
+        return token.to_lowercase();
+    }
+    if lexer::looks_like_dereference(&token) {
+        if let (Some(start), Some(end)) = (token.find('('), token.rfind(')')) {
+            let register = token[start + 1..end].to_string();
+            if lexer::is_register(&register) {
+                return format!(
+                    "{}({}){}",
+                    &token[..start],
+                    register.to_lowercase(),
+                    &token[end + 1..]
+                );
+            }
+        }
+    }
+    token
+}
+
+/// Adds the comment which ended a line back to it, at its old column if there is room.
+fn with_comment(code: String, comment: &Option<Comment>) -> String {
+    let Some(comment) = comment else {
+        return code;
+    };
+    let width = code.chars().count();
+    if width < comment.column {
+        format!(
+            "{}{}{}",
+            code,
+            " ".repeat(comment.column - width),
+            comment.text
+        )
+    } else {
+        format!("{} {}", code, comment.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::instruction_set::InstructionSet;
+    use crate::parser::lexer::parse_lines;
+    use crate::util::word_size::WordSize;
+
+    #[test]
+    fn test_formatter() {
+        let code = "# fibonacci\nmove $A0, 10\n  fib:   # loop\n  # update\nadd $T2 $t0 $t1 # add\nmove $t0 $t1  # shift\n\tbne $t3 $a0 fib\n\n\nprints \"a # b\"\nload $t0 -8($SP)\n\n";
+        let expected = "# fibonacci\n    move $a0 10\nfib:     # loop\n    # update\n    add  $t2 $t0 $t1 # add\n    move $t0 $t1     # shift\n    bne  $t3 $a0 fib\n\n\n    prints \"a # b\"\n    load   $t0 -8($sp)\n";
+
+        let formatter = Formatter::new();
+        let formatted = formatter.format(code);
+        assert_eq!(formatted, expected);
+        assert!(formatter.is_formatted(&formatted));
+        assert!(!formatter.is_formatted(code));
+
+        let word_size = WordSize::default();
+        let instructions = InstructionSet::standard();
+        assert_eq!(
+            parse_lines(code, &word_size, &instructions).unwrap(),
+            parse_lines(&formatted, &word_size, &instructions).unwrap()
+        );
+
+        assert_eq!(
+            Formatter::new_custom(2).format("add $t0 $t0 1"),
+            "  add $t0 $t0 1\n"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::parser::lexer;
+
+/// The labels a file defines, along with the rules for which definition a reference means.
+///
+/// Besides global labels, a file may define local labels: numeric labels such as `1:`, which may
+/// be defined any number of times and are referred to as `1b` or `1f` for the nearest definition
+/// before or after the reference, and dot labels such as `.loop:`, which belong to the nearest
+/// global label before them, so that each function can have its own `.loop`.
+///
+/// Lines must be defined in order, as they are added to a program.
+#[derive(Debug, Clone, Default)]
+pub struct FileLabels {
+    // label -> line_number, where dot labels are named after the label they belong to, e.g.
+    // `outer.loop`
+    labels: HashMap<String, i64>,
+    // label -> the lines it is defined on, in order, for numeric labels
+    numeric_labels: HashMap<String, Vec<i64>>,
+    // the global labels and the lines they are defined on, in order
+    scopes: Vec<(i64, String)>,
+}
+
+impl FileLabels {
+    pub fn new() -> FileLabels {
+        FileLabels::default()
+    }
+
+    /// Defines a label on a line, returning the line it is already defined on instead if it is.
+    pub fn define(&mut self, label: &str, line_number: i64) -> Option<i64> {
+        if lexer::is_numeric_label(label) {
+            self.numeric_labels
+                .entry(label.to_string())
+                .or_default()
+                .push(line_number);
+            return None;
+        }
+
+        let key = self.get_key(label, line_number);
+        if let Some(defined) = self.labels.get(&key) {
+            return Some(*defined);
+        }
+        self.labels.insert(key, line_number);
+        if !lexer::is_dot_label(label) {
+            self.scopes.push((line_number, label.to_string()));
+        }
+        None
+    }
+
+    /// Finds the line of the definition a reference on a line means.
+    pub fn resolve(&self, reference: &str, line_number: i64) -> Option<i64> {
+        match lexer::get_numeric_label_reference(reference) {
+            Some((label, forward)) => {
+                let lines = self.numeric_labels.get(label)?;
+                if forward {
+                    lines.iter().find(|line| **line > line_number).copied()
+                } else {
+                    lines
+                        .iter()
+                        .rev()
+                        .find(|line| **line < line_number)
+                        .copied()
+                }
+            }
+            None => self
+                .labels
+                .get(&self.get_key(reference, line_number))
+                .copied(),
+        }
+    }
+
+    /// Gets the line a global label is defined on.
+    pub fn get(&self, label: &str) -> Option<i64> {
+        if lexer::is_dot_label(label) {
+            return None;
+        }
+        self.labels.get(label).copied()
+    }
+
+    /// Gets every definition, with dot labels named after the label they belong to and numeric
+    /// labels once for each time they are defined.
+    pub fn definitions(&self) -> Vec<(String, i64)> {
+        let numeric = self
+            .numeric_labels
+            .iter()
+            .flat_map(|(label, lines)| lines.iter().map(|line| (label.to_string(), *line)));
+        self.labels
+            .iter()
+            .map(|(label, line)| (label.to_string(), *line))
+            .chain(numeric)
+            .collect()
+    }
+
+    /// Names a dot label after the global label it belongs to on a line, or leaves any other label
+    /// as it is.
+    fn get_key(&self, label: &str, line_number: i64) -> String {
+        if !lexer::is_dot_label(label) {
+            return label.to_string();
+        }
+        match self
+            .scopes
+            .iter()
+            .rev()
+            .find(|(line, _)| *line < line_number)
+        {
+            Some((_, scope)) => format!("{}{}", scope, label),
+            None => label.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_labels() {
+        let mut labels = FileLabels::new();
+        for (line, label) in [
+            (0, "first"),
+            (1, ".loop"),
+            (3, "1"),
+            (5, "second"),
+            (6, ".loop"),
+        ] {
+            assert_eq!(labels.define(label, line), None);
+        }
+        assert_eq!(labels.define("1", 7), None);
+        assert_eq!(labels.define(".loop", 8), Some(6));
+        assert_eq!(labels.define("first", 9), Some(0));
+
+        assert_eq!(labels.resolve(".loop", 2), Some(1));
+        assert_eq!(labels.resolve(".loop", 9), Some(6));
+        assert_eq!(labels.resolve("1b", 3), None);
+        assert_eq!(labels.resolve("1b", 4), Some(3));
+        assert_eq!(labels.resolve("1f", 4), Some(7));
+        assert_eq!(labels.resolve("1f", 7), None);
+        assert_eq!(labels.resolve("second", 0), Some(5));
+        assert_eq!(labels.get("second"), Some(5));
+        assert_eq!(labels.get(".loop"), None);
+        assert_eq!(labels.definitions().len(), 6);
+    }
+}
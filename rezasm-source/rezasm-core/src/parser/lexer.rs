@@ -2,6 +2,7 @@ use regex::Regex;
 use std::str::FromStr;
 use std::sync::OnceLock;
 
+use crate::instructions::instruction_set::InstructionSet;
 use crate::parser::line::*;
 use crate::simulation::registry;
 use crate::util::error::ParserError;
@@ -160,13 +161,61 @@ pub fn is_label(token: &str) -> bool {
     match token.find(":") {
         None => false,
         Some(i) => {
-            token.len() > 1 && i == token.len() - 1 && all_alphanumeric_underscore(&token[..i])
+            token.len() > 1
+                && i == token.len() - 1
+                && (all_alphanumeric_underscore(&token[..i])
+                    || is_numeric_label(&token[..i])
+                    || is_dot_label(&token[..i]))
         }
     }
 }
 
+/// Checks if a label is a numeric local label, e.g. the `1` of `1:`, which can be defined any
+/// number of times and is referred to as `1b` or `1f`.
+pub fn is_numeric_label(label: &str) -> bool {
+    !label.is_empty() && label.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Checks if a label is local to the nearest global label before it, e.g. `.loop`.
+pub fn is_dot_label(label: &str) -> bool {
+    match label.strip_prefix('.') {
+        None => false,
+        Some(name) => !name.is_empty() && name.chars().all(|c| is_alphanumeric_underscore(&c)),
+    }
+}
+
+/// Splits a reference to a numeric local label into the label and whether it is the nearest
+/// definition after the reference rather than before it, e.g. `1f` into `1` and `true`.
+pub fn get_numeric_label_reference(token: &str) -> Option<(&str, bool)> {
+    let forward = match token.chars().last()? {
+        'f' => true,
+        'b' => false,
+        _ => return None,
+    };
+    let label = &token[..token.len() - 1];
+    is_numeric_label(label).then_some((label, forward))
+}
+
+/// Checks if a token refers to a label, either by its name, qualified by the name of the file
+/// which exports it, e.g. `math::sqrt`, or as a local label, e.g. `.loop` or `1b`.
 pub fn looks_like_label_reference(token: &String) -> bool {
-    all_alphanumeric_underscore(token)
+    if is_dot_label(token) || get_numeric_label_reference(token).is_some() {
+        return true;
+    }
+    match token.split_once("::") {
+        None => all_alphanumeric_underscore(token),
+        Some((file, label)) => {
+            !file.is_empty()
+                && !label.is_empty()
+                && all_alphanumeric_underscore(file)
+                && all_alphanumeric_underscore(label)
+        }
+    }
+}
+
+/// Checks if a token is a directive which makes a label visible to other files.
+pub fn is_export_directive(token: &str) -> bool {
+    token == ".global" || token == ".export"
 }
 
 pub fn is_register(token: &String) -> bool {
@@ -274,24 +323,73 @@ pub fn get_character_immediate(token: &String) -> Result<Token, ParserError> {
     }
 }
 
-pub fn parse_line(line: &String, word_size: &WordSize) -> Option<Result<Line, ParserError>> {
+pub fn parse_line(
+    line: &String,
+    word_size: &WordSize,
+    instructions: &InstructionSet,
+) -> Option<Result<Line, ParserError>> {
     let tokens = tokenize_line(line);
 
     if tokens.len() == 0 {
         None
     } else {
-        Some(Line::new(&tokens[0], (&tokens[1..]).to_vec(), word_size))
+        Some(Line::new(
+            &tokens[0],
+            (&tokens[1..]).to_vec(),
+            word_size,
+            instructions,
+        ))
     }
 }
 
-// TODO ake the simulator use this
-pub fn parse_lines(lines: &str, word_size: &WordSize) -> Result<Vec<Line>, ParserError> {
-    let mut output: Vec<Line> = Vec::new();
-    for s in lines.lines() {
-        output.push(match parse_line(&s.into(), word_size) {
-            Some(v) => v,
-            None => continue,
-        }?);
+/// Parses every line of a program and checks them against the policy of the instruction set.
+///
+/// Unlike `parse_line`, which only sees a single line, this enforces the whole policy, including
+/// its usage limits, and reports the line number of any violation.
+pub fn parse_lines(
+    lines: &str,
+    word_size: &WordSize,
+    instructions: &InstructionSet,
+) -> Result<Vec<Line>, ParserError> {
+    Ok(parse_numbered_lines(lines, word_size, instructions)?
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect())
+}
+
+/// Parses every line of a program like `parse_lines`, pairing each line with the (1-based) line
+/// number of the text it came from, since blank and comment lines are left out.
+pub fn parse_numbered_lines(
+    lines: &str,
+    word_size: &WordSize,
+    instructions: &InstructionSet,
+) -> Result<Vec<(usize, Line)>, ParserError> {
+    let output = parse_numbered_lines_unchecked(lines, word_size, instructions)?;
+    instructions.get_policy().check(
+        output
+            .iter()
+            .map(|(line_number, line)| (*line_number, line)),
+        instructions,
+    )?;
+    Ok(output)
+}
+
+/// Parses every line of a program like `parse_numbered_lines`, leaving the policy of the
+/// instruction set to be checked by the caller, e.g. once every file of a program has been parsed.
+pub(crate) fn parse_numbered_lines_unchecked(
+    lines: &str,
+    word_size: &WordSize,
+    instructions: &InstructionSet,
+) -> Result<Vec<(usize, Line)>, ParserError> {
+    let mut output: Vec<(usize, Line)> = Vec::new();
+    for (index, s) in lines.lines().enumerate() {
+        output.push((
+            index + 1,
+            match parse_line(&s.into(), word_size, instructions) {
+                Some(v) => v,
+                None => continue,
+            }?,
+        ));
     }
     Ok(output)
 }
@@ -355,18 +453,44 @@ pub fn is_numeric(token: &String) -> bool {
 }
 
 pub fn tokenize_line(text: &str) -> Vec<String> {
-    let mut tokens: Vec<String> = Vec::new();
+    tokenize_line_spans(text)
+        .into_iter()
+        .map(|(_, token)| token)
+        .collect()
+}
+
+/// Text from a line along with the byte offset it starts at.
+pub type Span = (usize, String);
+
+/// Splits a line into tokens like `tokenize_line`, along with the byte offset each token starts
+/// at, so that editors can find the tokens in the line.
+pub fn tokenize_line_spans(text: &str) -> Vec<Span> {
+    tokenize_line_with_comment(text).0
+}
+
+/// Splits a line into tokens like `tokenize_line_spans`, along with the comment which ends the
+/// line and the byte offset it starts at, so that the line can be written out again without
+/// losing anything.
+pub fn tokenize_line_with_comment(text: &str) -> (Vec<Span>, Option<Span>) {
+    let mut tokens: Vec<Span> = Vec::new();
+    let mut comment = None;
 
     let mut in_single_quotes = false;
     let mut in_double_quotes = false;
     let mut escape_next = false;
+    let mut start: usize = 0;
     let mut current: String = String::new();
 
-    for c in text.chars() {
+    for (index, c) in text.char_indices() {
         if c == '#' && !in_single_quotes && !in_double_quotes {
+            comment = Some((index, text[index..].to_string()));
             break;
         }
 
+        if current.is_empty() {
+            start = index;
+        }
+
         if escape_next {
             escape_next = false;
             current.push(c);
@@ -382,23 +506,21 @@ pub fn tokenize_line(text: &str) -> Vec<String> {
         } else if in_single_quotes || in_double_quotes || !(char::is_whitespace(c) || c == ',') {
             current.push(c);
         } else if current.len() > 0 {
-            tokens.push(current);
+            tokens.push((start, current));
             current = String::new();
         }
     }
 
     if current.len() > 0 {
-        tokens.push(current);
+        tokens.push((start, current));
     }
 
-    tokens
+    (tokens, comment)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::instructions::implementation::register_instructions;
-    use crate::instructions::instruction_registry::get_instruction;
     use crate::test_utils::workspace_root;
     use std::fs;
 
@@ -412,18 +534,32 @@ mod tests {
             ),
             "[\"add\", \"$t0\", \"1\", \"2\"]"
         );
+        assert_eq!(
+            tokenize_line_spans("  move $t0, 'a' # comment"),
+            vec![
+                (2, "move".to_string()),
+                (7, "$t0".to_string()),
+                (12, "'a'".to_string())
+            ]
+        );
+        assert_eq!(
+            tokenize_line_with_comment("prints \"#1\" # first"),
+            (
+                vec![(0, "prints".to_string()), (7, "\"#1\"".to_string())],
+                Some((12, "# first".to_string()))
+            )
+        );
     }
 
     #[test]
     fn test_parse_line() {
-        register_instructions();
         let word_size = WordSize::default();
-
-        let add = get_instruction("add", 3).expect("Instruction not found");
+        let instructions = InstructionSet::standard();
 
         let actual = parse_line(
             &"add $t0 1 2 # this - is = a # comment".to_string(),
             &word_size,
+            &instructions,
         )
         .expect("Parsing Empty")
         .expect("Parsing Error");
@@ -432,6 +568,7 @@ mod tests {
             "add",
             vec!["$t0".into(), "1".into(), "2".into()],
             &word_size,
+            &instructions,
         )
         .expect("New line failed");
 
@@ -440,6 +577,7 @@ mod tests {
         let actual = parse_line(
             &r#"move $s0 "This has a # character"  # and a # comment"#.to_string(),
             &word_size,
+            &instructions,
         )
         .expect("Parsing Empty")
         .expect("Parsing Error");
@@ -448,6 +586,7 @@ mod tests {
             "move",
             vec!["$s0".into(), r#""This has a # character""#.into()],
             &word_size,
+            &instructions,
         )
         .expect("New line failed");
 
@@ -456,26 +595,35 @@ mod tests {
 
     #[test]
     fn test_parse_lines() {
-        register_instructions();
         let code = r#"
             move $s0 "This has a # character"  # and an end of line comment
             prints $s0  # and another end of line comment
             # This is a full-line comment, and the next line is white space"#;
         let word_size = WordSize::default();
+        let instructions = InstructionSet::standard();
 
-        let actual = parse_lines(code, &word_size).expect("Parsing Error");
+        let actual = parse_lines(code, &word_size, &instructions).expect("Parsing Error");
 
         let expected = vec![
             Line::new(
                 "move",
                 vec!["$s0".into(), r#""This has a # character""#.into()],
                 &word_size,
+                &instructions,
             )
             .expect("New line failed"),
-            Line::new("prints", vec!["$s0".into()], &word_size).expect("New line failed"),
+            Line::new("prints", vec!["$s0".into()], &word_size, &instructions)
+                .expect("New line failed"),
         ];
 
         assert_eq!(expected, actual);
+
+        let numbers: Vec<usize> = parse_numbered_lines(code, &word_size, &instructions)
+            .expect("Parsing Error")
+            .into_iter()
+            .map(|(number, _)| number)
+            .collect();
+        assert_eq!(numbers, vec![2, 3]);
     }
 
     #[test]
@@ -491,8 +639,8 @@ mod tests {
 
     #[test]
     fn test_parse_fibo() {
-        register_instructions();
         let word_size = WordSize::default();
+        let instructions = InstructionSet::standard();
 
         let workspace = workspace_root()
             .to_str()
@@ -501,7 +649,7 @@ mod tests {
         let path = format!("{workspace}/example/fibonacci_jump.ez");
 
         let code = fs::read_to_string(&path).expect(&format!("File {path}: read failed"));
-        let actual = parse_lines(&code, &word_size).expect("Parsing failed");
+        let actual = parse_lines(&code, &word_size, &instructions).expect("Parsing failed");
 
         let expected_args = vec![
             ("move", vec!["$a0".to_string(), "10".into()]),
@@ -519,7 +667,9 @@ mod tests {
 
         let mut expected = vec![];
         for (inst, args) in expected_args {
-            expected.push(Line::new(inst, args, &word_size).expect("Failed to create Line"));
+            expected.push(
+                Line::new(inst, args, &word_size, &instructions).expect("Failed to create Line"),
+            );
         }
 
         assert_eq!(expected, actual);
@@ -1,7 +1,6 @@
 use crate::instructions::argument_type::ArgumentType;
 use crate::instructions::instruction::Instruction;
-use crate::instructions::instruction_registry;
-use crate::instructions::instruction_registry::is_instruction_name_registered;
+use crate::instructions::instruction_set::InstructionSet;
 use crate::instructions::targets::input_output_target::InputOutputTarget;
 use crate::instructions::targets::input_target::InputTarget;
 use crate::parser::lexer::*;
@@ -9,13 +8,16 @@ use crate::util::error::ParserError;
 use crate::util::word_size::WordSize;
 use std::any::TypeId;
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 
 use super::lexer;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Line {
-    Instruction(&'static Instruction, Vec<ArgumentType>),
+    Instruction(Arc<Instruction>, Vec<ArgumentType>),
     Label(String),
+    /// A `.global` or `.export` directive, which lets other files refer to a label of this one.
+    Export(String),
 }
 
 impl Line {
@@ -23,6 +25,7 @@ impl Line {
         instruction: &str,
         args: Vec<String>,
         word_size: &WordSize,
+        instructions: &InstructionSet,
     ) -> Result<Self, ParserError> {
         if looks_like_label(instruction) {
             return if is_label(instruction) {
@@ -32,7 +35,14 @@ impl Line {
             } else {
                 Err(ParserError::LabelDefinitionError(instruction.to_string()))
             };
-        } else if !is_instruction_name_registered(instruction) {
+        } else if is_export_directive(instruction) {
+            return match args.as_slice() {
+                [label] if all_alphanumeric_underscore(label) => {
+                    Ok(Line::Export(label.to_string()))
+                }
+                _ => Err(ParserError::DirectiveError(instruction.to_string())),
+            };
+        } else if !instructions.contains_name(instruction) {
             return Err(ParserError::InvalidInstructionError(
                 instruction.to_string(),
             ));
@@ -60,11 +70,11 @@ impl Line {
         }
 
         let mut arguments: Vec<ArgumentType> = Vec::new();
-        let instruction_retrieved =
-            match instruction_registry::get_instruction(instruction, args_out.len()) {
-                Ok(instruction) => instruction,
-                Err(e) => return Err(e),
-            };
+        let instruction_retrieved = match instructions.get_instruction(instruction, args_out.len())
+        {
+            Ok(instruction) => instruction.clone(),
+            Err(e) => return Err(e),
+        };
 
         for (index, (argument, type_of)) in args_out
             .iter()
@@ -110,36 +120,38 @@ impl Line {
     }
 }
 
+/// Displays the line as it would be written in source code, e.g. `add $t0 $t1 5` or `loop:`.
 impl Display for Line {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(
-            format!(
-                "{}",
-                match self {
-                    Line::Instruction(instruction, arguments) =>
-                        format!("Instruction {{ {:?} }}", instruction),
-                    Line::Label(label) => format!("Label {{ {} }}", label),
+        match self {
+            Line::Instruction(instruction, arguments) => {
+                f.write_str(instruction.get_name())?;
+                for argument in arguments {
+                    write!(f, " {}", argument)?;
                 }
-            )
-            .as_str(),
-        )
+                Ok(())
+            }
+            Line::Label(label) => write!(f, "{}:", label),
+            Line::Export(label) => write!(f, ".global {}", label),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::instructions::implementation::register_instructions;
-    use crate::instructions::instruction_registry::get_instruction;
     use crate::util::raw_data::RawData;
 
     #[test]
     fn test_new_line() {
         let word_size = WordSize::default();
 
-        register_instructions();
+        let instructions = InstructionSet::standard();
         let expected = Line::Instruction(
-            get_instruction("add", 3).expect("Instruction not found"),
+            instructions
+                .get_instruction("add", 3)
+                .expect("Instruction not found")
+                .clone(),
             vec![
                 ArgumentType::InputOutput(InputOutputTarget::RegisterInputOutput(22)),
                 ArgumentType::Input(InputTarget::ImmediateInput(RawData::from_int(
@@ -155,6 +167,7 @@ mod tests {
             "add",
             vec!["$t0".into(), "10".into(), "5".into()],
             &word_size,
+            &instructions,
         )
         .expect("Failed to create new line");
 
@@ -0,0 +1,308 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::instructions::argument_type::ArgumentType;
+use crate::instructions::instruction_set::InstructionSet;
+use crate::instructions::targets::input_target::InputTarget;
+use crate::parser::lexer;
+use crate::parser::line::Line;
+use crate::util::error::{IoError, ParserError, SimulatorError};
+use crate::util::io::RezasmFileReader;
+use crate::util::word_size::WordSize;
+
+/// A file of a program, parsed and ready to be added to a simulator.
+#[derive(Debug)]
+pub struct LinkedFile {
+    /// The name the file is known by in the program, which is the path it was found at.
+    pub name: String,
+    /// The lines of the file, each with the (1-based) line of the file it came from.
+    pub lines: Vec<(usize, Line)>,
+}
+
+/// Gathers the files a program imports when it is loaded, rather than as the imports are run.
+///
+/// An import names a path relative to the file which imports it or, failing that, to one of the
+/// search paths, which are tried in the order they were added. Each file is loaded once however
+/// many files import it, but a file may not import itself, even through other files.
+///
+/// The policy of the instruction set is checked once over every file of the program, rather than
+/// for each file on its own.
+#[derive(Debug, Clone, Default)]
+pub struct Linker {
+    search_paths: Vec<PathBuf>,
+    root: Option<PathBuf>,
+}
+
+impl Linker {
+    pub fn new() -> Linker {
+        Linker::default()
+    }
+
+    /// Adds a directory to look for imports in when they are not found beside the importing file.
+    pub fn add_search_path<P: AsRef<Path>>(&mut self, path: P) {
+        self.search_paths.push(path.as_ref().to_path_buf());
+    }
+
+    /// Refuses imports which are found outside of a directory, e.g. through `..` or a link.
+    pub fn set_root<P: AsRef<Path>>(&mut self, root: P) {
+        self.root = Some(root.as_ref().to_path_buf());
+    }
+
+    /// Reads and parses a program and every file it imports, the main file first.
+    pub fn link<P: AsRef<Path>>(
+        &self,
+        main_file: P,
+        word_size: &WordSize,
+        instructions: &InstructionSet,
+    ) -> Result<Vec<LinkedFile>, SimulatorError> {
+        let code = read_file(main_file.as_ref())?;
+        self.link_code(main_file, &code, word_size, instructions)
+    }
+
+    /// Parses a program whose main file has already been read, and every file it imports, the main
+    /// file first.
+    pub fn link_code<P: AsRef<Path>>(
+        &self,
+        main_file: P,
+        code: &str,
+        word_size: &WordSize,
+        instructions: &InstructionSet,
+    ) -> Result<Vec<LinkedFile>, SimulatorError> {
+        let main_file = main_file.as_ref();
+        let lines = lexer::parse_numbered_lines_unchecked(code, word_size, instructions)?;
+        let identity = identify(main_file);
+        let mut linking = Linking {
+            linker: self,
+            word_size,
+            instructions,
+            importers: vec![(identity.clone(), display(main_file))],
+            loaded: HashSet::from([identity]),
+            files: Vec::new(),
+        };
+        linking.add_file(main_file, lines)?;
+        instructions
+            .get_policy()
+            .check_files(&linking.files, instructions)?;
+        Ok(linking.files)
+    }
+
+    /// Finds the file an import names, beside the importing file or in the search paths.
+    fn resolve(&self, import: &str, importer: &Path) -> Result<PathBuf, SimulatorError> {
+        let directory = importer.parent().unwrap_or(Path::new(""));
+        let found = std::iter::once(directory.to_path_buf())
+            .chain(self.search_paths.iter().cloned())
+            .map(|directory| directory.join(import))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| {
+                ParserError::ImportNotFoundError(import.to_string(), display(importer))
+            })?;
+
+        if let Some(root) = &self.root {
+            let root = root
+                .canonicalize()
+                .map_err(|_| IoError::FileDoesNotExistError(display(root)))?;
+            if !identify(&found).starts_with(&root) {
+                return Err(ParserError::ImportOutsideRootError(
+                    import.to_string(),
+                    display(&root),
+                ))?;
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// The state of linking a program.
+struct Linking<'a> {
+    linker: &'a Linker,
+    word_size: &'a WordSize,
+    instructions: &'a InstructionSet,
+    /// The files which imported the file being added, from the main file, ending with that file.
+    importers: Vec<(PathBuf, String)>,
+    /// The files which have already been loaded.
+    loaded: HashSet<PathBuf>,
+    files: Vec<LinkedFile>,
+}
+
+impl Linking<'_> {
+    /// Adds a file followed by the files it imports which have not been loaded yet.
+    fn add_file(&mut self, path: &Path, lines: Vec<(usize, Line)>) -> Result<(), SimulatorError> {
+        let imports: Vec<String> = lines
+            .iter()
+            .filter_map(|(_, line)| get_import(line))
+            .collect();
+        self.files.push(LinkedFile {
+            name: display(path),
+            lines,
+        });
+
+        for import in imports {
+            let resolved = self.linker.resolve(&import, path)?;
+            let identity = identify(&resolved);
+            if let Some(start) = self
+                .importers
+                .iter()
+                .position(|(other, _)| *other == identity)
+            {
+                let mut cycle: Vec<String> = self.importers[start..]
+                    .iter()
+                    .map(|(_, name)| name.to_string())
+                    .collect();
+                cycle.push(display(&resolved));
+                return Err(ParserError::ImportCycleError(cycle.join(" -> ")))?;
+            }
+            if !self.loaded.insert(identity.clone()) {
+                continue;
+            }
+
+            let name = display(&resolved);
+            let code = read_file(&resolved)?;
+            let lines =
+                lexer::parse_numbered_lines_unchecked(&code, self.word_size, self.instructions)
+                    .map_err(|error| {
+                        ParserError::ImportedFileError(name.to_string(), Box::new(error))
+                    })?;
+            self.importers.push((identity, name));
+            self.add_file(&resolved, lines)?;
+            self.importers.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Gets the file an `import` line names, if the line is one.
+pub fn get_import(line: &Line) -> Option<String> {
+    match line {
+        Line::Instruction(instruction, arguments) if instruction.get_name() == "import" => {
+            match arguments.first() {
+                Some(ArgumentType::Input(InputTarget::StringInput(file))) => Some(file.to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Gets a path which is the same for every way of naming a file, as far as possible.
+fn identify(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn read_file(path: &Path) -> Result<String, SimulatorError> {
+    Ok(RezasmFileReader::new(path)?.lines()?.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::instructions::instruction_policy::InstructionPolicy;
+
+    #[test]
+    fn test_linker() {
+        let directory = std::env::temp_dir().join(format!("rezasm-linker-{}", std::process::id()));
+        let write = |name: &str, code: &str| {
+            let path = directory.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, code).unwrap();
+            path
+        };
+        let main = write(
+            "project/main.ez",
+            "import \"lib/math.ez\"\nimport \"util.ez\"\ncall square",
+        );
+        write(
+            "project/lib/math.ez",
+            "import \"../util.ez\"\nsquare:\nreturn",
+        );
+        write("project/util.ez", "\n\nhelper:\nreturn");
+        write("shared/io.ez", "print:\nreturn");
+        let word_size = WordSize::default();
+        let instructions = InstructionSet::standard();
+
+        let files = Linker::new()
+            .link(&main, &word_size, &instructions)
+            .unwrap();
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                display(&main),
+                display(&directory.join("project/lib/math.ez")),
+                display(&directory.join("project/lib/../util.ez")),
+            ]
+        );
+        assert_eq!(files[2].lines[0].0, 3);
+
+        // imports are found beside the importing file before the search paths
+        let main = write("project/uses_io.ez", "import \"io.ez\"");
+        let error = Linker::new()
+            .link(&main, &word_size, &instructions)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SimulatorError::ParserError(ParserError::ImportNotFoundError(..))
+        ));
+        let mut linker = Linker::new();
+        linker.add_search_path(directory.join("shared"));
+        assert_eq!(
+            linker.link(&main, &word_size, &instructions).unwrap().len(),
+            2
+        );
+        linker.set_root(directory.join("project"));
+        let error = linker.link(&main, &word_size, &instructions).unwrap_err();
+        assert!(matches!(
+            error,
+            SimulatorError::ParserError(ParserError::ImportOutsideRootError(..))
+        ));
+
+        let main = write("project/a.ez", "import \"b.ez\"");
+        write("project/b.ez", "import \"./a.ez\"");
+        let error = Linker::new()
+            .link(&main, &word_size, &instructions)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "import cycle: {} -> {} -> {}",
+                display(&main),
+                display(&directory.join("project/b.ez")),
+                display(&directory.join("project/./a.ez"))
+            )
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+    #[test]
+    fn test_linked_policy() {
+        let directory = std::env::temp_dir().join(format!("rezasm-policy-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let main = directory.join("main.ez");
+        fs::write(&main, "import \"lib.ez\"\ncall square\nexit").unwrap();
+        fs::write(directory.join("lib.ez"), "\nsquare:\ncall square\nreturn").unwrap();
+        let word_size = WordSize::default();
+        let mut instructions = InstructionSet::standard();
+        let mut policy = InstructionPolicy::new();
+        policy.parse_limit("call=1").unwrap();
+        instructions.set_policy(policy).unwrap();
+
+        // each file keeps to the limit on its own, but not together
+        let error = Linker::new()
+            .link(&main, &word_size, &instructions)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "in `{}`: instruction `call` on line 3 exceeds the limit of 1 uses of `call` set by the instruction policy",
+                display(&directory.join("lib.ez"))
+            )
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,670 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
+use crate::instructions::argument_type::ArgumentType;
+use crate::instructions::instruction::{Instruction, OperandRole};
+use crate::instructions::instruction_set::InstructionSet;
+use crate::instructions::targets::input_output_target::InputOutputTarget;
+use crate::instructions::targets::input_target::InputTarget;
+use crate::parser::labels::FileLabels;
+use crate::parser::lexer;
+use crate::parser::line::Line;
+use crate::simulation::registry;
+use crate::util::error::ParserError;
+use crate::util::word_size::WordSize;
+
+/// The registers which hold a value before the program starts, or which instructions set without
+/// naming them, so reading them is never a mistake.
+const PRESET_REGISTERS: [&str; 9] = [
+    registry::ZERO,
+    registry::PID,
+    registry::FID,
+    registry::PC,
+    registry::SP,
+    registry::RA,
+    registry::LO,
+    registry::HI,
+    registry::FFLAGS,
+];
+
+/// A mistake which the linter looks for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// A line which does not parse.
+    ParseError,
+    /// Code which no path through the program reaches, e.g. after a `jump`, `return` or `exit`.
+    UnreachableCode,
+    /// A label which nothing jumps to, branches to or calls.
+    UnusedLabel,
+    /// An instruction which writes to `$zero`, which can never change.
+    ZeroRegisterWrite,
+    /// A register which is read before anything has been written to it on any path.
+    UninitializedRead,
+    /// Code which runs on into a function instead of ending the program with `exit`.
+    MissingExit,
+    /// A call to a function which can never return.
+    CallWithoutReturn,
+}
+
+pub const ALL_LINT_RULES: [LintRule; 7] = [
+    LintRule::ParseError,
+    LintRule::UnreachableCode,
+    LintRule::UnusedLabel,
+    LintRule::ZeroRegisterWrite,
+    LintRule::UninitializedRead,
+    LintRule::MissingExit,
+    LintRule::CallWithoutReturn,
+];
+
+impl LintRule {
+    pub fn from(name: &str) -> Result<LintRule, ParserError> {
+        ALL_LINT_RULES
+            .iter()
+            .find(|rule| rule.get_name() == name)
+            .copied()
+            .ok_or_else(|| ParserError::UnknownLintRuleError(name.to_string()))
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            LintRule::ParseError => "parse-error",
+            LintRule::UnreachableCode => "unreachable-code",
+            LintRule::UnusedLabel => "unused-label",
+            LintRule::ZeroRegisterWrite => "zero-register-write",
+            LintRule::UninitializedRead => "uninitialized-read",
+            LintRule::MissingExit => "missing-exit",
+            LintRule::CallWithoutReturn => "call-without-return",
+        }
+    }
+}
+
+impl Display for LintRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.get_name())
+    }
+}
+
+/// A problem found in a line of code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    /// The (1-based) line of the code the problem is on.
+    pub line_number: usize,
+    pub message: String,
+}
+
+impl Display for LintFinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} [{}]", self.line_number, self.message, self.rule)
+    }
+}
+
+/// A line of code which is not blank, or `None` in place of a line which does not parse.
+#[derive(Debug)]
+struct LintLine {
+    line_number: usize,
+    line: Option<Line>,
+}
+
+/// How running a line can continue.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Flow {
+    /// On to the next line, e.g. an `add` or a label.
+    Next,
+    /// On to the next line or to a label, e.g. a `beq`.
+    Branch,
+    Jump,
+    Call,
+    Return,
+    Exit,
+}
+
+/// Looks for mistakes in code without running it.
+///
+/// The code is read as a graph of the lines which can run after each other: calls continue at the
+/// function they call, and returns continue after every call, since which call a return goes back
+/// to is not known without running the program.
+///
+/// Each file is linted on its own, so a call to a label the file does not define, such as one it
+/// imports, is assumed to return and to write any register, and an exported label is assumed to be
+/// called from other files with any register written.
+#[derive(Debug, Clone)]
+pub struct Linter {
+    instructions: InstructionSet,
+    word_size: WordSize,
+    disabled: HashSet<LintRule>,
+}
+
+impl Linter {
+    pub fn new() -> Linter {
+        Linter::new_custom(InstructionSet::standard(), WordSize::default())
+    }
+
+    pub fn new_custom(instructions: InstructionSet, word_size: WordSize) -> Linter {
+        Linter {
+            instructions,
+            word_size,
+            disabled: HashSet::new(),
+        }
+    }
+
+    /// Stops looking for one kind of mistake.
+    pub fn disable(&mut self, rule: LintRule) {
+        self.disabled.insert(rule);
+    }
+
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+
+    /// Finds the problems with code, in the order of the lines they are on.
+    pub fn lint(&self, code: &str) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        let mut lines = Vec::new();
+        for (index, text) in code.lines().enumerate() {
+            let line =
+                match lexer::parse_line(&text.to_string(), &self.word_size, &self.instructions) {
+                    None => continue,
+                    Some(Ok(line)) => Some(line),
+                    Some(Err(error)) => {
+                        findings.push(LintFinding {
+                            rule: LintRule::ParseError,
+                            line_number: index + 1,
+                            message: error.to_string(),
+                        });
+                        None
+                    }
+                };
+            lines.push(LintLine {
+                line_number: index + 1,
+                line,
+            });
+        }
+
+        let program = Program::new(lines);
+        findings.extend(program.unreachable_code());
+        findings.extend(program.unused_labels());
+        findings.extend(program.zero_register_writes());
+        findings.extend(program.uninitialized_reads());
+        findings.extend(program.missing_exits());
+        findings.extend(program.calls_without_return());
+        findings.retain(|finding| self.is_enabled(finding.rule));
+        findings.sort_by_key(|finding| finding.line_number);
+        findings
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Linter::new()
+    }
+}
+
+/// The lines of code along with the graph of which can run after which.
+struct Program {
+    lines: Vec<LintLine>,
+    labels: FileLabels,
+    successors: Vec<Vec<usize>>,
+    reachable: Vec<bool>,
+}
+
+impl Program {
+    fn new(lines: Vec<LintLine>) -> Program {
+        let mut labels = FileLabels::new();
+        for (index, line) in lines.iter().enumerate() {
+            if let Some(Line::Label(label)) = &line.line {
+                labels.define(label, index as i64);
+            }
+        }
+        let mut program = Program {
+            lines,
+            labels,
+            successors: Vec::new(),
+            reachable: Vec::new(),
+        };
+
+        let return_sites: Vec<usize> = (0..program.lines.len())
+            .filter(|index| program.flow(*index) == Flow::Call)
+            .map(|index| index + 1)
+            .filter(|index| *index < program.lines.len())
+            .collect();
+        program.successors = (0..program.lines.len())
+            .map(|index| {
+                let next = Some(index + 1).filter(|next| *next < program.lines.len());
+                let target = program.target(index);
+                match program.flow(index) {
+                    Flow::Next => next.into_iter().collect(),
+                    Flow::Branch => next.into_iter().chain(target).collect(),
+                    Flow::Call if target.is_none() => next.into_iter().collect(),
+                    Flow::Jump | Flow::Call => target.into_iter().collect(),
+                    Flow::Return => return_sites.clone(),
+                    Flow::Exit => Vec::new(),
+                }
+            })
+            .collect();
+        let entries = std::iter::once(0)
+            .chain(program.exported_labels())
+            .collect();
+        program.reachable = program.reach(entries, |index| program.successors[index].clone());
+        program
+    }
+
+    /// Gets the lines of the labels the file exports, which other files can call.
+    fn exported_labels(&self) -> Vec<usize> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| match &line.line {
+                Some(Line::Export(label)) => self.resolve(label, index),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn instruction(&self, index: usize) -> Option<(&Instruction, &Vec<ArgumentType>)> {
+        match &self.lines[index].line {
+            Some(Line::Instruction(instruction, arguments)) => Some((instruction, arguments)),
+            _ => None,
+        }
+    }
+
+    fn flow(&self, index: usize) -> Flow {
+        let Some((instruction, _)) = self.instruction(index) else {
+            return Flow::Next;
+        };
+        match instruction.get_name().as_str() {
+            "jump" => Flow::Jump,
+            "call" => Flow::Call,
+            "return" => Flow::Return,
+            "exit" => Flow::Exit,
+            _ if is_branch(instruction) => Flow::Branch,
+            _ => Flow::Next,
+        }
+    }
+
+    /// Gets the line a jump, branch or call goes to, if it names a label which exists.
+    fn target(&self, index: usize) -> Option<usize> {
+        let (_, arguments) = self.instruction(index)?;
+        arguments.iter().find_map(|argument| match argument {
+            ArgumentType::Input(InputTarget::LabelReferenceInput(label)) => {
+                self.resolve(label, index)
+            }
+            _ => None,
+        })
+    }
+
+    /// Gets the line of the label a line refers to, if it exists.
+    fn resolve(&self, label: &str, index: usize) -> Option<usize> {
+        self.labels
+            .resolve(label, index as i64)
+            .map(|line| line as usize)
+    }
+
+    /// Finds the lines which can be reached from any of the given lines through the given
+    /// successors.
+    fn reach(&self, starts: Vec<usize>, successors: impl Fn(usize) -> Vec<usize>) -> Vec<bool> {
+        let mut reached = vec![false; self.lines.len()];
+        let mut pending = starts;
+        while let Some(index) = pending.pop() {
+            if index >= self.lines.len() || reached[index] {
+                continue;
+            }
+            reached[index] = true;
+            pending.extend(successors(index));
+        }
+        reached
+    }
+
+    fn finding(&self, rule: LintRule, index: usize, message: String) -> LintFinding {
+        LintFinding {
+            rule,
+            line_number: self.lines[index].line_number,
+            message,
+        }
+    }
+
+    /// Reports the first instruction of each run of lines which can never run.
+    fn unreachable_code(&self) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        let mut in_run = false;
+        for index in 0..self.lines.len() {
+            if self.reachable[index] {
+                in_run = false;
+            } else if !in_run && self.instruction(index).is_some() {
+                in_run = true;
+                findings.push(self.finding(
+                    LintRule::UnreachableCode,
+                    index,
+                    "this code can never run".to_string(),
+                ));
+            }
+        }
+        findings
+    }
+
+    /// Reports labels which are neither referred to in the file nor exported to other files.
+    fn unused_labels(&self) -> Vec<LintFinding> {
+        let mut used: HashSet<usize> = HashSet::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            let references: Vec<&String> = match &line.line {
+                Some(Line::Instruction(_, arguments)) => arguments
+                    .iter()
+                    .filter_map(|argument| match argument {
+                        ArgumentType::Input(InputTarget::LabelReferenceInput(label)) => Some(label),
+                        _ => None,
+                    })
+                    .collect(),
+                Some(Line::Export(label)) => vec![label],
+                _ => Vec::new(),
+            };
+            used.extend(
+                references
+                    .into_iter()
+                    .filter_map(|label| self.resolve(label, index)),
+            );
+        }
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| match &line.line {
+                Some(Line::Label(label)) if !used.contains(&index) => Some(self.finding(
+                    LintRule::UnusedLabel,
+                    index,
+                    format!("label `{}` is never used", label),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Reports instructions which write to `$zero`, which the simulator only rejects when they run.
+    fn zero_register_writes(&self) -> Vec<LintFinding> {
+        (0..self.lines.len())
+            .filter_map(|index| {
+                let (instruction, arguments) = self.instruction(index)?;
+                let writes =
+                    instruction
+                        .get_operands()
+                        .iter()
+                        .zip(arguments)
+                        .any(|(operand, argument)| {
+                            operand.role == OperandRole::Output
+                                && matches!(
+                                    argument,
+                                    ArgumentType::InputOutput(
+                                        InputOutputTarget::RegisterInputOutput(
+                                            registry::ZERO_NUMBER
+                                        )
+                                    )
+                                )
+                        });
+                writes.then(|| {
+                    self.finding(
+                        LintRule::ZeroRegisterWrite,
+                        index,
+                        "`$zero` cannot be written to".to_string(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Reports registers which are read where no path from the start of the program has written
+    /// them.
+    fn uninitialized_reads(&self) -> Vec<LintFinding> {
+        let preset = PRESET_REGISTERS
+            .iter()
+            .filter_map(|name| registry::get_register_number(&name.to_string()).ok())
+            .fold(0u64, |mask, register| mask | 1 << register);
+        let accesses: Vec<(u64, u64)> = (0..self.lines.len())
+            .map(|index| match self.instruction(index) {
+                Some(_) if self.flow(index) == Flow::Call && self.target(index).is_none() => {
+                    (0, u64::MAX)
+                }
+                Some((instruction, arguments)) => accesses(instruction, arguments),
+                None => (0, 0),
+            })
+            .collect();
+
+        // the registers which may have been written before each line, growing until nothing changes
+        let mut written: Vec<Option<u64>> = vec![None; self.lines.len()];
+        let mut pending = VecDeque::new();
+        if !self.lines.is_empty() {
+            written[0] = Some(preset);
+            pending.push_back(0);
+        }
+        for index in self.exported_labels() {
+            written[index] = Some(u64::MAX);
+            pending.push_back(index);
+        }
+        while let Some(index) = pending.pop_front() {
+            let after = written[index].unwrap_or(preset) | accesses[index].1;
+            for successor in &self.successors[index] {
+                let before = written[*successor].unwrap_or(0);
+                if written[*successor].is_none() || before | after != before {
+                    written[*successor] = Some(before | after);
+                    pending.push_back(*successor);
+                }
+            }
+        }
+
+        let mut findings = Vec::new();
+        for index in 0..self.lines.len() {
+            let Some(written) = written[index] else {
+                continue;
+            };
+            let unwritten = accesses[index].0 & !written;
+            for register in 0..registry::ALL_REGISTERS.len() {
+                if unwritten & 1 << register != 0 {
+                    findings.push(self.finding(
+                        LintRule::UninitializedRead,
+                        index,
+                        format!(
+                            "`{}` is read before anything is written to it",
+                            registry::get_register_display_name(register)
+                        ),
+                    ));
+                }
+            }
+        }
+        findings
+    }
+
+    /// Reports functions which the code before them runs on into, rather than ending the program.
+    fn missing_exits(&self) -> Vec<LintFinding> {
+        let functions = self.called_functions();
+        let mut findings = Vec::new();
+        for (label, index) in &functions {
+            let Some(before) = (0..*index)
+                .rev()
+                .find(|before| self.instruction(*before).is_some())
+            else {
+                continue;
+            };
+            let falls_through = matches!(self.flow(before), Flow::Next | Flow::Branch | Flow::Call);
+            let is_function = functions
+                .values()
+                .any(|start| (before..*index).contains(start));
+            if self.reachable[before] && falls_through && !is_function {
+                findings.push(self.finding(
+                    LintRule::MissingExit,
+                    *index,
+                    format!(
+                        "the code before function `{}` runs on into it; add an `exit` before it",
+                        label
+                    ),
+                ));
+            }
+        }
+        findings
+    }
+
+    /// Reports calls to functions from which no `return` can be reached, assuming that every call
+    /// they make returns.
+    fn calls_without_return(&self) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        let mut returns: HashMap<usize, bool> = HashMap::new();
+        for index in 0..self.lines.len() {
+            if self.flow(index) != Flow::Call {
+                continue;
+            }
+            let Some(start) = self.target(index) else {
+                continue;
+            };
+            let can_return = *returns.entry(start).or_insert_with(|| {
+                let reached = self.reach(vec![start], |line| match self.flow(line) {
+                    Flow::Call => vec![line + 1],
+                    Flow::Return => Vec::new(),
+                    _ => self.successors[line].clone(),
+                });
+                (0..self.lines.len()).any(|line| reached[line] && self.flow(line) == Flow::Return)
+            });
+            if !can_return {
+                let (_, arguments) = self.instruction(index).unwrap();
+                findings.push(self.finding(
+                    LintRule::CallWithoutReturn,
+                    index,
+                    format!("function `{}` never returns", arguments[0]),
+                ));
+            }
+        }
+        findings
+    }
+
+    /// Gets the labels which are called, and the lines they are on.
+    fn called_functions(&self) -> HashMap<String, usize> {
+        (0..self.lines.len())
+            .filter(|index| self.flow(*index) == Flow::Call)
+            .filter_map(|index| self.target(index))
+            .filter_map(|target| match &self.lines[target].line {
+                Some(Line::Label(label)) => Some((label.to_string(), target)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Checks whether an instruction jumps to a label only under some condition.
+fn is_branch(instruction: &Instruction) -> bool {
+    let semantics = &instruction.get_info().semantics;
+    semantics.starts_with("if ") && semantics.contains("goto")
+}
+
+/// Gets the registers an instruction reads and writes, as masks of register numbers.
+///
+/// An output is also read when the instruction only changes part of it, like `bins`, or works
+/// from its old value, like `inc`.
+fn accesses(instruction: &Instruction, arguments: &[ArgumentType]) -> (u64, u64) {
+    let semantics = &instruction.get_info().semantics;
+    let (assigned, expression) = semantics.split_once(" = ").unwrap_or(("", semantics));
+    let mut reads = 0u64;
+    let mut writes = 0u64;
+    for (operand, argument) in instruction.get_operands().iter().zip(arguments) {
+        match argument {
+            ArgumentType::Input(InputTarget::RegisterInput(register))
+            | ArgumentType::Input(InputTarget::DereferenceInput(register, _))
+            | ArgumentType::InputOutput(InputOutputTarget::DereferenceInputOutput(register, _)) => {
+                reads |= 1 << register;
+            }
+            ArgumentType::InputOutput(InputOutputTarget::RegisterInputOutput(register)) => {
+                writes |= 1 << register;
+                let partial = assigned.starts_with(&format!("{}[", operand.name));
+                let reused = expression
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|word| word == operand.name);
+                if operand.role == OperandRole::Output && (partial || reused) {
+                    reads |= 1 << register;
+                }
+            }
+            _ => {}
+        }
+    }
+    (reads, writes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linter() {
+        let code = "\
+main:
+    move $a0 5
+    inc $t0
+    move $zero 1
+    call square
+    call spin
+    printi $r0
+square:
+    mul $r0 $a0 $a0
+    return
+    add $t1 $t1 1
+spin:
+    jump spin
+frob $t0";
+        let findings: Vec<(usize, LintRule)> = Linter::new()
+            .lint(code)
+            .into_iter()
+            .map(|finding| (finding.line_number, finding.rule))
+            .collect();
+        assert_eq!(
+            findings,
+            vec![
+                (1, LintRule::UnusedLabel),
+                (3, LintRule::UninitializedRead),
+                (4, LintRule::ZeroRegisterWrite),
+                (6, LintRule::CallWithoutReturn),
+                (8, LintRule::MissingExit),
+                (11, LintRule::UnreachableCode),
+                (14, LintRule::ParseError),
+            ]
+        );
+
+        let mut linter = Linter::new();
+        assert!(linter.lint(".global f\nf:\n  exit").is_empty());
+        let code = "f:\n1:\n  beq $a0 0 1f\n  sub $a0 $a0 1\n  jump 1b\n1:\n.end:\n  exit";
+        let rules: Vec<LintRule> = linter
+            .lint(code)
+            .iter()
+            .map(|finding| finding.rule)
+            .collect();
+        assert_eq!(rules, vec![LintRule::UnusedLabel, LintRule::UnusedLabel]);
+        linter.disable(LintRule::from("unused-label").unwrap());
+        let findings = linter.lint("x:\n  add $t0 $t1 1\n  exit\n  exit");
+        let rules: Vec<LintRule> = findings.iter().map(|finding| finding.rule).collect();
+        assert_eq!(
+            rules,
+            vec![LintRule::UninitializedRead, LintRule::UnreachableCode]
+        );
+        assert_eq!(
+            findings[0].to_string(),
+            "2: `$t1` is read before anything is written to it [uninitialized-read]"
+        );
+        assert!(LintRule::from("nonsense").is_err());
+    }
+    #[test]
+    fn test_lint_imports() {
+        let linter = Linter::new();
+        let main = "import \"lib.ez\"\ncall square\nprinti $r0\nexit\n  add $t0 $t0 1";
+        let lib = ".export square\nsquare:\n  mul $r0 $a0 $a0\n  return\nhelper:\n  return";
+        let rules = |code: &str| -> Vec<(usize, LintRule)> {
+            linter
+                .lint(code)
+                .into_iter()
+                .map(|finding| (finding.line_number, finding.rule))
+                .collect()
+        };
+
+        // the imported function returns, and may set `$r0`
+        assert_eq!(rules(main), vec![(5, LintRule::UnreachableCode)]);
+        // the exported function is called from other files, with its arguments set
+        assert_eq!(
+            rules(lib),
+            vec![(5, LintRule::UnusedLabel), (6, LintRule::UnreachableCode)]
+        );
+    }
+}
@@ -1,2 +1,6 @@
+pub mod formatter;
+pub mod labels;
 pub mod lexer;
 pub mod line;
+pub mod linker;
+pub mod linter;
@@ -0,0 +1,187 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::raw_data::RawData;
+
+/// A single active function call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallFrame {
+    /// The (file identifier, line number) of the `call` which created this frame.
+    pub call_site: (i64, i64),
+    /// The (file identifier, line number) the call jumped to.
+    pub callee: (i64, i64),
+    /// The label the call jumped to, or `line N` if it jumped to a line which is not a label.
+    #[serde(default)]
+    pub label: String,
+    /// The value of `$sp` once the call had saved `$ra` and `$fid`, as the function was entered.
+    #[serde(default)]
+    pub stack_pointer: i64,
+    /// The values of the registers a function must restore, as they were just after the call, if
+    /// the calling convention is being checked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub saved_registers: Vec<(usize, RawData)>,
+}
+
+/// A function which is running, as listed in a backtrace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacktraceFrame {
+    /// The label the function was called through, or `main` for the code the program started in.
+    pub function: String,
+    /// The name of the file the function is running in.
+    pub file: String,
+    /// The (file identifier, line number) the function is at: the current line for the innermost
+    /// function, and the `call` it is waiting on for every other function.
+    pub location: (i64, i64),
+    /// The (1-based) line of its code file the function is at.
+    pub line: i64,
+    /// The code of the line the function is at, or an empty string if it is past the end of its
+    /// file.
+    pub code: String,
+    /// The value of `$sp` as the function was entered, or `None` for `main`.
+    pub stack_pointer: Option<i64>,
+}
+
+/// The functions which are running, from innermost to outermost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backtrace {
+    pub frames: Vec<BacktraceFrame>,
+}
+
+impl Display for Backtrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "backtrace (most recent call first):")?;
+        for frame in &self.frames {
+            write!(
+                f,
+                "\n  {} at line {} of `{}`",
+                frame.function, frame.line, frame.file
+            )?;
+            if !frame.code.is_empty() {
+                write!(f, ": {}", frame.code)?;
+            }
+            if let Some(stack_pointer) = frame.stack_pointer {
+                write!(f, " ($sp at entry = {:#x})", stack_pointer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum CallStackChange {
+    Pushed,
+    Popped(CallFrame),
+}
+
+/// A shadow of the calls made by the program, pushed on `call` and popped on `return`.
+///
+/// Each change is tagged with the index of the step which caused it so that undoing that step can
+/// revert the change as well.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallStack {
+    frames: Vec<CallFrame>,
+    changes: Vec<(usize, CallStackChange)>,
+}
+
+impl CallStack {
+    pub fn new() -> CallStack {
+        CallStack::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.frames.clear();
+        self.changes.clear();
+    }
+
+    /// Gets the active frames, from outermost to innermost.
+    pub fn frames(&self) -> &Vec<CallFrame> {
+        &self.frames
+    }
+
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Pushes a frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - the new frame.
+    /// * `step` - the index of the step which made the call, or `None` if it cannot be undone.
+    pub fn push(&mut self, frame: CallFrame, step: Option<usize>) {
+        self.frames.push(frame);
+        if let Some(step) = step {
+            self.changes.push((step, CallStackChange::Pushed));
+        }
+    }
+
+    /// Pops the innermost frame, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - the index of the step which returned, or `None` if it cannot be undone.
+    pub fn pop(&mut self, step: Option<usize>) -> Option<CallFrame> {
+        let frame = self.frames.pop()?;
+        if let Some(step) = step {
+            self.changes
+                .push((step, CallStackChange::Popped(frame.clone())));
+        }
+        Some(frame)
+    }
+
+    /// Attributes every change made by the given step or later to the given step, for when those
+    /// steps are combined into one.
+    pub fn merge_steps(&mut self, step: usize) {
+        for (change_step, _) in self.changes.iter_mut().rev() {
+            if *change_step < step {
+                break;
+            }
+            *change_step = step;
+        }
+    }
+
+    /// Reverts every change made by the given step.
+    pub fn undo(&mut self, step: usize) {
+        while let Some((change_step, _)) = self.changes.last() {
+            if *change_step < step {
+                break;
+            }
+            match self.changes.pop() {
+                Some((_, CallStackChange::Pushed)) => {
+                    self.frames.pop();
+                }
+                Some((_, CallStackChange::Popped(frame))) => self.frames.push(frame),
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_stack_undo() {
+        let mut call_stack = CallStack::new();
+        let frame = |line| CallFrame {
+            call_site: (0, line),
+            callee: (0, 10),
+            label: "f".to_string(),
+            stack_pointer: 0,
+            saved_registers: Vec::new(),
+        };
+        call_stack.push(frame(1), Some(0));
+        call_stack.push(frame(2), Some(3));
+        call_stack.pop(Some(5));
+        assert_eq!(call_stack.depth(), 1);
+
+        call_stack.undo(5);
+        assert_eq!(call_stack.frames(), &vec![frame(1), frame(2)]);
+        call_stack.undo(3);
+        assert_eq!(call_stack.frames(), &vec![frame(1)]);
+        call_stack.undo(0);
+        assert_eq!(call_stack.depth(), 0);
+    }
+}
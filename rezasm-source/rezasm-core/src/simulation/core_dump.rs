@@ -0,0 +1,318 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::call_stack::CallFrame;
+use crate::simulation::registry;
+use crate::simulation::simulator::Simulator;
+use crate::simulation::trace::TraceEntry;
+use crate::util::endianness::Endianness;
+use crate::util::error::{IoError, SimulatorError};
+use crate::util::raw_data::RawData;
+use crate::util::word_size::WordSize;
+
+/// Identifies a file on disk as a core dump.
+pub const CORE_DUMP_FORMAT: &str = "rezasm-core-dump";
+
+/// The current version of the core dump format.
+pub const CORE_DUMP_VERSION: u32 = 1;
+
+/// The line which separates the human-readable report from the machine-readable data.
+const MACHINE_READABLE_MARKER: &str = "--- machine-readable ---";
+
+/// The most words of the stack which are included in a dump.
+const MAX_STACK_WORDS: usize = 256;
+
+/// The state of the simulator at the moment a runtime fault occurred.
+///
+/// On disk, a core dump is a human-readable report followed by the same data as JSON, so it can be
+/// read directly or examined afterwards with `CoreDump::load`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreDump {
+    pub format: String,
+    pub version: u32,
+    pub error: String,
+    pub word_size: usize,
+    #[serde(default)]
+    pub endianness: Endianness,
+    pub file_identifier: i64,
+    pub file_name: String,
+    pub program_counter: i64,
+    pub registers: Vec<RegisterValue>,
+    pub trace: Vec<TraceEntry>,
+    pub call_stack: Vec<CallFrame>,
+    pub stack: StackDump,
+    pub heap: HeapSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterValue {
+    pub name: String,
+    pub value: i64,
+}
+
+/// The words between the stack pointer and the top of the stack, from the stack pointer upwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackDump {
+    pub stack_pointer: i64,
+    pub top: usize,
+    pub words: Vec<(usize, i64)>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeapSummary {
+    pub start: usize,
+    pub heap_pointer: usize,
+    pub allocated_bytes: usize,
+    pub non_zero_words: usize,
+}
+
+impl CoreDump {
+    /// Captures the state of a simulator which has just failed with an error.
+    pub fn capture(simulator: &Simulator, error: &SimulatorError) -> CoreDump {
+        let registers = simulator.get_registers();
+        let memory = simulator.get_memory();
+        let word_size = simulator.get_word_size().value();
+        let file_identifier = registers.get_fid().get_data().int_value();
+
+        let registers: Vec<RegisterValue> = registry::ALL_REGISTERS
+            .iter()
+            .enumerate()
+            .map(|(number, name)| RegisterValue {
+                name: name.to_string(),
+                value: registers
+                    .get_register_by_number(number)
+                    .map(|register| register.get_data().int_value())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let stack_pointer = simulator.get_registers().get_sp().get_data().int_value();
+        let top = memory.initial_stack_pointer();
+        let mut words = Vec::new();
+        let mut address = usize::try_from(stack_pointer).unwrap_or(top);
+        while address + word_size <= top && words.len() < MAX_STACK_WORDS {
+            if let Ok(data) = memory.read(address) {
+                words.push((address, data.int_value()));
+            }
+            address += word_size;
+        }
+        let stack = StackDump {
+            stack_pointer,
+            top,
+            truncated: address + word_size <= top,
+            words,
+        };
+
+        let start = memory.initial_heap_pointer();
+        let heap_pointer = memory.current_heap_pointer();
+        let non_zero_words = (start..heap_pointer)
+            .step_by(word_size)
+            .filter(|address| {
+                memory
+                    .read(*address)
+                    .is_ok_and(|data| data.int_value() != 0)
+            })
+            .count();
+        let heap = HeapSummary {
+            start,
+            heap_pointer,
+            allocated_bytes: heap_pointer - start,
+            non_zero_words,
+        };
+
+        CoreDump {
+            format: CORE_DUMP_FORMAT.to_string(),
+            version: CORE_DUMP_VERSION,
+            error: error.to_string(),
+            word_size,
+            endianness: *simulator.get_endianness(),
+            file_identifier,
+            file_name: simulator.get_program().file_name(file_identifier),
+            program_counter: simulator.get_registers().get_pc().get_data().int_value(),
+            registers,
+            trace: simulator.get_trace().entries().cloned().collect(),
+            call_stack: simulator.get_call_stack().frames().clone(),
+            stack,
+            heap,
+        }
+    }
+
+    /// Renders the dump as its on-disk representation.
+    pub fn to_file_string(&self) -> Result<String, SimulatorError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| SimulatorError::CoreDumpError(e.to_string()))?;
+        Ok(format!("{}\n{}\n{}\n", self, MACHINE_READABLE_MARKER, json))
+    }
+
+    /// Parses a dump from its on-disk representation.
+    pub fn from_file_string(string: &str) -> Result<CoreDump, SimulatorError> {
+        let json = match string.split_once(MACHINE_READABLE_MARKER) {
+            Some((_, json)) => json,
+            None => string,
+        };
+        let dump: CoreDump =
+            serde_json::from_str(json).map_err(|e| SimulatorError::CoreDumpError(e.to_string()))?;
+        if dump.format != CORE_DUMP_FORMAT {
+            return Err(SimulatorError::CoreDumpError(format!(
+                "unrecognized format `{}`",
+                dump.format
+            )));
+        }
+        if dump.version != CORE_DUMP_VERSION {
+            return Err(SimulatorError::CoreDumpError(format!(
+                "unsupported version `{}`",
+                dump.version
+            )));
+        }
+        Ok(dump)
+    }
+
+    /// Writes the dump to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SimulatorError> {
+        fs::write(path, self.to_file_string()?).map_err(|e| IoError::StdIoError(e).into())
+    }
+
+    /// Reads a dump from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CoreDump, SimulatorError> {
+        let path_string = path.as_ref().to_string_lossy().to_string();
+        let string =
+            fs::read_to_string(path).map_err(|_| IoError::FileDoesNotExistError(path_string))?;
+        CoreDump::from_file_string(&string)
+    }
+}
+
+impl Display for CoreDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "rezasm core dump")?;
+        writeln!(f, "error: {}", self.error)?;
+        writeln!(
+            f,
+            "at: {} (file {}), line {}",
+            self.file_name, self.file_identifier, self.program_counter
+        )?;
+        writeln!(
+            f,
+            "word size: {} bytes, {}",
+            self.word_size, self.endianness
+        )?;
+
+        writeln!(f, "\nregisters:")?;
+        for row in self.registers.chunks(4) {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|register| {
+                    format!("${:<5}{:>12}", register.name.to_lowercase(), register.value)
+                })
+                .collect();
+            writeln!(f, "  {}", cells.join("  "))?;
+        }
+
+        writeln!(f, "\nrecent trace (oldest first):")?;
+        if self.trace.is_empty() {
+            writeln!(f, "  (empty)")?;
+        }
+        for entry in &self.trace {
+            writeln!(
+                f,
+                "  [{}:{}] {}",
+                entry.file_identifier, entry.line_number, entry.line
+            )?;
+        }
+
+        writeln!(f, "\ncall stack (innermost first):")?;
+        if self.call_stack.is_empty() {
+            writeln!(f, "  (empty)")?;
+        }
+        for frame in self.call_stack.iter().rev() {
+            writeln!(
+                f,
+                "  {} [{}:{}] called from [{}:{}], $sp at entry = {:#x}",
+                frame.label,
+                frame.callee.0,
+                frame.callee.1,
+                frame.call_site.0,
+                frame.call_site.1,
+                frame.stack_pointer
+            )?;
+        }
+
+        writeln!(
+            f,
+            "\nstack ($sp = {:#x}, top = {:#x}):",
+            self.stack.stack_pointer, self.stack.top
+        )?;
+        if self.stack.words.is_empty() {
+            writeln!(f, "  (empty)")?;
+        }
+        let word_size = WordSize::from(self.word_size).unwrap_or_default();
+        for (address, value) in &self.stack.words {
+            let bytes = RawData::from_int(*value, &word_size).to_bytes(&self.endianness);
+            let bytes: Vec<String> = bytes[..word_size.value()]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            writeln!(
+                f,
+                "  {:#010x}: {:<12} [{}]",
+                address,
+                value,
+                bytes.join(" ")
+            )?;
+        }
+        if self.stack.truncated {
+            writeln!(f, "  ...")?;
+        }
+
+        writeln!(f, "\nheap:")?;
+        writeln!(f, "  start: {:#x}", self.heap.start)?;
+        writeln!(f, "  heap pointer: {:#x}", self.heap.heap_pointer)?;
+        writeln!(f, "  allocated: {} bytes", self.heap.allocated_bytes)?;
+        write!(f, "  non-zero words: {}", self.heap.non_zero_words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::lexer::parse_lines;
+
+    #[test]
+    fn test_core_dump() {
+        let program = "
+        alloc $s0 8
+        call fail
+        exit
+        fail:
+        push 7
+        div $t0 1 0";
+        let mut simulator = Simulator::new();
+        simulator.set_trace_capacity(2);
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "main".to_string()).unwrap();
+
+        let error = loop {
+            if let Err(error) = simulator.run_line_from_pc() {
+                break error;
+            }
+        };
+
+        let dump = CoreDump::capture(&simulator, &error);
+        let parsed = CoreDump::from_file_string(&dump.to_file_string().unwrap()).unwrap();
+        assert_eq!(parsed.error, "attempted to divide by zero");
+        assert_eq!(parsed.trace.len(), 2);
+        assert_eq!(parsed.trace[1].line, "div $t0 1 0");
+        assert_eq!(parsed.call_stack.len(), 1);
+        assert_eq!(parsed.call_stack[0].call_site, (0, 1));
+        assert_eq!(parsed.stack.words[0].1, 7);
+        assert_eq!(parsed.heap.allocated_bytes, 8);
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::instructions::argument_type::ArgumentType;
+use crate::instructions::targets::input_target::InputTarget;
+use crate::parser::line::Line;
+use crate::simulation::memory::Memory;
+use crate::simulation::program::Program;
+use crate::util::error::SimulatorError;
+use crate::util::raw_data::RawData;
+use crate::util::word_size::WordSize;
+
+/// A line of a program along with its operands as they are run, with the labels and strings they
+/// refer to already looked up.
+#[derive(Debug)]
+pub struct DecodedLine {
+    line: Arc<Line>,
+    arguments: Vec<ArgumentType>,
+}
+
+impl DecodedLine {
+    /// Gets the line as it was written.
+    pub fn get_line(&self) -> &Line {
+        &self.line
+    }
+
+    /// Gets the operands the instruction of the line is run with.
+    pub fn get_arguments(&self) -> &[ArgumentType] {
+        &self.arguments
+    }
+}
+
+/// The lines of a program decoded once, so that running a line does not look up its targets again.
+///
+/// A label is replaced by the line it refers to when it is in the same file, and a string by its
+/// address. Labels which refer to other files, or to nothing, are left to be looked up when the line
+/// runs, so that they behave exactly as they would undecoded.
+#[derive(Debug, Default)]
+pub struct DecodedProgram {
+    file_id_to_file: HashMap<i64, Vec<Arc<DecodedLine>>>,
+}
+
+impl DecodedProgram {
+    /// Decodes every line of a program, whose strings have been added to `memory`.
+    pub fn new(program: &Program, memory: &Memory, word_size: &WordSize) -> DecodedProgram {
+        let file_id_to_file = program
+            .files()
+            .map(|(file_id, lines)| {
+                let decoded = lines
+                    .iter()
+                    .enumerate()
+                    .map(|(line_number, line)| {
+                        Arc::new(DecodedLine {
+                            line: line.clone(),
+                            arguments: decode_arguments(
+                                line,
+                                (file_id, line_number as i64),
+                                program,
+                                memory,
+                                word_size,
+                            ),
+                        })
+                    })
+                    .collect();
+                (file_id, decoded)
+            })
+            .collect();
+        DecodedProgram { file_id_to_file }
+    }
+
+    pub fn get_line(
+        &self,
+        file_id: i64,
+        line_number: i64,
+    ) -> Result<&Arc<DecodedLine>, SimulatorError> {
+        if line_number < 0 {
+            return Err(SimulatorError::InvalidLineNumber(line_number));
+        }
+
+        match self.file_id_to_file.get(&file_id) {
+            None => Err(SimulatorError::InvalidFileIdentifier(file_id)),
+            Some(file) => match file.get(line_number as usize) {
+                None => Err(SimulatorError::InvalidProgramCounterError(line_number)),
+                Some(line) => Ok(line),
+            },
+        }
+    }
+}
+
+fn decode_arguments(
+    line: &Line,
+    (file_id, line_number): (i64, i64),
+    program: &Program,
+    memory: &Memory,
+    word_size: &WordSize,
+) -> Vec<ArgumentType> {
+    let Line::Instruction(_, arguments) = line else {
+        return Vec::new();
+    };
+    arguments
+        .iter()
+        .map(|argument| match argument {
+            ArgumentType::Input(InputTarget::LabelReferenceInput(label)) => {
+                match program.resolve_label(file_id, line_number, label) {
+                    Ok((label_file_id, label_line)) if label_file_id == file_id => {
+                        ArgumentType::Input(InputTarget::ImmediateInput(RawData::from_int(
+                            label_line, word_size,
+                        )))
+                    }
+                    _ => argument.clone(),
+                }
+            }
+            ArgumentType::Input(InputTarget::StringInput(string)) => {
+                match memory.get_string_immediate_address(string) {
+                    Ok(address) => ArgumentType::Input(InputTarget::ImmediateInput(*address)),
+                    Err(_) => argument.clone(),
+                }
+            }
+            _ => argument.clone(),
+        })
+        .collect()
+}
@@ -0,0 +1,375 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
+use crate::instructions::argument_type::ArgumentType;
+use crate::instructions::instruction::{Instruction, OperandRole};
+use crate::instructions::targets::input_output_target::InputOutputTarget;
+use crate::instructions::targets::input_target::{Input, InputTarget};
+use crate::parser::line::Line;
+use crate::simulation::registry;
+use crate::simulation::simulator::Simulator;
+use crate::simulation::transform::transformable::Transformable;
+use crate::simulation::transform::transformation_sequence::TransformationSequence;
+use crate::util::raw_data::RawData;
+
+/// A change which running a line made to a register, memory or the heap pointer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub target: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ← {} (was {})", self.target, self.to, self.from)
+    }
+}
+
+/// What the most recently run line did, in words, e.g.
+///
+/// ```text
+/// add $t0 $t1 5: $t0 ← 3 + 5 = 8 (was 0)
+/// beq $t0 $t1 loop: 8 ≠ 3, branch not taken
+/// ```
+///
+/// Explanations are written from the semantics of the instruction, with its operands replaced by
+/// the values they had just before the line was run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    pub file_identifier: i64,
+    pub line_number: i64,
+    pub line: String,
+    pub changes: Vec<Change>,
+    pub description: String,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.line)?;
+        if !self.line.ends_with(':') {
+            f.write_str(":")?;
+        }
+        write!(f, " {}", self.description)
+    }
+}
+
+/// An operand of a line, as it is written in the semantics of an explanation.
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedOperand {
+    name: String,
+    role: OperandRole,
+    float: bool,
+    argument: ArgumentType,
+    text: String,
+}
+
+/// Reads the operands of a line, which must happen before the line is run.
+pub(crate) fn resolve_operands(simulator: &Simulator, line: &Line) -> Vec<ResolvedOperand> {
+    let Line::Instruction(instruction, arguments) = line else {
+        return Vec::new();
+    };
+    let unsigned = instruction.get_info().semantics.contains("(unsigned)");
+    instruction
+        .get_operands()
+        .into_iter()
+        .zip(arguments)
+        .map(|(operand, argument)| {
+            let float = is_float_operand(simulator, instruction, operand.role);
+            let value = match argument {
+                ArgumentType::Input(InputTarget::LabelReferenceInput(_))
+                | ArgumentType::Input(InputTarget::StringInput(_)) => None,
+                ArgumentType::Input(target) => target.get(simulator).ok(),
+                ArgumentType::InputOutput(target) => target.get(simulator).ok(),
+            };
+            let text = match value {
+                Some(value) => format_value(&value, float, unsigned),
+                None => argument.to_string(),
+            };
+            ResolvedOperand {
+                name: operand.name,
+                role: operand.role,
+                float,
+                argument: argument.clone(),
+                text,
+            }
+        })
+        .collect()
+}
+
+impl Explanation {
+    /// Explains a line from its operands, as read by `resolve_operands` before it was run, and the
+    /// transformations which running it made.
+    pub(crate) fn new(
+        file_identifier: i64,
+        line_number: i64,
+        line: &Line,
+        operands: &[ResolvedOperand],
+        sequence: &TransformationSequence,
+    ) -> Explanation {
+        let output = operands
+            .iter()
+            .find(|operand| operand.role == OperandRole::Output && operand.name == "output");
+        let mut changes = Vec::new();
+        let mut output_change = None;
+        let mut taken = false;
+        for transformation in sequence.get_transformations() {
+            let transformable = transformation.get_output();
+            let Some(target) = target_name(transformable) else {
+                continue;
+            };
+            if is_program_counter(transformable) {
+                taken = true;
+            }
+            let is_output = match (output, transformable) {
+                (Some(output), Transformable::InputOutputTransformable(target)) => {
+                    output.argument == ArgumentType::InputOutput(*target)
+                }
+                _ => false,
+            };
+            let float = is_output && output.is_some_and(|output| output.float);
+            let change = Change {
+                target,
+                from: format_value(transformation.get_from(), float, false),
+                to: format_value(transformation.get_to(), float, false),
+            };
+            if is_output && output_change.is_none() {
+                output_change = Some(change);
+            } else {
+                changes.push(change);
+            }
+        }
+
+        let description = match line {
+            Line::Label(_) => "label, nothing to do".to_string(),
+            Line::Export(_) => "directive, nothing to do".to_string(),
+            Line::Instruction(instruction, _) => describe(
+                &instruction.get_info().semantics,
+                operands,
+                output_change.as_ref(),
+                &changes,
+                taken,
+            ),
+        };
+        changes.splice(0..0, output_change);
+        Explanation {
+            file_identifier,
+            line_number,
+            line: line.to_string(),
+            changes,
+            description,
+        }
+    }
+}
+
+fn describe(
+    semantics: &str,
+    operands: &[ResolvedOperand],
+    output_change: Option<&Change>,
+    changes: &[Change],
+    taken: bool,
+) -> String {
+    let program_counter = registry::get_register_display_name(registry::PC_NUMBER);
+    let others = changes
+        .iter()
+        .filter(|change| change.target != program_counter)
+        .map(|change| change.to_string())
+        .collect::<Vec<String>>();
+
+    if let Some(condition) = semantics
+        .strip_prefix("if ")
+        .and_then(|rest| rest.split_once(": goto"))
+        .map(|(condition, _)| condition)
+    {
+        let condition = describe_condition(&substitute(condition, operands), taken);
+        return if taken {
+            format!("{}, branch taken", condition)
+        } else {
+            format!("{}, branch not taken", condition)
+        };
+    }
+
+    if let (Some(change), Some(expression)) = (
+        output_change,
+        semantics
+            .strip_prefix("output = ")
+            .filter(|expression| !expression.contains(';')),
+    ) {
+        let expression = substitute(expression, operands);
+        let mut description = if expression == change.to {
+            format!("{} ← {} (was {})", change.target, change.to, change.from)
+        } else if ["==", "!=", "<=", ">=", " < ", " > "]
+            .iter()
+            .any(|operator| expression.contains(operator))
+        {
+            format!(
+                "{} ← ({}) = {} (was {})",
+                change.target, expression, change.to, change.from
+            )
+        } else {
+            format!(
+                "{} ← {} = {} (was {})",
+                change.target, expression, change.to, change.from
+            )
+        };
+        for other in others {
+            description.push_str(", ");
+            description.push_str(&other);
+        }
+        return description;
+    }
+
+    let all = output_change
+        .map(|change| change.to_string())
+        .into_iter()
+        .chain(changes.iter().map(|change| change.to_string()))
+        .collect::<Vec<String>>();
+    if !all.is_empty() {
+        all.join(", ")
+    } else if !semantics.is_empty() {
+        substitute(semantics, operands)
+    } else {
+        "nothing changed".to_string()
+    }
+}
+
+/// Writes a comparison with its operator as it would be written by hand, negated if it was false.
+fn describe_condition(condition: &str, holds: bool) -> String {
+    const OPERATORS: [(&str, &str, &str); 6] = [
+        ("==", "=", "≠"),
+        ("!=", "≠", "="),
+        (">=", "≥", "<"),
+        ("<=", "≤", ">"),
+        (">", ">", "≤"),
+        ("<", "<", "≥"),
+    ];
+    for (operator, written, negated) in OPERATORS {
+        if let Some((left, right)) = condition.split_once(&format!(" {} ", operator)) {
+            let written = if holds { written } else { negated };
+            return format!("{} {} {}", left, written, right);
+        }
+    }
+    condition.to_string()
+}
+
+/// Replaces the names of operands in semantics by their values.
+fn substitute(semantics: &str, operands: &[ResolvedOperand]) -> String {
+    let mut result = String::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, result: &mut String| {
+        match operands.iter().find(|operand| operand.name == *word) {
+            Some(operand) => result.push_str(&operand.text),
+            None => result.push_str(word),
+        }
+        word.clear();
+    };
+    for c in semantics.chars() {
+        if c.is_alphanumeric() || c == '_' || c == '$' {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut result);
+            result.push(c);
+        }
+    }
+    flush(&mut word, &mut result);
+    result
+}
+
+fn target_name(transformable: &Transformable) -> Option<String> {
+    match transformable {
+        Transformable::InputOutputTransformable(target) => Some(target.to_string()),
+        Transformable::MemoryTransformable(address) => Some(format!("memory[{}]", address)),
+        Transformable::HeapPointerTransformable => Some("heap pointer".to_string()),
+        Transformable::FileReadTransformable(_) => Some("file cursor".to_string()),
+        Transformable::NullOpTransformable => None,
+    }
+}
+
+fn is_program_counter(transformable: &Transformable) -> bool {
+    matches!(
+        transformable,
+        Transformable::InputOutputTransformable(InputOutputTarget::RegisterInputOutput(
+            registry::PC_NUMBER
+        ))
+    )
+}
+
+/// Whether an operand holds a floating point value, which depends on the instruction: the float
+/// instructions work on floats, except for the integer side of conversions and the results of
+/// comparisons.
+fn is_float_operand(simulator: &Simulator, instruction: &Instruction, role: OperandRole) -> bool {
+    let category = simulator
+        .get_instruction_set()
+        .get_category(instruction.get_name())
+        .map(|category| category.as_str());
+    match (category, instruction.get_name().as_str(), role) {
+        (_, "readf", _) | (_, "printf", _) => true,
+        (_, "itof", OperandRole::Input) | (_, "ftoi", OperandRole::Output) => false,
+        (Some("float-arithmetic"), _, _) => true,
+        (Some("float-comparison"), _, role) => role == OperandRole::Input,
+        _ => false,
+    }
+}
+
+pub(crate) fn format_value(value: &RawData, float: bool, unsigned: bool) -> String {
+    if float {
+        format!("{:?}", value.float_value())
+    } else if unsigned {
+        value.unsigned_value().to_string()
+    } else {
+        value.int_value().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::instruction_set::InstructionSet;
+    use crate::parser::lexer::parse_lines;
+    use crate::simulation::simulator::Simulator;
+    use crate::util::word_size::WordSize;
+
+    #[test]
+    fn test_explanation() {
+        let program = "
+        move $t1 3
+        add $t0 $t1 5
+        beq $t0 $t1 end
+        itof $t2 3
+        addf $t2 $t2 $t2
+        seq $t3 $t0 8
+        push $t0
+        bne $t0 $t1 end
+        end:";
+        let mut simulator = Simulator::new();
+        simulator.set_explain(true);
+        let lines =
+            parse_lines(program, &WordSize::default(), &InstructionSet::standard()).unwrap();
+        simulator.add_lines(lines, "".to_string()).unwrap();
+
+        let mut explanations = Vec::new();
+        while !simulator.is_done() {
+            simulator.run_line_from_pc().unwrap();
+            explanations.push(simulator.get_explanation().unwrap().to_string());
+        }
+        assert_eq!(explanations[0], "move $t1 3: $t1 ← 3 (was 0)");
+        assert_eq!(explanations[1], "add $t0 $t1 5: $t0 ← 3 + 5 = 8 (was 0)");
+        assert_eq!(explanations[2], "beq $t0 $t1 end: 8 ≠ 3, branch not taken");
+        assert_eq!(
+            explanations[3],
+            "itof $t2 3: $t2 ← float(3) = 3.0 (was 0.0)"
+        );
+        assert_eq!(
+            explanations[4],
+            "addf $t2 $t2 $t2: $t2 ← 3.0 + 3.0 = 6.0 (was 3.0)"
+        );
+        assert_eq!(explanations[5], "seq $t3 $t0 8: $t3 ← (8 == 8) = 1 (was 0)");
+        assert!(explanations[6].starts_with("push $t0: $sp ← "));
+        assert_eq!(explanations[7], "bne $t0 $t1 end: 8 ≠ 3, branch taken");
+
+        let explanation = simulator.get_explanation().unwrap();
+        assert_eq!(explanation.line_number, 7);
+        simulator.undo_last_transformation().unwrap();
+        assert!(simulator.get_explanation().is_none());
+    }
+}
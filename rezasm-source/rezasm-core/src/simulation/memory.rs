@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+use crate::simulation::memory_backend::{MemoryBackend, MemoryBackendKind};
+use crate::simulation::snapshot::MemorySnapshot;
+use crate::util::endianness::{Endianness, DEFAULT_ENDIANNESS};
 use crate::util::error::SimulatorError;
 use crate::util::raw_data::RawData;
 use crate::util::word_size::{WordSize, DEFAULT_WORD_SIZE};
@@ -9,16 +12,23 @@ pub const DEFAULT_MEMORY_WORDS: usize = 0x20_0000;
 const DEFAULT_OFFSET: usize = 0x1_0000;
 const DEFAULT_STRING_OFFSET: usize = 0x1_0000;
 
+// With 16-bit words every address has to fit in a positive word, so the reserved regions are
+// smaller and the total size is capped.
+const SMALL_OFFSET: usize = 0x100;
+const SMALL_STRING_OFFSET: usize = 0x400;
+const SMALL_MAX_BYTES: usize = i16::MAX as usize & !1;
+
 #[derive(Debug)]
 pub struct Memory {
     // const
     word_size: WordSize,
+    endianness: Endianness,
     memory_size: usize,
     offset_bytes: usize,
     disallowed_bytes: usize,
 
     // non-const
-    memory: Vec<u8>,
+    memory: Box<dyn MemoryBackend>,
     alloc_index: usize,
     string_alloc_index: usize,
     string_address_map: HashMap<String, RawData>,
@@ -30,20 +40,47 @@ impl Memory {
     }
 
     pub fn new_sized(word_size: &WordSize, memory_size: usize) -> Memory {
-        let word_size = word_size.clone();
-        let offset_bytes = word_size.value() * (DEFAULT_OFFSET + DEFAULT_STRING_OFFSET);
-        let disallowed_bytes = word_size.value() * DEFAULT_OFFSET;
+        Memory::new_custom(
+            word_size,
+            memory_size,
+            MemoryBackendKind::Dense,
+            DEFAULT_ENDIANNESS,
+        )
+    }
+
+    /// Creates a memory with space for `memory_size` words of stack and heap.
+    ///
+    /// With 16-bit words the whole memory must be addressable by a positive word, so the size is
+    /// reduced to fit if necessary.
+    pub fn new_custom(
+        word_size: &WordSize,
+        memory_size: usize,
+        backend: MemoryBackendKind,
+        endianness: Endianness,
+    ) -> Memory {
+        let word_size = *word_size;
+        let (offset, string_offset) = match word_size {
+            WordSize::Two => (SMALL_OFFSET, SMALL_STRING_OFFSET),
+            _ => (DEFAULT_OFFSET, DEFAULT_STRING_OFFSET),
+        };
+        let offset_bytes = word_size.value() * (offset + string_offset);
+        let disallowed_bytes = word_size.value() * offset;
         let memory_size = offset_bytes + memory_size * word_size.value();
+        let memory_size = match word_size {
+            WordSize::Two => memory_size.min(SMALL_MAX_BYTES),
+            _ => memory_size,
+        };
 
         Memory {
             word_size,
+            endianness,
             memory_size,
             offset_bytes,
             disallowed_bytes,
 
-            memory: vec![0; memory_size],
+            memory: backend.create(memory_size),
             alloc_index: offset_bytes,
-            string_alloc_index: DEFAULT_STRING_OFFSET * word_size.value(),
+            string_alloc_index: disallowed_bytes,
             string_address_map: HashMap::new(),
         }
     }
@@ -52,14 +89,22 @@ impl Memory {
         &self.word_size
     }
 
+    pub fn endianness(&self) -> &Endianness {
+        &self.endianness
+    }
+
     pub fn memory_size(&self) -> usize {
         self.memory_size
     }
 
+    pub fn backend_kind(&self) -> MemoryBackendKind {
+        self.memory.kind()
+    }
+
     pub fn reset(&mut self) {
-        self.memory = vec![0; self.memory_size];
+        self.memory.reset();
         self.alloc_index = self.offset_bytes;
-        self.string_alloc_index = DEFAULT_STRING_OFFSET * self.word_size.value();
+        self.string_alloc_index = self.disallowed_bytes;
         self.string_address_map = HashMap::new();
     }
 
@@ -72,7 +117,7 @@ impl Memory {
     }
 
     pub fn initial_text_pointer(&self) -> usize {
-        DEFAULT_STRING_OFFSET * self.word_size.value()
+        self.disallowed_bytes
     }
 
     pub fn current_heap_pointer(&self) -> usize {
@@ -87,11 +132,25 @@ impl Memory {
         Ok(())
     }
 
+    /// Reads a word of `count` bytes, where `count` must be a valid word size.
     pub fn read_bytes(&self, address: usize, count: usize) -> Result<RawData, SimulatorError> {
+        WordSize::from(count)?;
+        if address < self.disallowed_bytes || address + count > self.memory_size {
+            return Err(SimulatorError::ReadOutOfBoundsError(address));
+        }
+        let mut bytes = [0u8; 8];
+        self.memory.read(address, &mut bytes[..count]);
+        RawData::from_bytes(&bytes[..count], &self.endianness)
+    }
+
+    /// Reads bytes exactly as they are laid out in memory, without interpreting them as words.
+    pub fn read_raw(&self, address: usize, count: usize) -> Result<Vec<u8>, SimulatorError> {
         if address < self.disallowed_bytes || address + count > self.memory_size {
             return Err(SimulatorError::ReadOutOfBoundsError(address));
         }
-        Ok(RawData::new(&self.memory[address..address + count]))
+        let mut bytes = vec![0u8; count];
+        self.memory.read(address, &mut bytes);
+        Ok(bytes)
     }
 
     pub fn read(&self, address: usize) -> Result<RawData, SimulatorError> {
@@ -99,7 +158,7 @@ impl Memory {
     }
 
     pub fn write(&mut self, address: usize, data: &RawData) -> Result<(), SimulatorError> {
-        if address < self.offset_bytes || address + data.data.len() > self.memory_size {
+        if address < self.offset_bytes || address + data.word_size().value() > self.memory_size {
             Err(SimulatorError::WriteOutOfBoundsError(address))
         } else {
             self.unsafe_write(address, data)
@@ -107,16 +166,66 @@ impl Memory {
     }
 
     pub fn unsafe_write(&mut self, address: usize, data: &RawData) -> Result<(), SimulatorError> {
-        if address + data.data.len() > self.memory_size {
+        if address + data.word_size().value() > self.memory_size {
             Err(SimulatorError::WriteOutOfBoundsError(address))
         } else {
-            for (index, byte) in data.get_iter().enumerate() {
-                self.memory[address + index] = byte.clone();
-            }
+            let bytes = data.to_bytes(&self.endianness);
+            self.memory
+                .write(address, &bytes[..data.word_size().value()]);
             Ok(())
         }
     }
 
+    /// Captures the contents of memory, keeping only the regions which are not zero.
+    pub fn snapshot(&self) -> MemorySnapshot {
+        MemorySnapshot {
+            endianness: self.endianness,
+            memory_words: (self.memory_size - self.offset_bytes) / self.word_size.value(),
+            alloc_index: self.alloc_index,
+            string_alloc_index: self.string_alloc_index,
+            strings: self
+                .string_address_map
+                .iter()
+                .map(|(string, address)| (string.clone(), *address))
+                .collect(),
+            regions: self.memory.regions(),
+        }
+    }
+
+    /// Replaces this memory with one built from a snapshot, keeping the same kind of backend but
+    /// taking the byte order of the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `word_size` - the word size the snapshot was taken with.
+    /// * `snapshot` - the snapshot to restore.
+    pub fn restore(
+        &mut self,
+        word_size: &WordSize,
+        snapshot: &MemorySnapshot,
+    ) -> Result<(), SimulatorError> {
+        let mut memory = Memory::new_custom(
+            word_size,
+            snapshot.memory_words,
+            self.backend_kind(),
+            snapshot.endianness,
+        );
+        for region in &snapshot.regions {
+            if region.address + region.bytes.len() > memory.memory_size {
+                return Err(SimulatorError::SnapshotError(format!(
+                    "memory region at `{}` is out of bounds",
+                    region.address
+                )));
+            }
+            memory.memory.write(region.address, &region.bytes);
+        }
+        memory.set_heap_pointer(snapshot.alloc_index)?;
+        memory.string_alloc_index = snapshot.string_alloc_index;
+        memory.string_address_map = snapshot.strings.iter().cloned().collect();
+        *self = memory;
+        Ok(())
+    }
+
     pub fn add_string_immediates(&mut self, strings: Vec<String>) -> Result<(), SimulatorError> {
         for string in strings {
             if !self.string_address_map.contains_key(&string) {
@@ -197,15 +306,46 @@ mod tests {
 
     #[test]
     fn test_memory() {
-        let mut memory: Memory = Memory::new();
-        let data = RawData::from_int(100, &DEFAULT_WORD_SIZE);
-        memory.write(memory.current_heap_pointer(), &data).unwrap();
-        assert_eq!(
-            memory
-                .read(memory.current_heap_pointer())
-                .unwrap()
-                .int_value(),
-            100
-        );
+        for backend in [MemoryBackendKind::Dense, MemoryBackendKind::Sparse] {
+            let mut memory: Memory = Memory::new_custom(
+                &DEFAULT_WORD_SIZE,
+                DEFAULT_MEMORY_WORDS,
+                backend,
+                DEFAULT_ENDIANNESS,
+            );
+            let data = RawData::from_int(100, &DEFAULT_WORD_SIZE);
+            memory.write(memory.current_heap_pointer(), &data).unwrap();
+            assert_eq!(
+                memory
+                    .read(memory.current_heap_pointer())
+                    .unwrap()
+                    .int_value(),
+                100
+            );
+            memory.reset();
+            assert_eq!(
+                memory
+                    .read(memory.current_heap_pointer())
+                    .unwrap()
+                    .int_value(),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_endianness() {
+        for (endianness, bytes) in [
+            (Endianness::Big, [0x12, 0x34, 0x56, 0x78]),
+            (Endianness::Little, [0x78, 0x56, 0x34, 0x12]),
+        ] {
+            let mut memory =
+                Memory::new_custom(&WordSize::Four, 16, MemoryBackendKind::Dense, endianness);
+            let address = memory.current_heap_pointer();
+            let data = RawData::from_int(0x12345678, &WordSize::Four);
+            memory.write(address, &data).unwrap();
+            assert_eq!(memory.read_raw(address, 4).unwrap(), bytes);
+            assert_eq!(memory.read(address).unwrap(), data);
+        }
     }
 }
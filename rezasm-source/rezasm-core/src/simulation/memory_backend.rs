@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::simulation::snapshot::{non_zero_regions, MemoryRegion};
+use crate::util::error::SimulatorError;
+
+/// The number of bytes in a page of `SparseMemory`.
+pub const PAGE_SIZE: usize = 0x1000;
+
+/// The storage behind a `Memory`.
+///
+/// A backend is a flat array of `size()` bytes which all start out as zero. Bounds and permission
+/// checks are done by `Memory` before a backend is accessed, so a backend may assume every access
+/// is in range.
+pub trait MemoryBackend: Debug + Send + Sync {
+    /// The kind of this backend, so that an equivalent one can be created.
+    fn kind(&self) -> MemoryBackendKind;
+
+    /// The number of addressable bytes.
+    fn size(&self) -> usize;
+
+    /// Fills `buffer` with the bytes starting at `address`.
+    fn read(&self, address: usize, buffer: &mut [u8]);
+
+    /// Copies `bytes` into memory starting at `address`.
+    fn write(&mut self, address: usize, bytes: &[u8]);
+
+    /// Sets every byte back to zero.
+    fn reset(&mut self);
+
+    /// Gets the regions which contain non-zero bytes, in increasing order of address.
+    fn regions(&self) -> Vec<MemoryRegion>;
+}
+
+/// The available implementations of `MemoryBackend`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MemoryBackendKind {
+    /// A single contiguous allocation of the whole address space.
+    #[default]
+    Dense,
+    /// Fixed-size pages which are only allocated once they are written to.
+    Sparse,
+}
+
+impl MemoryBackendKind {
+    pub fn from(name: &str) -> Result<MemoryBackendKind, SimulatorError> {
+        match name {
+            "dense" => Ok(MemoryBackendKind::Dense),
+            "sparse" => Ok(MemoryBackendKind::Sparse),
+            _ => Err(SimulatorError::InvalidMemoryBackendError(name.to_string())),
+        }
+    }
+
+    /// Creates an empty backend of this kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - the number of addressable bytes.
+    pub fn create(&self, size: usize) -> Box<dyn MemoryBackend> {
+        match self {
+            MemoryBackendKind::Dense => Box::new(DenseMemory::new(size)),
+            MemoryBackendKind::Sparse => Box::new(SparseMemory::new(size)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DenseMemory {
+    memory: Vec<u8>,
+}
+
+impl DenseMemory {
+    pub fn new(size: usize) -> DenseMemory {
+        DenseMemory {
+            memory: vec![0; size],
+        }
+    }
+}
+
+impl MemoryBackend for DenseMemory {
+    fn kind(&self) -> MemoryBackendKind {
+        MemoryBackendKind::Dense
+    }
+
+    fn size(&self) -> usize {
+        self.memory.len()
+    }
+
+    fn read(&self, address: usize, buffer: &mut [u8]) {
+        buffer.copy_from_slice(&self.memory[address..address + buffer.len()]);
+    }
+
+    fn write(&mut self, address: usize, bytes: &[u8]) {
+        self.memory[address..address + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn reset(&mut self) {
+        self.memory.fill(0);
+    }
+
+    fn regions(&self) -> Vec<MemoryRegion> {
+        non_zero_regions(&self.memory)
+    }
+}
+
+/// Memory split into pages of `PAGE_SIZE` bytes, where a page is only allocated the first time a
+/// non-zero byte is written to it. Reading an unallocated page yields zeroes, and resetting simply
+/// drops every page.
+#[derive(Debug)]
+pub struct SparseMemory {
+    size: usize,
+    pages: HashMap<usize, Box<[u8; PAGE_SIZE]>>,
+}
+
+impl SparseMemory {
+    pub fn new(size: usize) -> SparseMemory {
+        SparseMemory {
+            size,
+            pages: HashMap::new(),
+        }
+    }
+
+    /// The number of pages which have been allocated.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Splits an access into the parts which fall in each page, as
+    /// (page number, offset in page, offset in access, length).
+    fn chunks(address: usize, length: usize) -> impl Iterator<Item = (usize, usize, usize, usize)> {
+        let mut done = 0usize;
+        std::iter::from_fn(move || {
+            if done == length {
+                return None;
+            }
+            let current = address + done;
+            let offset = current % PAGE_SIZE;
+            let count = (PAGE_SIZE - offset).min(length - done);
+            let chunk = (current / PAGE_SIZE, offset, done, count);
+            done += count;
+            Some(chunk)
+        })
+    }
+}
+
+impl MemoryBackend for SparseMemory {
+    fn kind(&self) -> MemoryBackendKind {
+        MemoryBackendKind::Sparse
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn read(&self, address: usize, buffer: &mut [u8]) {
+        for (page, offset, start, count) in SparseMemory::chunks(address, buffer.len()) {
+            let target = &mut buffer[start..start + count];
+            match self.pages.get(&page) {
+                Some(page) => target.copy_from_slice(&page[offset..offset + count]),
+                None => target.fill(0),
+            }
+        }
+    }
+
+    fn write(&mut self, address: usize, bytes: &[u8]) {
+        for (page, offset, start, count) in SparseMemory::chunks(address, bytes.len()) {
+            let source = &bytes[start..start + count];
+            if !self.pages.contains_key(&page) && source.iter().all(|byte| *byte == 0) {
+                continue;
+            }
+            let page = self
+                .pages
+                .entry(page)
+                .or_insert_with(|| Box::new([0; PAGE_SIZE]));
+            page[offset..offset + count].copy_from_slice(source);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pages.clear();
+    }
+
+    fn regions(&self) -> Vec<MemoryRegion> {
+        let mut page_numbers: Vec<&usize> = self.pages.keys().collect();
+        page_numbers.sort();
+        page_numbers
+            .into_iter()
+            .flat_map(|number| {
+                non_zero_regions(&self.pages[number][..])
+                    .into_iter()
+                    .map(move |region| MemoryRegion {
+                        address: number * PAGE_SIZE + region.address,
+                        bytes: region.bytes,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_memory() {
+        let mut memory = SparseMemory::new(PAGE_SIZE * 1024);
+        let mut buffer = [1u8; 8];
+        memory.read(PAGE_SIZE * 10, &mut buffer);
+        assert_eq!(buffer, [0; 8]);
+
+        memory.write(PAGE_SIZE * 3, &[0; 8]);
+        assert_eq!(memory.page_count(), 0);
+
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        memory.write(PAGE_SIZE * 5 - 4, &bytes);
+        assert_eq!(memory.page_count(), 2);
+        memory.read(PAGE_SIZE * 5 - 4, &mut buffer);
+        assert_eq!(buffer, bytes);
+
+        let regions = memory.regions();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].address, PAGE_SIZE * 5 - 4);
+        assert_eq!(regions[0].bytes, vec![1, 2, 3, 4]);
+        assert_eq!(regions[1].address, PAGE_SIZE * 5);
+
+        memory.reset();
+        assert_eq!(memory.page_count(), 0);
+        memory.read(PAGE_SIZE * 5 - 4, &mut buffer);
+        assert_eq!(buffer, [0; 8]);
+    }
+}
@@ -1,9 +1,16 @@
+pub mod call_stack;
+pub mod core_dump;
+pub mod decoded_program;
+pub mod explanation;
 pub mod memory;
+pub mod memory_backend;
 pub mod program;
 pub mod reader;
 pub mod reader_cell;
 pub mod register;
 pub mod registry;
 pub mod simulator;
+pub mod snapshot;
+pub mod trace;
 pub mod transform;
 pub mod writer;
@@ -1,14 +1,27 @@
+use crate::instructions::instruction_set::InstructionSet;
+use crate::parser::labels::FileLabels;
 use crate::parser::line::Line;
+use crate::simulation::snapshot::{FileSnapshot, LabelSnapshot, LineSnapshot, ProgramSnapshot};
 use crate::util::error::SimulatorError;
 use bimap::BiHashMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Program {
     file_identifiers: BiHashMap<String, i64>,
-    file_id_to_file: HashMap<i64, Vec<Line>>,
-    // label -> (file_id, line_number)
-    label_to_line: HashMap<String, (i64, i64)>,
+    // lines are shared with the decoded program the simulator runs, so that decoding does not
+    // clone them
+    file_id_to_file: HashMap<i64, Vec<Arc<Line>>>,
+    // the (1-based) line of its code file each line came from, since blank and comment lines are
+    // left out of the program
+    file_id_to_source_lines: HashMap<i64, Vec<i64>>,
+    // each file has labels of its own
+    file_id_to_labels: HashMap<i64, FileLabels>,
+    // (file_id, label) for the labels which files export to the rest of the program
+    exported_labels: HashSet<(i64, String)>,
 }
 
 impl Program {
@@ -16,7 +29,9 @@ impl Program {
         let mut program = Program {
             file_identifiers: BiHashMap::new(),
             file_id_to_file: HashMap::new(),
-            label_to_line: HashMap::new(),
+            file_id_to_source_lines: HashMap::new(),
+            file_id_to_labels: HashMap::new(),
+            exported_labels: HashSet::new(),
         };
         program.initialize();
         program
@@ -29,7 +44,9 @@ impl Program {
     pub fn reset(&mut self) {
         self.file_identifiers.clear();
         self.file_id_to_file.clear();
-        self.label_to_line.clear();
+        self.file_id_to_source_lines.clear();
+        self.file_id_to_labels.clear();
+        self.exported_labels.clear();
         self.initialize();
     }
 
@@ -42,12 +59,36 @@ impl Program {
             None => Err(SimulatorError::InvalidFileIdentifier(file_id)),
             Some(file) => match file.get(line_number as usize) {
                 None => Err(SimulatorError::InvalidProgramCounterError(line_number)),
-                Some(line) => Ok(line),
+                Some(line) => Ok(line.as_ref()),
             },
         }
     }
 
+    /// Iterates over the lines of each file, by the identifier of the file.
+    pub fn files(&self) -> impl Iterator<Item = (i64, &[Arc<Line>])> {
+        self.file_id_to_file
+            .iter()
+            .map(|(file_id, lines)| (*file_id, lines.as_slice()))
+    }
+
+    /// Adds a line to a file, as the line of its code file after the last one added, which is where
+    /// it came from unless blank and comment lines were left out; see `add_numbered_line`.
     pub fn add_line(&mut self, line: Line, file: String) -> Result<(), SimulatorError> {
+        let source_line = match self.file_identifiers.get_by_left(file.as_str()) {
+            Some(file_id) => self.end_pc(*file_id) + 1,
+            None => 1,
+        };
+        self.add_numbered_line(line, source_line, file)
+    }
+
+    /// Adds a line to a file along with the (1-based) line of its code file it came from, which is
+    /// how errors refer to it.
+    pub fn add_numbered_line(
+        &mut self,
+        line: Line,
+        source_line: usize,
+        file: String,
+    ) -> Result<(), SimulatorError> {
         let file_id: i64 = match self.file_identifiers.get_by_left(file.as_str()) {
             None => {
                 let id = self.file_identifiers.len() as i64;
@@ -57,38 +98,238 @@ impl Program {
             }
             Some(id) => id.clone(),
         };
-        match &line {
+        let line_number = match self.file_id_to_file.get(&file_id) {
+            None => return Err(SimulatorError::InvalidFileIdentifier(file_id)),
+            Some(lines) => lines.len() as i64,
+        };
+        self.file_id_to_source_lines
+            .entry(file_id)
+            .or_default()
+            .push(source_line as i64);
+        self.add_labels(file_id, line_number, &line)?;
+
+        match self.file_id_to_file.get_mut(&file_id) {
+            None => {
+                self.file_id_to_file.insert(file_id, vec![Arc::new(line)]);
+            }
+            Some(file) => file.push(Arc::new(line)),
+        }
+
+        Ok(())
+    }
+
+    /// Records the label a line defines or exports.
+    fn add_labels(
+        &mut self,
+        file_id: i64,
+        line_number: i64,
+        line: &Line,
+    ) -> Result<(), SimulatorError> {
+        match line {
             Line::Label(label) => {
-                if self.label_to_line.contains_key(label) {
-                    return Err(SimulatorError::LabelInUseError(label.to_string()));
-                } else {
-                    self.label_to_line.insert(
+                let labels = self.file_id_to_labels.entry(file_id).or_default();
+                if let Some(defined) = labels.define(label, line_number) {
+                    return Err(SimulatorError::LabelInUseError(
                         label.to_string(),
-                        (
-                            file_id,
-                            match self.file_id_to_file.get(&file_id) {
-                                None => return Err(SimulatorError::InvalidFileIdentifier(file_id)),
-                                Some(lines) => lines.len() as i64,
-                            },
-                        ),
-                    );
+                        self.describe_location(file_id, line_number),
+                        self.describe_location(file_id, defined),
+                    ));
                 }
+                self.check_exported_label(file_id, label)
+            }
+            Line::Export(label) => {
+                self.exported_labels.insert((file_id, label.to_string()));
+                self.check_exported_label(file_id, label)
             }
-            _ => {}
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the line a file defines a global label on.
+    fn get_label(&self, file_id: i64, label: &str) -> Option<i64> {
+        self.file_id_to_labels.get(&file_id)?.get(label)
+    }
+
+    /// Checks that no other file exports a label of the same name, once a label is both defined and
+    /// exported.
+    fn check_exported_label(&self, file_id: i64, label: &str) -> Result<(), SimulatorError> {
+        let Some(defined) = self.get_label(file_id, label) else {
+            return Ok(());
         };
+        if !self.exported_labels.contains(&(file_id, label.to_string())) {
+            return Ok(());
+        }
+        match self.find_exported_label(label, Some(file_id)) {
+            Some((other_file_id, other_defined)) => Err(SimulatorError::LabelInUseError(
+                label.to_string(),
+                self.describe_location(file_id, defined),
+                self.describe_location(other_file_id, other_defined),
+            )),
+            None => Ok(()),
+        }
+    }
 
-        match self.file_id_to_file.get_mut(&file_id) {
-            None => {
-                self.file_id_to_file.insert(file_id, vec![line]);
-            }
-            Some(file) => file.push(line),
+    /// Finds where an exported label is defined, in any file other than `except`.
+    fn find_exported_label(&self, label: &str, except: Option<i64>) -> Option<(i64, i64)> {
+        self.exported_labels
+            .iter()
+            .filter(|(file_id, exported)| exported == label && Some(*file_id) != except)
+            .filter_map(|(file_id, _)| Some((*file_id, self.get_label(*file_id, label)?)))
+            .min()
+    }
+
+    /// Describes a line of the program for error messages by the line of its code file it came
+    /// from, e.g. "line 3 of `main.ez`".
+    pub fn describe_location(&self, file_id: i64, line_number: i64) -> String {
+        format!(
+            "line {} of `{}`",
+            self.source_line(file_id, line_number),
+            self.file_name(file_id)
+        )
+    }
+
+    /// Gets the (1-based) line of its code file a line of the program came from. Past the end of a
+    /// file, this is the line after the last line of code.
+    pub fn source_line(&self, file_id: i64, line_number: i64) -> i64 {
+        let source_lines = self
+            .file_id_to_source_lines
+            .get(&file_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        match source_lines.get(line_number.max(0) as usize) {
+            Some(source_line) => *source_line,
+            None => source_lines.last().map_or(line_number + 1, |last| last + 1),
         }
+    }
+
+    /// Captures every file of the program along with its labels.
+    pub fn snapshot(&self) -> ProgramSnapshot {
+        let mut files: Vec<FileSnapshot> = self
+            .file_id_to_file
+            .iter()
+            .map(|(identifier, lines)| FileSnapshot {
+                identifier: *identifier,
+                name: self.file_identifiers.get_by_right(identifier).cloned(),
+                lines: lines
+                    .iter()
+                    .map(|line| LineSnapshot::from(line.as_ref()))
+                    .collect(),
+                source_lines: self
+                    .file_id_to_source_lines
+                    .get(identifier)
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect();
+        files.sort_by_key(|file| file.identifier);
+
+        let mut labels: Vec<LabelSnapshot> = self
+            .file_id_to_labels
+            .iter()
+            .flat_map(|(file_identifier, labels)| {
+                labels
+                    .definitions()
+                    .into_iter()
+                    .map(|(label, line_number)| LabelSnapshot {
+                        label,
+                        file_identifier: *file_identifier,
+                        line_number,
+                    })
+            })
+            .collect();
+        labels.sort_by(|a, b| {
+            (&a.label, a.file_identifier, a.line_number).cmp(&(
+                &b.label,
+                b.file_identifier,
+                b.line_number,
+            ))
+        });
+
+        ProgramSnapshot { files, labels }
+    }
 
+    /// Replaces the program with the one described by a snapshot, looking its instructions up in
+    /// `instructions`. The labels are found again from the lines of its files.
+    pub fn restore(
+        &mut self,
+        snapshot: &ProgramSnapshot,
+        instructions: &InstructionSet,
+    ) -> Result<(), SimulatorError> {
+        self.reset();
+        for file in &snapshot.files {
+            if let Some(name) = &file.name {
+                self.file_identifiers
+                    .insert(name.to_string(), file.identifier);
+            }
+            let lines = file
+                .lines
+                .iter()
+                .map(|line| line.to_line(instructions).map(Arc::new))
+                .collect::<Result<Vec<Arc<Line>>, SimulatorError>>()?;
+            // snapshots from before source lines were kept number lines by their position
+            let source_lines = if file.source_lines.len() == lines.len() {
+                file.source_lines.clone()
+            } else {
+                (1..=lines.len() as i64).collect()
+            };
+            self.file_id_to_source_lines
+                .insert(file.identifier, source_lines);
+            for (line_number, line) in lines.iter().enumerate() {
+                self.add_labels(file.identifier, line_number as i64, line)?;
+            }
+            self.file_id_to_file.insert(file.identifier, lines);
+        }
         Ok(())
     }
 
-    pub fn resolve_label(&self, label: &String) -> Option<&(i64, i64)> {
-        self.label_to_line.get(label.as_str())
+    /// Finds the file and line of a label referred to from a line of a file.
+    ///
+    /// A label is looked for in the referring file first, then among the labels other files export.
+    /// A label qualified by the name of a file, e.g. `math::sqrt` for `lib/math.ez`, is only looked
+    /// for in that file, and must be exported unless it is the referring file. Local labels, e.g.
+    /// `.loop` or `1b`, are only looked for in the referring file, around the referring line.
+    pub fn resolve_label(
+        &self,
+        file_id: i64,
+        line_number: i64,
+        label: &str,
+    ) -> Result<(i64, i64), SimulatorError> {
+        let not_found = || SimulatorError::NonExistentLabelError(label.to_string());
+        let Some((module, name)) = label.split_once("::") else {
+            let local = self
+                .file_id_to_labels
+                .get(&file_id)
+                .and_then(|labels| labels.resolve(label, line_number));
+            return match local {
+                Some(line_number) => Ok((file_id, line_number)),
+                None => self.find_exported_label(label, None).ok_or_else(not_found),
+            };
+        };
+
+        let mut file_ids: Vec<i64> = self
+            .file_identifiers
+            .iter()
+            .filter(|(file, _)| Path::new(file).file_stem() == Some(OsStr::new(module)))
+            .map(|(_, file_id)| *file_id)
+            .collect();
+        file_ids.sort();
+        for other_file_id in file_ids {
+            let Some(line_number) = self.get_label(other_file_id, name) else {
+                continue;
+            };
+            if other_file_id != file_id
+                && !self
+                    .exported_labels
+                    .contains(&(other_file_id, name.to_string()))
+            {
+                return Err(SimulatorError::LabelNotExportedError(
+                    name.to_string(),
+                    self.file_name(other_file_id),
+                ));
+            }
+            return Ok((other_file_id, line_number));
+        }
+        Err(not_found())
     }
 
     pub fn is_error(&self, fid: i64, pc: i64) -> bool {
@@ -119,7 +360,80 @@ impl Program {
         }
     }
 
+    /// Gets the name of the file with the given identifier, or an empty string if it has none.
+    pub fn file_name(&self, file_id: i64) -> String {
+        match self.file_identifiers.get_by_right(&file_id) {
+            Some(x) => x.into(),
+            None => "".into(),
+        }
+    }
+
     pub fn file_exists(&self, file: &String) -> bool {
         self.file_identifiers.get_by_left(file).is_some()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::lexer::{parse_lines, parse_numbered_lines};
+    use crate::util::word_size::WordSize;
+
+    #[test]
+    fn test_label_scoping() {
+        let word_size = WordSize::default();
+        let instructions = InstructionSet::standard();
+        let mut program = Program::new();
+        let add_file = |program: &mut Program, name: &str, code: &str| {
+            for (source_line, line) in
+                parse_numbered_lines(code, &word_size, &instructions).unwrap()
+            {
+                program.add_numbered_line(line, source_line, name.to_string())?;
+            }
+            Ok::<(), SimulatorError>(())
+        };
+        add_file(
+            &mut program,
+            "main.ez",
+            "loop:\ncall sqrt\ncall math::helper",
+        )
+        .unwrap();
+        add_file(
+            &mut program,
+            "lib/math.ez",
+            "loop:\n.global sqrt\nsqrt:\nhelper:",
+        )
+        .unwrap();
+
+        assert_eq!(program.resolve_label(0, 0, "loop").unwrap(), (0, 0));
+        assert_eq!(program.resolve_label(1, 0, "loop").unwrap(), (1, 0));
+        assert_eq!(program.resolve_label(0, 0, "sqrt").unwrap(), (1, 2));
+        assert_eq!(program.resolve_label(1, 0, "math::helper").unwrap(), (1, 3));
+        assert!(matches!(
+            program.resolve_label(0, 0, "helper"),
+            Err(SimulatorError::NonExistentLabelError(_))
+        ));
+        assert_eq!(
+            program
+                .resolve_label(0, 0, "math::helper")
+                .unwrap_err()
+                .to_string(),
+            "label `helper` is not exported by `lib/math.ez`"
+        );
+
+        let error = add_file(&mut program, "roots.ez", "# roots\nsqrt:\n.export sqrt").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "label `sqrt` at line 2 of `roots.ez` is already defined at line 3 of `lib/math.ez`"
+        );
+        // a line added without its line is numbered after the last line of its file
+        let line = parse_lines("loop:", &word_size, &instructions).unwrap();
+        let error = program
+            .add_line(line[0].clone(), "main.ez".to_string())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "label `loop` at line 4 of `main.ez` is already defined at line 1 of `main.ez`"
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 use std::io::{self, Read, Write};
 use std::rc::Rc;
 
@@ -26,12 +27,23 @@ pub type Scanner = ScannerAscii<ReaderCell>;
 ///   * `Send`
 ///   * `Sync`
 ///
+/// The cell also counts the bytes read through it, shared between all of its clones, so that the
+/// position of the simulator in its input can be saved and restored. Bytes which were read ahead
+/// but not used can be given back with `unread`, so that they neither count as read nor are lost.
+///
 /// # Panics
 ///
 /// * When a mutable reference to the interior `Reader` already exists, yet another one is
 ///   requested, the program must panic in order to preserve Rust's memory safety guarantees.
 #[derive(Debug)]
-pub struct ReaderCell(Rc<RefCell<dyn Reader>>);
+pub struct ReaderCell {
+    reader: Rc<RefCell<dyn Reader>>,
+    position: Rc<Cell<usize>>,
+    // bytes which were given back, to be read again before anything more from the reader
+    unread: Rc<RefCell<VecDeque<u8>>>,
+    // whether reads return nothing rather than reading from the reader
+    paused: Rc<Cell<bool>>,
+}
 
 impl ReaderCell {
     /// Creates a new reader cell from a reader.
@@ -44,7 +56,62 @@ impl ReaderCell {
     /// let reader_cell = ReaderCell::new(DummyReader::new());
     /// ```
     pub fn new<R: Reader + 'static>(reader: R) -> Self {
-        Self(Rc::new(RefCell::new(reader)))
+        Self {
+            reader: Rc::new(RefCell::new(reader)),
+            position: Rc::new(Cell::new(0)),
+            unread: Rc::new(RefCell::new(VecDeque::new())),
+            paused: Rc::new(Cell::new(false)),
+        }
+    }
+
+    /// Gets the number of bytes which have been read through this cell, less any given back.
+    pub fn position(&self) -> usize {
+        self.position.get()
+    }
+
+    /// Gives back bytes which were read but not used, so that they are the next to be read.
+    pub fn unread(&mut self, bytes: &[u8]) {
+        let mut unread = self.unread.borrow_mut();
+        for byte in bytes.iter().rev() {
+            unread.push_front(*byte);
+        }
+        self.position
+            .set(self.position.get().saturating_sub(bytes.len()));
+    }
+
+    /// Runs a function during which reads through this cell return nothing, e.g. to see what a
+    /// scanner has already read without it waiting for more.
+    pub fn without_reading<T>(&self, function: impl FnOnce() -> T) -> T {
+        // resumes reading when dropped, even if the function panics
+        struct Resume<'a>(&'a Cell<bool>);
+        impl Drop for Resume<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+
+        self.paused.set(true);
+        let _resume = Resume(&self.paused);
+        function()
+    }
+
+    /// Reads and discards up to `count` bytes, returning the number of bytes actually skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - the number of bytes to skip.
+    pub fn skip(&mut self, count: usize) -> io::Result<usize> {
+        let mut buf = [0u8; 256];
+        let mut skipped = 0usize;
+        while skipped < count {
+            let wanted = usize::min(buf.len(), count - skipped);
+            let read = self.read(&mut buf[..wanted])?;
+            if read == 0 {
+                break;
+            }
+            skipped += read;
+        }
+        Ok(skipped)
     }
 }
 
@@ -54,23 +121,36 @@ unsafe impl Sync for ReaderCell {}
 
 impl Clone for ReaderCell {
     fn clone(&self) -> Self {
-        Self(self.0.clone())
+        Self {
+            reader: self.reader.clone(),
+            position: self.position.clone(),
+            unread: self.unread.clone(),
+            paused: self.paused.clone(),
+        }
     }
 }
 
 impl Read for ReaderCell {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        (*self.0.borrow_mut()).read(buf)
+        if self.paused.get() {
+            return Ok(0);
+        }
+        let mut read = self.unread.borrow_mut().read(buf)?;
+        if read == 0 {
+            read = (*self.reader.borrow_mut()).read(buf)?;
+        }
+        self.position.set(self.position.get() + read);
+        Ok(read)
     }
 }
 
 impl Write for ReaderCell {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        (*self.0.borrow_mut()).write(buf)
+        (*self.reader.borrow_mut()).write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        (*self.0.borrow_mut()).flush()
+        (*self.reader.borrow_mut()).flush()
     }
 }
 
@@ -83,3 +163,32 @@ impl AsAny for ReaderCell {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+    use crate::test_utils::TestReader;
+
+    #[test]
+    fn test_without_reading() {
+        let mut reader = ReaderCell::new(TestReader::new("ab"));
+        let mut buf = [0u8; 1];
+        let mut paused = reader.clone();
+        assert_eq!(reader.without_reading(|| paused.read(&mut buf).unwrap()), 0);
+
+        // reading resumes even when the function panics
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            reader.without_reading(|| panic!("stopped while paused"))
+        }));
+        assert!(result.is_err());
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], b'a');
+
+        reader.unread(b"a");
+        assert_eq!(reader.position(), 0);
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], b'a');
+    }
+}
@@ -4,11 +4,11 @@ use bimap::BiMap;
 use lazy_static::lazy_static;
 
 use crate::simulation::register::Register;
-use crate::util::error::ParserError;
+use crate::util::error::{ParserError, SimulatorError};
 use crate::util::raw_data::RawData;
 use crate::util::word_size::WordSize;
 
-const REGISTERS_COUNT: usize = 54;
+const REGISTERS_COUNT: usize = 55;
 
 pub const ZERO_NUMBER: usize = 0;
 pub const FID_NUMBER: usize = 2;
@@ -16,6 +16,7 @@ pub const PC_NUMBER: usize = 3;
 pub const SP_NUMBER: usize = 4;
 pub const RA_NUMBER: usize = 5;
 pub const R0_NUMBER: usize = 9;
+pub const FFLAGS_NUMBER: usize = 54;
 
 lazy_static! {
     pub static ref REGISTERS_MAP: BiMap<String, usize> = {
@@ -97,12 +98,13 @@ pub const FLOAT_TEMPORARY_REGISTERS: [&str; 10] =
 
 pub const LO: &str = "LO"; // Special "LOW" register to store the lower part of a multiplication
 pub const HI: &str = "HI"; // Special "HIGH" register to store the higher part of a multiplication
-pub const SPECIAL_REGISTERS: [&str; 2] = [LO, HI];
+pub const FFLAGS: &str = "FFLAGS"; // The floating point exception flags which have been raised
+pub const SPECIAL_REGISTERS: [&str; 3] = [LO, HI, FFLAGS];
 
 pub const ALL_REGISTERS: [&str; REGISTERS_COUNT] = [
     ZERO, PID, FID, PC, SP, RA, A0, A1, A2, R0, R1, R2, S0, S1, S2, S3, S4, S5, S6, S7, S8, S9, T0,
     T1, T2, T3, T4, T5, T6, T7, T8, T9, FS0, FS1, FS2, FS3, FS4, FS5, FS6, FS7, FS8, FS9, FT0, FT1,
-    FT2, FT3, FT4, FT5, FT6, FT7, FT8, FT9, LO, HI,
+    FT2, FT3, FT4, FT5, FT6, FT7, FT8, FT9, LO, HI, FFLAGS,
 ];
 
 fn remove_dollar(string: &String) -> String {
@@ -162,6 +164,38 @@ pub fn is_valid_register_number(register: usize) -> bool {
     REGISTERS_MAP.contains_right(&register)
 }
 
+/// Gets the name of a register as it is written in source code, e.g. `$t0`.
+pub fn get_register_display_name(register: usize) -> String {
+    match REGISTERS_MAP.get_by_right(&register) {
+        Some(name) => format!("${}", name.to_lowercase()),
+        None => format!("${}", register),
+    }
+}
+
+/// Describes what a register is used for, e.g. `Temporary register 0` for `$t0`.
+pub fn get_register_description(register: usize) -> Option<String> {
+    let name = REGISTERS_MAP.get_by_right(&register)?.as_str();
+    let description = match name {
+        ZERO => "The number zero".to_string(),
+        PID => "Program identifier".to_string(),
+        FID => "File identifier".to_string(),
+        PC => "Program counter".to_string(),
+        SP => "Stack pointer".to_string(),
+        RA => "Return address".to_string(),
+        LO => "The lower part of a multiplication".to_string(),
+        HI => "The higher part of a multiplication".to_string(),
+        FFLAGS => "The floating point exception flags which have been raised".to_string(),
+        _ if name.starts_with("FS") => format!("Saved float register {}", &name[2..]),
+        _ if name.starts_with("FT") => format!("Temporary float register {}", &name[2..]),
+        _ if name.starts_with('A') => format!("Argument {}", &name[1..]),
+        _ if name.starts_with('R') => format!("Return {}", &name[1..]),
+        _ if name.starts_with('S') => format!("Saved register {}", &name[1..]),
+        _ if name.starts_with('T') => format!("Temporary register {}", &name[1..]),
+        _ => return None,
+    };
+    Some(description)
+}
+
 #[derive(Debug)]
 pub struct Registry {
     word_size: WordSize,
@@ -186,6 +220,45 @@ impl Registry {
         }
     }
 
+    /// Captures the values of all registers, in register number order.
+    pub fn snapshot(&self) -> Vec<RawData> {
+        self.registers
+            .iter()
+            .map(|register| *register.get_data())
+            .collect()
+    }
+
+    /// Replaces the word size and the values of all registers.
+    ///
+    /// Registers which are missing from the end of `values` are set to zero, so that snapshots
+    /// taken before a register was added can still be restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `word_size` - the word size the values were captured with.
+    /// * `values` - the register values, in register number order.
+    pub fn restore(
+        &mut self,
+        word_size: &WordSize,
+        values: &[RawData],
+    ) -> Result<(), SimulatorError> {
+        if values.len() > REGISTERS_COUNT {
+            return Err(SimulatorError::SnapshotError(format!(
+                "expected at most {} registers but found {}",
+                REGISTERS_COUNT,
+                values.len()
+            )));
+        }
+        self.word_size = *word_size;
+        for (number, register) in self.registers.iter_mut().enumerate() {
+            register.set_data(match values.get(number) {
+                Some(value) => *value,
+                None => RawData::empty_data(word_size),
+            });
+        }
+        Ok(())
+    }
+
     pub fn get_register_by_number(&self, register: usize) -> Result<&Register, ParserError> {
         if register >= REGISTERS_COUNT {
             Err(ParserError::InvalidRegisterNumberError(register))
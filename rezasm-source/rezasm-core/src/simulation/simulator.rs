@@ -1,4 +1,6 @@
 use std::fmt::Debug;
+use std::io::Write;
+use std::sync::Arc;
 
 use scanner_rust::ScannerAscii;
 
@@ -6,15 +8,24 @@ use super::reader::DummyReader;
 use super::reader_cell::{ReaderCell, Scanner};
 use super::transform::transformable::Transformable;
 use super::transform::transformation_sequence::TransformationSequence;
+use crate::instructions::instruction_set::InstructionSet;
 use crate::instructions::targets::input_output_target::InputOutputTarget;
 use crate::parser::line::Line;
+use crate::parser::linker::LinkedFile;
+use crate::simulation::call_stack::{Backtrace, BacktraceFrame, CallFrame, CallStack};
+use crate::simulation::decoded_program::{DecodedLine, DecodedProgram};
+use crate::simulation::explanation::{self, Explanation};
 use crate::simulation::memory;
 use crate::simulation::memory::Memory;
 use crate::simulation::program::Program;
 use crate::simulation::registry;
 use crate::simulation::registry::Registry;
+use crate::simulation::snapshot::{SimulatorSnapshot, SNAPSHOT_FORMAT, SNAPSHOT_VERSION};
+use crate::simulation::trace::Trace;
 use crate::simulation::writer::{DummyWriter, WriterBox};
-use crate::util::error::SimulatorError;
+use crate::util::endianness::Endianness;
+use crate::util::error::{IoError, SimulatorError};
+use crate::util::floating_point::RoundingMode;
 use crate::util::raw_data::RawData;
 use crate::util::word_size::{WordSize, DEFAULT_WORD_SIZE};
 
@@ -23,12 +34,23 @@ pub struct Simulator {
     memory: Memory,
     registry: Registry,
     program: Program,
+    // decoded from the program when it is first run after changing
+    decoded: Option<DecodedProgram>,
+    instruction_set: InstructionSet,
     word_size: WordSize,
     scanner: Scanner,
     reader: ReaderCell,
     writer: WriterBox,
+    output_cursor: usize,
     sequence: Vec<TransformationSequence>,
     can_undo: bool,
+    trap_overflow: bool,
+    check_calling_convention: bool,
+    rounding_mode: RoundingMode,
+    call_stack: CallStack,
+    trace: Trace,
+    explain: bool,
+    explanation: Option<Explanation>,
 }
 
 impl Simulator {
@@ -63,16 +85,34 @@ impl Simulator {
         reader: ReaderCell,
         writer: WriterBox,
     ) -> Simulator {
+        Simulator::new_with_memory(Memory::new_sized(word_size, memory_size), reader, writer)
+    }
+
+    /// Creates a simulator around a memory which has already been configured, e.g. with
+    /// `Memory::new_custom` to choose its backend and byte order. The word size is taken from the
+    /// memory.
+    pub fn new_with_memory(memory: Memory, reader: ReaderCell, writer: WriterBox) -> Simulator {
+        let word_size = *memory.word_size();
         let mut sim = Simulator {
-            memory: Memory::new_sized(word_size, memory_size),
-            registry: Registry::new(word_size),
+            memory,
+            registry: Registry::new(&word_size),
             program: Program::new(),
-            word_size: word_size.clone(),
+            decoded: None,
+            instruction_set: InstructionSet::standard(),
+            word_size,
             scanner: ScannerAscii::new(reader.clone()),
             reader,
             writer,
+            output_cursor: 0,
             sequence: Vec::new(),
             can_undo: true,
+            trap_overflow: false,
+            check_calling_convention: false,
+            rounding_mode: RoundingMode::default(),
+            call_stack: CallStack::new(),
+            trace: Trace::default(),
+            explain: false,
+            explanation: None,
         };
         sim.initialize();
         sim
@@ -92,6 +132,10 @@ impl Simulator {
         self.memory.reset();
         self.registry.reset();
         self.sequence.clear();
+        self.call_stack.reset();
+        self.trace.clear();
+        self.explanation = None;
+        self.decoded = None;
     }
 
     pub fn reset(&mut self) {
@@ -103,9 +147,24 @@ impl Simulator {
     pub fn add_line(&mut self, line: Line, file: String) -> Result<(), SimulatorError> {
         self.memory
             .add_string_immediates(line.get_string_immediates())?;
+        self.decoded = None;
         self.program.add_line(line, file)
     }
 
+    /// Adds a line along with the (1-based) line of its code file it came from, which is how errors
+    /// and backtraces refer to it.
+    pub fn add_numbered_line(
+        &mut self,
+        line: Line,
+        source_line: usize,
+        file: String,
+    ) -> Result<(), SimulatorError> {
+        self.memory
+            .add_string_immediates(line.get_string_immediates())?;
+        self.decoded = None;
+        self.program.add_numbered_line(line, source_line, file)
+    }
+
     pub fn add_lines(&mut self, lines: Vec<Line>, file: String) -> Result<(), SimulatorError> {
         for line in lines {
             self.add_line(line, file.clone())?;
@@ -113,10 +172,36 @@ impl Simulator {
         Ok(())
     }
 
+    /// Adds lines as parsed by `parse_numbered_lines`, along with the lines of their code file.
+    pub fn add_numbered_lines(
+        &mut self,
+        lines: Vec<(usize, Line)>,
+        file: String,
+    ) -> Result<(), SimulatorError> {
+        for (source_line, line) in lines {
+            self.add_numbered_line(line, source_line, file.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Adds the files of a linked program, which must start with the main file.
+    pub fn add_files(&mut self, files: Vec<LinkedFile>) -> Result<(), SimulatorError> {
+        for file in files {
+            for (source_line, line) in file.lines {
+                self.add_numbered_line(line, source_line, file.name.clone())?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_word_size(&self) -> &WordSize {
         &self.word_size
     }
 
+    pub fn get_endianness(&self) -> &Endianness {
+        self.memory.endianness()
+    }
+
     pub fn get_memory(&self) -> &Memory {
         &self.memory
     }
@@ -142,6 +227,7 @@ impl Simulator {
     }
 
     pub fn get_word_size_mut(&mut self) -> &mut WordSize {
+        self.decoded = None;
         &mut self.word_size
     }
 
@@ -154,6 +240,7 @@ impl Simulator {
     }
 
     pub fn get_program_mut(&mut self) -> &mut Program {
+        self.decoded = None;
         &mut self.program
     }
 
@@ -165,34 +252,108 @@ impl Simulator {
         &mut self.writer
     }
 
+    pub fn get_call_stack(&self) -> &CallStack {
+        &self.call_stack
+    }
+
+    pub fn get_trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.can_undo
+    }
+
+    /// Sets whether steps are recorded so that they can be undone.
+    ///
+    /// With undo disabled, steps are not kept, and instructions which support it write their results
+    /// directly rather than building transformations, see `Instruction::with_direct_function`. This
+    /// makes long running programs considerably faster. Disabling undo discards any recorded history.
+    pub fn set_can_undo(&mut self, can_undo: bool) {
+        self.can_undo = can_undo;
+        if !can_undo {
+            self.sequence.clear();
+        }
+    }
+
+    pub fn traps_overflow(&self) -> bool {
+        self.trap_overflow
+    }
+
+    /// Sets whether integer arithmetic which overflows the word size fails with
+    /// `SimulatorError::IntegerOverflow` instead of wrapping around.
+    pub fn set_trap_overflow(&mut self, trap_overflow: bool) {
+        self.trap_overflow = trap_overflow;
+    }
+
+    pub fn checks_calling_convention(&self) -> bool {
+        self.check_calling_convention
+    }
+
+    /// Sets whether each `return` fails with `SimulatorError::CallingConventionError` unless the
+    /// saved registers, `$s0`-`$s9` and `$fs0`-`$fs9`, and `$sp` are as they were after the
+    /// matching `call`. Only calls made while checking are checked.
+    pub fn set_check_calling_convention(&mut self, check_calling_convention: bool) {
+        self.check_calling_convention = check_calling_convention;
+    }
+
+    /// Gets the instructions which lines added to this simulator can use.
+    pub fn get_instruction_set(&self) -> &InstructionSet {
+        &self.instruction_set
+    }
+
+    pub fn get_instruction_set_mut(&mut self) -> &mut InstructionSet {
+        &mut self.instruction_set
+    }
+
+    /// Replaces the instructions which lines added to this simulator can use. Lines which have
+    /// already been added keep the instructions they were parsed with.
+    pub fn set_instruction_set(&mut self, instruction_set: InstructionSet) {
+        self.instruction_set = instruction_set;
+    }
+
+    pub fn get_rounding_mode(&self) -> RoundingMode {
+        self.rounding_mode
+    }
+
+    /// Sets how floating point results which cannot be represented exactly are rounded.
+    pub fn set_rounding_mode(&mut self, rounding_mode: RoundingMode) {
+        self.rounding_mode = rounding_mode;
+    }
+
+    pub fn explains(&self) -> bool {
+        self.explain
+    }
+
+    /// Sets whether each line which is run is explained in words, see `get_explanation`.
+    pub fn set_explain(&mut self, explain: bool) {
+        self.explain = explain;
+        self.explanation = None;
+    }
+
+    /// Gets the explanation of the most recently run line, if explanations are enabled.
+    pub fn get_explanation(&self) -> Option<&Explanation> {
+        self.explanation.as_ref()
+    }
+
+    /// Sets how many of the most recently run lines are kept in the trace; zero disables tracing.
+    pub fn set_trace_capacity(&mut self, capacity: usize) {
+        self.trace.set_capacity(capacity);
+    }
+
     pub fn end_pc(&self) -> usize {
-        let fid = self
-            .registry
-            .get_register(&registry::FID.to_string())
-            .unwrap()
-            .get_data()
-            .int_value();
+        let fid = self.registry.get_fid().get_data().int_value();
         return self.program.end_pc(fid);
     }
 
     pub fn is_done(&self) -> bool {
-        let fid = self
-            .registry
-            .get_register(&registry::FID.to_string())
-            .unwrap()
-            .get_data()
-            .int_value();
+        let fid = self.registry.get_fid().get_data().int_value();
         let pc = self.registry.get_pc().get_data().int_value();
         self.program.is_done(fid, pc)
     }
 
     pub fn is_error(&self) -> bool {
-        let fid = self
-            .registry
-            .get_register(&registry::FID.to_string())
-            .unwrap()
-            .get_data()
-            .int_value();
+        let fid = self.registry.get_fid().get_data().int_value();
         let pc = self.registry.get_pc().get_data().int_value();
         self.program.is_error(fid, pc)
     }
@@ -207,35 +368,320 @@ impl Simulator {
         }
     }
 
-    fn run_line(&mut self, line: &Line) -> Result<(), SimulatorError> {
-        let result = match line {
-            Line::Instruction(instruction, args) => {
-                instruction.get_function()(self, instruction.get_types(), &args)?
+    /// Runs a line, returning the transformations it made, or `None` if it is awaiting input.
+    ///
+    /// The transformations are only returned when explaining, as they are otherwise not needed.
+    /// With undo disabled, they are not even built when the instruction can run directly.
+    fn run_line(
+        &mut self,
+        line: &DecodedLine,
+    ) -> Result<Option<TransformationSequence>, SimulatorError> {
+        let result = match line.get_line() {
+            Line::Instruction(instruction, _) => {
+                if let Some(function) = instruction.get_direct_function() {
+                    if !self.can_undo && !self.explain {
+                        function(self, line.get_arguments())?;
+                        self.increment_pc();
+                        return Ok(Some(TransformationSequence::new_empty()));
+                    }
+                }
+                instruction.call_function(self, line.get_arguments())?
             }
-            Line::Label(label) => {
+            Line::Label(_) | Line::Export(_) => {
                 // no-op
                 TransformationSequence::new_empty()
             }
         };
         if result.contains_nullop() {
-            return Ok(());
+            return Ok(None);
         }
-        self.apply_transformation(result)
+        let applied = if self.explain {
+            result.clone()
+        } else {
+            TransformationSequence::new_empty()
+        };
+        self.apply_transformation(result)?;
+        Ok(Some(applied))
     }
 
     pub fn run_line_from_pc(&mut self) -> Result<(), SimulatorError> {
-        let line_number = match self.validate_pc() {
-            Ok(x) => x,
-            Err(error) => return Err(error),
+        self.step().map(|_| ())
+    }
+
+    /// Runs lines until the program is done, `max_steps` lines have run, or a line is waiting for
+    /// input, returning the number of lines which were run.
+    ///
+    /// This is the preferred way to run long programs, particularly with undo disabled through
+    /// `set_can_undo`.
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, SimulatorError> {
+        let mut steps = 0usize;
+        while steps < max_steps && !self.is_done() {
+            if !self.step()? {
+                break;
+            }
+            steps += 1;
+        }
+        Ok(steps)
+    }
+
+    /// Runs the line at the program counter and, if it is a `call`, the function it calls up to and
+    /// including its `return`, returning the number of lines which were run.
+    ///
+    /// Running stops early if the program is done, `max_steps` lines have run, or a line is waiting
+    /// for input. The lines which were run are recorded as a single step, so that one undo reverts
+    /// them all.
+    pub fn step_over(&mut self, max_steps: usize) -> Result<usize, SimulatorError> {
+        self.step_over_until(max_steps, |_| false)
+    }
+
+    /// Runs lines as `step_over` does, but also stops before any line after the first for which
+    /// `stop_before` is true, such as a line with a breakpoint.
+    pub fn step_over_until(
+        &mut self,
+        max_steps: usize,
+        stop_before: impl Fn(&Simulator) -> bool,
+    ) -> Result<usize, SimulatorError> {
+        let depth = self.call_stack.depth();
+        self.run_as_one_step(max_steps, |simulator, steps| {
+            steps > 0 && (simulator.call_stack.depth() <= depth || stop_before(simulator))
+        })
+    }
+
+    /// Runs lines until the function at the program counter has returned, returning the number of
+    /// lines which were run. Outside of any function, this runs until the program is done.
+    ///
+    /// Running stops early as it does for `step_over`, and the lines which were run are likewise
+    /// recorded as a single step.
+    pub fn step_out(&mut self, max_steps: usize) -> Result<usize, SimulatorError> {
+        self.step_out_until(max_steps, |_| false)
+    }
+
+    /// Runs lines as `step_out` does, but also stops before any line after the first for which
+    /// `stop_before` is true.
+    pub fn step_out_until(
+        &mut self,
+        max_steps: usize,
+        stop_before: impl Fn(&Simulator) -> bool,
+    ) -> Result<usize, SimulatorError> {
+        let depth = self.call_stack.depth();
+        self.run_as_one_step(max_steps, |simulator, steps| {
+            simulator.call_stack.depth() < depth || (steps > 0 && stop_before(simulator))
+        })
+    }
+
+    /// Runs lines until `is_finished`, given the number of lines run so far, is true, combining
+    /// the steps which were run into one.
+    fn run_as_one_step(
+        &mut self,
+        max_steps: usize,
+        is_finished: impl Fn(&Simulator, usize) -> bool,
+    ) -> Result<usize, SimulatorError> {
+        let first = self.sequence.len();
+        let mut steps = 0usize;
+        let result = loop {
+            if steps >= max_steps || self.is_done() || is_finished(self, steps) {
+                break Ok(steps);
+            }
+            match self.step() {
+                Ok(true) => steps += 1,
+                Ok(false) => break Ok(steps),
+                Err(error) => break Err(error),
+            }
         };
-        let fid = self
-            .registry
-            .get_register(&registry::FID.to_string())
-            .unwrap();
-        let line = self
-            .program
-            .get_line(fid.get_data().int_value(), line_number)?;
-        self.run_line(&line.clone())
+        self.merge_steps(first);
+        result
+    }
+
+    /// Combines the steps recorded from the given one onwards into one.
+    fn merge_steps(&mut self, first: usize) {
+        if !self.can_undo || self.sequence.len() <= first + 1 {
+            return;
+        }
+        let mut merged = TransformationSequence::new_empty();
+        for sequence in self.sequence.drain(first..) {
+            merged.concatenate(sequence);
+        }
+        self.sequence.push(merged);
+        self.call_stack.merge_steps(first);
+    }
+
+    /// Runs the line at the program counter, returning whether it was run to completion rather than
+    /// awaiting input.
+    fn step(&mut self) -> Result<bool, SimulatorError> {
+        let line_number = self.validate_pc()?;
+        let fid = self.registry.get_fid().get_data().int_value();
+        let decoded = self.get_decoded_line(fid, line_number)?;
+        let line = decoded.get_line();
+        let operands = if self.explain {
+            explanation::resolve_operands(self, line)
+        } else {
+            Vec::new()
+        };
+        self.explanation = None;
+        let input_position = self.reader.position();
+        let result = self
+            .check_return(line)
+            .and_then(|_| self.run_line(&decoded));
+        if self.reader.position() != input_position {
+            self.return_read_ahead()?;
+        }
+        if !matches!(result, Ok(None)) {
+            self.trace.record(fid, line_number, line);
+        }
+        let Some(applied) = result? else {
+            return Ok(false);
+        };
+        self.track_call_stack(line, (fid, line_number));
+        if self.explain {
+            self.explanation = Some(Explanation::new(
+                fid,
+                line_number,
+                line,
+                &operands,
+                &applied,
+            ));
+        }
+        Ok(true)
+    }
+
+    /// Gets a line of the program as it is run, decoding the program first if it has changed.
+    fn get_decoded_line(
+        &mut self,
+        file_id: i64,
+        line_number: i64,
+    ) -> Result<Arc<DecodedLine>, SimulatorError> {
+        let decoded = self.decoded.get_or_insert_with(|| {
+            DecodedProgram::new(&self.program, &self.memory, &self.word_size)
+        });
+        decoded.get_line(file_id, line_number).cloned()
+    }
+
+    /// Updates the shadow call stack after a `call` or `return` has been run.
+    fn track_call_stack(&mut self, line: &Line, call_site: (i64, i64)) {
+        let Line::Instruction(instruction, _) = line else {
+            return;
+        };
+        let step = if self.can_undo {
+            self.sequence.len().checked_sub(1)
+        } else {
+            None
+        };
+        match instruction.get_name().as_str() {
+            "call" => {
+                let fid = self.registry.get_fid().get_data().int_value();
+                let pc = self.registry.get_pc().get_data().int_value();
+                let label = match self.program.get_line(fid, pc - 1) {
+                    Ok(Line::Label(label)) => label.to_string(),
+                    _ => format!("line {}", pc - 1),
+                };
+                let frame = CallFrame {
+                    call_site,
+                    callee: (fid, pc - 1),
+                    label,
+                    stack_pointer: self.registry.get_sp().get_data().int_value(),
+                    saved_registers: if self.check_calling_convention {
+                        self.get_saved_registers()
+                    } else {
+                        Vec::new()
+                    },
+                };
+                self.call_stack.push(frame, step);
+            }
+            "return" => {
+                self.call_stack.pop(step);
+            }
+            _ => {}
+        }
+    }
+
+    /// Lists the functions which are running, from the innermost, at the program counter, out to
+    /// the code the program started in.
+    pub fn backtrace(&self) -> Backtrace {
+        let mut location = (
+            self.registry.get_fid().get_data().int_value(),
+            self.registry.get_pc().get_data().int_value(),
+        );
+        let frame =
+            |function: &str, (fid, line_number): (i64, i64), stack_pointer| BacktraceFrame {
+                function: function.to_string(),
+                file: self.program.file_name(fid),
+                location: (fid, line_number),
+                line: self.program.source_line(fid, line_number),
+                code: self
+                    .program
+                    .get_line(fid, line_number)
+                    .map(|line| line.to_string())
+                    .unwrap_or_default(),
+                stack_pointer,
+            };
+        let mut frames = Vec::new();
+        for call in self.call_stack.frames().iter().rev() {
+            frames.push(frame(&call.label, location, Some(call.stack_pointer)));
+            location = call.call_site;
+        }
+        frames.push(frame("main", location, None));
+        Backtrace { frames }
+    }
+
+    /// Gets the values of the registers which the calling convention requires functions to restore.
+    fn get_saved_registers(&self) -> Vec<(usize, RawData)> {
+        registry::SAVED_REGISTERS
+            .iter()
+            .chain(registry::FLOAT_SAVED_REGISTERS.iter())
+            .chain([registry::SP].iter())
+            .filter_map(|name| {
+                let register = self.registry.get_register(name).ok()?;
+                let number = registry::get_register_number(&name.to_string()).ok()?;
+                Some((number, *register.get_data()))
+            })
+            .collect()
+    }
+
+    /// Checks that a `return` leaves the registers recorded by its `call` as they were, if the
+    /// calling convention is being checked.
+    fn check_return(&self, line: &Line) -> Result<(), SimulatorError> {
+        let Line::Instruction(instruction, _) = line else {
+            return Ok(());
+        };
+        if !self.check_calling_convention || instruction.get_name() != "return" {
+            return Ok(());
+        }
+        let Some(frame) = self.call_stack.frames().last() else {
+            return Ok(());
+        };
+        let changed: Vec<String> = frame
+            .saved_registers
+            .iter()
+            .filter_map(|(number, value)| {
+                let current = self
+                    .registry
+                    .get_register_by_number(*number)
+                    .ok()?
+                    .get_data();
+                if current == value {
+                    return None;
+                }
+                let float = registry::FLOAT_SAVED_REGISTERS.iter().any(|name| {
+                    registry::get_register_number(&name.to_string()).ok() == Some(*number)
+                });
+                Some(format!(
+                    "{} (was {}, now {})",
+                    registry::get_register_display_name(*number),
+                    explanation::format_value(value, float, false),
+                    explanation::format_value(current, float, false)
+                ))
+            })
+            .collect();
+        if changed.is_empty() {
+            return Ok(());
+        }
+        Err(SimulatorError::CallingConventionError(
+            frame.label.to_string(),
+            self.program.file_name(frame.call_site.0),
+            self.program
+                .source_line(frame.call_site.0, frame.call_site.1),
+            changed.join(", "),
+        ))
     }
 
     pub fn apply_transformation(
@@ -243,6 +689,10 @@ impl Simulator {
         mut transform: TransformationSequence,
     ) -> Result<(), SimulatorError> {
         transform.apply(self)?;
+        if !self.can_undo {
+            self.increment_pc();
+            return Ok(());
+        }
         let pc_transformable = Transformable::InputOutputTransformable(
             InputOutputTarget::RegisterInputOutput(registry::PC_NUMBER),
         );
@@ -252,51 +702,135 @@ impl Simulator {
         )?;
         pc_transformation.apply(self)?;
 
-        if self.can_undo {
-            transform.concatenate(TransformationSequence::new_single(pc_transformation));
-            self.sequence.push(transform.clone());
-            if transform.contains_nullop() {
-                return Ok(());
-            }
-        }
+        transform.concatenate(TransformationSequence::new_single(pc_transformation));
+        self.sequence.push(transform);
         Ok(())
     }
 
+    fn increment_pc(&mut self) {
+        let pc = self.registry.get_pc().get_data().int_value();
+        self.registry
+            .get_pc_mut()
+            .set_data(RawData::from_int(pc + 1, &self.word_size));
+    }
+
     pub fn undo_last_transformation(&mut self) -> Result<bool, SimulatorError> {
         if !self.can_undo || self.sequence.is_empty() {
             Ok(false)
         } else {
             // unwrap is safe because emptiness is checked
             self.sequence.pop().unwrap().invert().apply(self)?;
+            self.explanation = None;
+            self.call_stack.undo(self.sequence.len());
             Ok(true)
         }
     }
 
-    pub fn get_label_line_number(&self, label: &String) -> Result<i64, SimulatorError> {
-        match self.program.resolve_label(label) {
-            None => Err(SimulatorError::NonExistentLabelError(label.clone())),
-            Some((_, line_number)) => Ok(line_number.clone()),
+    /// Writes a string to the simulator's writer, keeping track of how much has been written.
+    pub fn write_output(&mut self, string: &str) -> Result<(), IoError> {
+        self.writer
+            .write_all(string.as_bytes())
+            .map_err(IoError::StdIoError)?;
+        self.writer.flush().map_err(IoError::StdIoError)?;
+        self.output_cursor += string.len();
+        Ok(())
+    }
+
+    /// Gets the number of bytes the program has written to its output.
+    pub fn get_output_cursor(&self) -> usize {
+        self.output_cursor
+    }
+
+    /// Captures the complete state of the simulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `include_history` - whether to include the undo history in the snapshot.
+    pub fn snapshot(&self, include_history: bool) -> SimulatorSnapshot {
+        SimulatorSnapshot {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            word_size: self.word_size,
+            memory: self.memory.snapshot(),
+            registers: self.registry.snapshot(),
+            program: self.program.snapshot(),
+            input_cursor: self.reader.position(),
+            output_cursor: self.output_cursor,
+            history: if include_history && self.can_undo {
+                Some(self.sequence.clone())
+            } else {
+                None
+            },
+            call_stack: self.call_stack.clone(),
         }
     }
+
+    /// Replaces the state of the simulator with a snapshot.
+    ///
+    /// The reader is expected to be positioned at the start of the same input the snapshot was
+    /// taken with; it is advanced past the input which had already been used, not counting any
+    /// which the scanner had only read ahead.
+    pub fn restore(&mut self, snapshot: SimulatorSnapshot) -> Result<(), SimulatorError> {
+        self.memory.restore(&snapshot.word_size, &snapshot.memory)?;
+        self.registry
+            .restore(&snapshot.word_size, &snapshot.registers)?;
+        self.program
+            .restore(&snapshot.program, &self.instruction_set)?;
+        self.decoded = None;
+        self.word_size = snapshot.word_size;
+        self.sequence = snapshot.history.unwrap_or_default();
+        self.call_stack = snapshot.call_stack;
+        self.trace.clear();
+
+        let to_skip = snapshot.input_cursor.saturating_sub(self.reader.position());
+        self.reader.skip(to_skip).map_err(IoError::StdIoError)?;
+        self.scanner = ScannerAscii::new(self.reader.clone());
+        self.output_cursor = snapshot.output_cursor;
+        Ok(())
+    }
+
+    /// Gives the input the scanner has read ahead of what it has used back to the reader, so that
+    /// the position of the reader is always the input which has been used, e.g. for a snapshot.
+    fn return_read_ahead(&mut self) -> Result<(), SimulatorError> {
+        let read_ahead = self
+            .reader
+            .without_reading(|| self.scanner.peek(false).map(|bytes| bytes.to_vec()))
+            .map_err(IoError::from)?;
+        if !read_ahead.is_empty() {
+            self.reader.unread(&read_ahead);
+            self.scanner = ScannerAscii::new(self.reader.clone());
+        }
+        Ok(())
+    }
+
+    pub fn get_label_line_number(&self, label: &str) -> Result<i64, SimulatorError> {
+        let fid = self.registry.get_fid().get_data().int_value();
+        let pc = self.registry.get_pc().get_data().int_value();
+        let (_, line_number) = self.program.resolve_label(fid, pc, label)?;
+        Ok(line_number)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        instructions::implementation::register_instructions,
-        parser::lexer::{parse_line, parse_lines},
+        parser::lexer::{parse_line, parse_lines, parse_numbered_lines},
+        util::error::ParserError,
     };
 
     use super::*;
 
     #[test]
     pub fn test_simulator_instruction() {
-        register_instructions();
         let mut simulator: Simulator = Simulator::new();
 
-        let line = parse_line(&"add $t0 $t0 1".to_string(), simulator.get_word_size())
-            .unwrap()
-            .unwrap();
+        let line = parse_line(
+            &"add $t0 $t0 1".to_string(),
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap()
+        .unwrap();
         let _ = simulator.add_line(line, "".to_string());
         let _ = simulator.run_line_from_pc();
 
@@ -313,7 +847,6 @@ mod tests {
 
     #[test]
     pub fn test_simulator_labels() {
-        register_instructions();
         let mut simulator: Simulator = Simulator::new();
         let program = "
         add $t0 0 0
@@ -323,7 +856,12 @@ mod tests {
         add $t0 0 $t1
         add $t1 0 $t2
         add $pc 0 fib";
-        let lines = parse_lines(&program.to_string(), simulator.get_word_size()).unwrap();
+        let lines = parse_lines(
+            &program.to_string(),
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
         match simulator.add_lines(lines, "".to_string()) {
             Ok(_) => {}
             Err(_) => assert!(false),
@@ -346,4 +884,237 @@ mod tests {
             233i64
         );
     }
+
+    #[test]
+    pub fn test_simulator_run_without_undo() {
+        let program = "
+        add $t0 0 0
+        add $t1 0 1
+        fib:
+        add $t2 $t0 $t1
+        add $t0 0 $t1
+        add $t1 0 $t2
+        add $pc 0 fib";
+
+        let mut results = Vec::new();
+        for can_undo in [true, false] {
+            let mut simulator: Simulator = Simulator::new();
+            simulator.set_can_undo(can_undo);
+            let lines = parse_lines(
+                &program.to_string(),
+                simulator.get_word_size(),
+                simulator.get_instruction_set(),
+            )
+            .unwrap();
+            simulator.add_lines(lines, "".to_string()).unwrap();
+            assert_eq!(simulator.run(50).unwrap(), 50);
+            results.push(
+                simulator
+                    .get_registers()
+                    .get_register(&registry::T1.to_string())
+                    .unwrap()
+                    .get_data()
+                    .int_value(),
+            );
+            assert_eq!(simulator.undo_last_transformation().unwrap(), can_undo);
+        }
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    pub fn test_simulator_decoded_lines() {
+        let program = "
+        add $t0 0 0
+        loop:
+        add $t0 $t0 1
+        blt $t0 10 loop
+        add $s0 0 \"hello\"
+        jump end
+        add $t0 0 0
+        end:";
+
+        let register = |simulator: &Simulator, name: &str| {
+            simulator
+                .get_registers()
+                .get_register(&name.to_string())
+                .unwrap()
+                .get_data()
+                .int_value()
+        };
+        for can_undo in [true, false] {
+            let mut simulator: Simulator = Simulator::new();
+            simulator.set_can_undo(can_undo);
+            let lines = parse_lines(
+                &program.to_string(),
+                simulator.get_word_size(),
+                simulator.get_instruction_set(),
+            )
+            .unwrap();
+            simulator.add_lines(lines, "".to_string()).unwrap();
+            simulator.run(100).unwrap();
+            assert!(simulator.is_done());
+            assert_eq!(register(&simulator, registry::T0), 10);
+            let address = register(&simulator, registry::S0) as usize;
+            assert_eq!(simulator.get_memory().get_string(address).unwrap(), "hello");
+
+            // lines added after running are decoded before they run
+            let lines = parse_lines(
+                "add $t1 0 \"world\"\nbeq 0 0 end",
+                simulator.get_word_size(),
+                simulator.get_instruction_set(),
+            )
+            .unwrap();
+            simulator.add_lines(lines, "".to_string()).unwrap();
+            simulator.run(2).unwrap();
+            let address = register(&simulator, registry::T1) as usize;
+            assert_eq!(simulator.get_memory().get_string(address).unwrap(), "world");
+            assert_eq!(register(&simulator, registry::PC), 8);
+        }
+    }
+
+    #[test]
+    pub fn test_simulator_word_size_two() {
+        let word_size = WordSize::Two;
+        let mut simulator = Simulator::new_custom(
+            &word_size,
+            memory::DEFAULT_MEMORY_WORDS,
+            ReaderCell::new(DummyReader::new()),
+            Box::new(DummyWriter::new()),
+        );
+        assert!(simulator.get_memory().initial_stack_pointer() <= i16::MAX as usize);
+
+        let program = "
+        add $t0 32767 1
+        push $t0
+        pop $t1
+        add $t2 0xffff 0";
+        let lines = parse_lines(program, &word_size, simulator.get_instruction_set()).unwrap();
+        simulator.add_lines(lines, "".to_string()).unwrap();
+        simulator.run(usize::MAX).unwrap();
+        let value = |register: &str| {
+            simulator
+                .get_registers()
+                .get_register(register)
+                .unwrap()
+                .get_data()
+                .int_value()
+        };
+        assert_eq!(value(registry::T0), -32768);
+        assert_eq!(value(registry::T1), -32768);
+        assert_eq!(value(registry::T2), -1);
+
+        assert!(matches!(
+            parse_lines(
+                "add $t0 $t0 65536",
+                &word_size,
+                simulator.get_instruction_set()
+            ),
+            Err(ParserError::ImmediateRangeError(65536, 2))
+        ));
+    }
+
+    #[test]
+    pub fn test_backtrace() {
+        let mut simulator: Simulator = Simulator::new();
+        let program = "
+        move $a0 2
+        call count
+        exit
+        count:
+        beq $a0 0 done
+        add $a0 $a0 -1
+        call count
+        return
+        done:
+        div $t0 1 0";
+        let lines =
+            parse_numbered_lines(program, &DEFAULT_WORD_SIZE, simulator.get_instruction_set())
+                .unwrap();
+        simulator
+            .add_numbered_lines(lines, "main.ez".to_string())
+            .unwrap();
+        assert!(simulator.run(usize::MAX).is_err());
+
+        let backtrace = simulator.backtrace();
+        let frames: Vec<(&str, i64, i64, Option<i64>)> = backtrace
+            .frames
+            .iter()
+            .map(|frame| {
+                (
+                    frame.function.as_str(),
+                    frame.location.1,
+                    frame.line,
+                    frame.stack_pointer,
+                )
+            })
+            .collect();
+        let sp = simulator.get_memory().initial_stack_pointer() as i64;
+        assert_eq!(
+            frames,
+            vec![
+                ("count", 9, 11, Some(sp - 24)),
+                ("count", 6, 8, Some(sp - 16)),
+                ("count", 6, 8, Some(sp - 8)),
+                ("main", 1, 3, None),
+            ]
+        );
+        assert!(backtrace
+            .to_string()
+            .ends_with("\n  main at line 3 of `main.ez`: call count"));
+    }
+
+    #[test]
+    pub fn test_step_over_and_out() {
+        let mut simulator: Simulator = Simulator::new();
+        let program = "
+        call twice
+        move $t1 1
+        exit
+        twice:
+        call once
+        call once
+        return
+        once:
+        add $t0 $t0 1
+        return";
+        let lines =
+            parse_lines(program, &DEFAULT_WORD_SIZE, simulator.get_instruction_set()).unwrap();
+        simulator.add_lines(lines, "".to_string()).unwrap();
+        let pc = |simulator: &Simulator| simulator.get_registers().get_pc().get_data().int_value();
+        let t0 = |simulator: &Simulator| {
+            simulator
+                .get_registers()
+                .get_register(registry::T0)
+                .unwrap()
+                .get_data()
+                .int_value()
+        };
+
+        assert_eq!(simulator.step_over(usize::MAX).unwrap(), 8);
+        assert_eq!((pc(&simulator), t0(&simulator)), (1, 2));
+        assert!(simulator.undo_last_transformation().unwrap());
+        assert_eq!((pc(&simulator), t0(&simulator)), (0, 0));
+        assert_eq!(simulator.get_call_stack().depth(), 0);
+
+        simulator.run(3).unwrap();
+        assert_eq!(simulator.get_call_stack().depth(), 2);
+        assert_eq!(simulator.step_out(usize::MAX).unwrap(), 1);
+        assert_eq!((pc(&simulator), simulator.get_call_stack().depth()), (5, 1));
+        // stopped early, inside the second call
+        assert_eq!(simulator.step_out(2).unwrap(), 2);
+        assert_eq!((t0(&simulator), simulator.get_call_stack().depth()), (2, 2));
+        assert!(simulator.undo_last_transformation().unwrap());
+        assert_eq!((pc(&simulator), t0(&simulator)), (5, 1));
+        assert_eq!(simulator.get_call_stack().depth(), 1);
+        assert!(simulator.undo_last_transformation().unwrap());
+        assert_eq!((pc(&simulator), simulator.get_call_stack().depth()), (9, 2));
+
+        // stopped before the line which was asked for, inside the first call
+        while simulator.undo_last_transformation().unwrap() {}
+        let steps = simulator.step_over_until(usize::MAX, |simulator| pc(simulator) == 8);
+        assert_eq!(steps.unwrap(), 2);
+        assert_eq!((pc(&simulator), simulator.get_call_stack().depth()), (8, 2));
+        assert!(simulator.undo_last_transformation().unwrap());
+        assert_eq!((pc(&simulator), simulator.get_call_stack().depth()), (0, 0));
+    }
 }
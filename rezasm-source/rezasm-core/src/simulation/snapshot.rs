@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::instructions::argument_type::ArgumentType;
+use crate::instructions::instruction_set::InstructionSet;
+use crate::parser::line::Line;
+use crate::simulation::call_stack::CallStack;
+use crate::simulation::transform::transformation_sequence::TransformationSequence;
+use crate::util::endianness::Endianness;
+use crate::util::error::{IoError, SimulatorError};
+use crate::util::raw_data::RawData;
+use crate::util::word_size::WordSize;
+
+/// Identifies a file on disk as a simulator snapshot.
+pub const SNAPSHOT_FORMAT: &str = "rezasm-snapshot";
+
+/// The current version of the snapshot format; bumped whenever a change is not backwards
+/// compatible.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A complete, serializable copy of the state of a `Simulator`.
+///
+/// A snapshot can be restored into any simulator with `Simulator::restore`, which replaces that
+/// simulator's memory, registers, and program. The reader and writer are not part of a snapshot;
+/// only the number of bytes consumed from and produced to them is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatorSnapshot {
+    pub format: String,
+    pub version: u32,
+    pub word_size: WordSize,
+    pub memory: MemorySnapshot,
+    pub registers: Vec<RawData>,
+    pub program: ProgramSnapshot,
+    pub input_cursor: usize,
+    pub output_cursor: usize,
+    pub history: Option<Vec<TransformationSequence>>,
+    #[serde(default)]
+    pub call_stack: CallStack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySnapshot {
+    #[serde(default)]
+    pub endianness: Endianness,
+    pub memory_words: usize,
+    pub alloc_index: usize,
+    pub string_alloc_index: usize,
+    pub strings: Vec<(String, RawData)>,
+    pub regions: Vec<MemoryRegion>,
+}
+
+/// A run of memory which contains non-zero bytes; everything outside of the regions is zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryRegion {
+    pub address: usize,
+    #[serde(with = "hex_bytes")]
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramSnapshot {
+    pub files: Vec<FileSnapshot>,
+    pub labels: Vec<LabelSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSnapshot {
+    pub identifier: i64,
+    pub name: Option<String>,
+    pub lines: Vec<LineSnapshot>,
+    /// The (1-based) line of its code file each line came from.
+    #[serde(default)]
+    pub source_lines: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelSnapshot {
+    pub label: String,
+    pub file_identifier: i64,
+    pub line_number: i64,
+}
+
+/// A `Line` with its instruction stored by name so that it can be looked up again on restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LineSnapshot {
+    Instruction {
+        name: String,
+        arguments: Vec<ArgumentType>,
+    },
+    Label(String),
+    Export(String),
+}
+
+impl From<&Line> for LineSnapshot {
+    fn from(line: &Line) -> Self {
+        match line {
+            Line::Instruction(instruction, arguments) => LineSnapshot::Instruction {
+                name: instruction.get_name().to_string(),
+                arguments: arguments.clone(),
+            },
+            Line::Label(label) => LineSnapshot::Label(label.to_string()),
+            Line::Export(label) => LineSnapshot::Export(label.to_string()),
+        }
+    }
+}
+
+impl LineSnapshot {
+    /// Converts the snapshot back into a line, looking its instruction up in an instruction set.
+    pub fn to_line(&self, instructions: &InstructionSet) -> Result<Line, SimulatorError> {
+        match self {
+            LineSnapshot::Instruction { name, arguments } => {
+                let instruction = instructions.get_instruction(name, arguments.len())?;
+                Ok(Line::Instruction(instruction.clone(), arguments.clone()))
+            }
+            LineSnapshot::Label(label) => Ok(Line::Label(label.to_string())),
+            LineSnapshot::Export(label) => Ok(Line::Export(label.to_string())),
+        }
+    }
+}
+
+impl SimulatorSnapshot {
+    /// Serializes the snapshot into its on-disk JSON representation.
+    pub fn to_json(&self) -> Result<String, SimulatorError> {
+        serde_json::to_string(self).map_err(|e| SimulatorError::SnapshotError(e.to_string()))
+    }
+
+    /// Deserializes a snapshot from its on-disk JSON representation, checking its format and
+    /// version first.
+    pub fn from_json(json: &str) -> Result<SimulatorSnapshot, SimulatorError> {
+        #[derive(Deserialize)]
+        struct Header {
+            format: String,
+            version: u32,
+        }
+
+        let header: Header =
+            serde_json::from_str(json).map_err(|e| SimulatorError::SnapshotError(e.to_string()))?;
+        if header.format != SNAPSHOT_FORMAT {
+            return Err(SimulatorError::SnapshotError(format!(
+                "unrecognized format `{}`",
+                header.format
+            )));
+        }
+        if header.version != SNAPSHOT_VERSION {
+            return Err(SimulatorError::SnapshotVersionError(header.version));
+        }
+        serde_json::from_str(json).map_err(|e| SimulatorError::SnapshotError(e.to_string()))
+    }
+
+    /// Writes the snapshot to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SimulatorError> {
+        fs::write(path, self.to_json()?).map_err(|e| IoError::StdIoError(e).into())
+    }
+
+    /// Reads a snapshot from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SimulatorSnapshot, SimulatorError> {
+        let path_string = path.as_ref().to_string_lossy().to_string();
+        let json =
+            fs::read_to_string(path).map_err(|_| IoError::FileDoesNotExistError(path_string))?;
+        SimulatorSnapshot::from_json(&json)
+    }
+}
+
+/// Splits memory into the regions which contain non-zero bytes.
+///
+/// Runs of zeroes shorter than `MERGE_DISTANCE` are kept inside a region rather than splitting it,
+/// so that words containing some zero bytes do not produce many tiny regions.
+pub fn non_zero_regions(memory: &[u8]) -> Vec<MemoryRegion> {
+    const MERGE_DISTANCE: usize = 16;
+    let mut regions: Vec<MemoryRegion> = Vec::new();
+    let mut index = 0usize;
+    while index < memory.len() {
+        if memory[index] == 0 {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        let mut end = index;
+        while index < memory.len() && index - end < MERGE_DISTANCE {
+            if memory[index] != 0 {
+                end = index;
+            }
+            index += 1;
+        }
+        regions.push(MemoryRegion {
+            address: start,
+            bytes: memory[start..=end].to_vec(),
+        });
+    }
+    regions
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let string: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        serializer.serialize_str(&string)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        if string.len() % 2 != 0 {
+            return Err(serde::de::Error::custom("odd number of hex digits"));
+        }
+        (0..string.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&string[i..i + 2], 16).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::lexer::parse_lines;
+    use crate::simulation::reader_cell::ReaderCell;
+    use crate::simulation::registry;
+    use crate::simulation::simulator::Simulator;
+    use crate::simulation::writer::DummyWriter;
+    use crate::test_utils::TestReader;
+
+    #[test]
+    fn test_non_zero_regions() {
+        let mut memory = vec![0u8; 100];
+        memory[3] = 1;
+        memory[5] = 2;
+        memory[60] = 3;
+        let regions = non_zero_regions(&memory);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].address, 3);
+        assert_eq!(regions[0].bytes, vec![1, 0, 2]);
+        assert_eq!(regions[1].address, 60);
+        assert_eq!(regions[1].bytes, vec![3]);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let program = "
+        move $s0 \"snapshot\"
+        alloc $s1 16
+        store 42 $s1
+        add $t0 0 0
+        add $t1 0 1
+        fib:
+        add $t2 $t0 $t1
+        add $t0 0 $t1
+        add $t1 0 $t2
+        add $pc 0 fib";
+
+        let mut simulator = Simulator::new();
+        let lines = parse_lines(
+            program,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .unwrap();
+        simulator.add_lines(lines, "main".to_string()).unwrap();
+        for _ in 0..25 {
+            simulator.run_line_from_pc().unwrap();
+        }
+
+        let json = simulator.snapshot(true).to_json().unwrap();
+        let mut restored = Simulator::new();
+        restored
+            .restore(SimulatorSnapshot::from_json(&json).unwrap())
+            .unwrap();
+
+        for _ in 0..25 {
+            simulator.run_line_from_pc().unwrap();
+            restored.run_line_from_pc().unwrap();
+        }
+        let t1 = |simulator: &Simulator| {
+            simulator
+                .get_registers()
+                .get_register(registry::T1)
+                .unwrap()
+                .get_data()
+                .int_value()
+        };
+        assert_eq!(t1(&simulator), t1(&restored));
+
+        let address = restored
+            .get_registers()
+            .get_register(registry::S0)
+            .unwrap()
+            .get_data()
+            .int_value();
+        assert_eq!(
+            restored.get_memory().get_string(address as usize).unwrap(),
+            "snapshot"
+        );
+
+        while restored.undo_last_transformation().unwrap() {}
+        assert_eq!(restored.get_registers().get_pc().get_data().int_value(), 0);
+    }
+
+    #[test]
+    fn test_snapshot_between_reads() {
+        let new_simulator = || {
+            let mut simulator = Simulator::new_custom_reader_writer(
+                ReaderCell::new(TestReader::new("12 34\n")),
+                Box::new(DummyWriter::new()),
+            );
+            let lines = parse_lines(
+                "readi $t0\nreadi $t1",
+                simulator.get_word_size(),
+                simulator.get_instruction_set(),
+            )
+            .unwrap();
+            simulator.add_lines(lines, "main".to_string()).unwrap();
+            simulator
+        };
+        let mut simulator = new_simulator();
+        simulator.run_line_from_pc().unwrap();
+
+        // the scanner read all of the input for the first number, but only used `12`
+        let json = simulator.snapshot(false).to_json().unwrap();
+        let mut restored = new_simulator();
+        restored
+            .restore(SimulatorSnapshot::from_json(&json).unwrap())
+            .unwrap();
+        restored.run_line_from_pc().unwrap();
+        let value = restored
+            .get_registers()
+            .get_register(registry::T1)
+            .unwrap()
+            .get_data()
+            .int_value();
+        assert_eq!(value, 34);
+    }
+
+    #[test]
+    fn test_snapshot_version() {
+        let json = format!("{{\"format\":\"{}\",\"version\":999}}", SNAPSHOT_FORMAT);
+        assert!(matches!(
+            SimulatorSnapshot::from_json(&json),
+            Err(SimulatorError::SnapshotVersionError(999))
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::line::Line;
+
+/// A line which the simulator ran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub file_identifier: i64,
+    pub line_number: i64,
+    pub line: String,
+}
+
+/// A bounded record of the most recently run lines, oldest first.
+///
+/// A trace with a capacity of zero records nothing, which is the default.
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    capacity: usize,
+    entries: VecDeque<TraceEntry>,
+}
+
+impl Trace {
+    pub fn new(capacity: usize) -> Trace {
+        Trace {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn record(&mut self, file_identifier: i64, line_number: i64, line: &Line) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(TraceEntry {
+            file_identifier,
+            line_number,
+            line: line.to_string(),
+        });
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -4,11 +4,12 @@ use crate::instructions::targets::output_target::Output;
 use crate::simulation::simulator::Simulator;
 use crate::util::error::SimulatorError;
 use crate::util::raw_data::RawData;
+use serde::{Deserialize, Serialize};
 
 use super::transformation::Transformation;
 
 /// NullOpTransformable is primarily for signalling the simulator to enter AWAITING
-#[derive(Copy, Debug)]
+#[derive(Copy, Debug, Serialize, Deserialize)]
 pub enum Transformable {
     FileReadTransformable(i64),
     HeapPointerTransformable,
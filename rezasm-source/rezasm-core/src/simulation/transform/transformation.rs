@@ -3,9 +3,11 @@ use crate::{
     util::{error::SimulatorError, raw_data::RawData},
 };
 
+use serde::{Deserialize, Serialize};
+
 use super::transformable::Transformable;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Transformation {
     output: Transformable,
     from: RawData,
@@ -21,19 +23,23 @@ impl Transformation {
         &self.from
     }
 
+    pub fn get_output(&self) -> &Transformable {
+        &self.output
+    }
+
     pub fn new(output: Transformable, from: RawData, to: RawData) -> Transformation {
         Transformation { output, from, to }
     }
     pub fn invert(&mut self) -> Transformation {
         Transformation {
             output: self.output.clone(),
-            from: self.to.clone(),
-            to: self.from.clone(),
+            from: self.to,
+            to: self.from,
         }
     }
 
     pub fn apply(&self, simulator: &mut Simulator) -> Result<(), SimulatorError> {
-        self.output.set(self.to.clone(), simulator)
+        self.output.set(self.to, simulator)
     }
 
     pub fn is_nullop(&self) -> bool {
@@ -45,8 +51,8 @@ impl Clone for Transformation {
     fn clone(&self) -> Transformation {
         Transformation {
             output: self.output.clone(),
-            from: self.from.clone(),
-            to: self.to.clone(),
+            from: self.from,
+            to: self.to,
         }
     }
 }
@@ -3,9 +3,11 @@ use crate::{
     util::{error::SimulatorError, raw_data::RawData},
 };
 
+use serde::{Deserialize, Serialize};
+
 use super::{transformable::Transformable, transformation::Transformation};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransformationSequence {
     transformations: Vec<Transformation>,
 }
@@ -51,6 +53,10 @@ impl TransformationSequence {
         }
     }
 
+    pub fn get_transformations(&self) -> &Vec<Transformation> {
+        &self.transformations
+    }
+
     pub fn contains_nullop(&self) -> bool {
         for t in &self.transformations {
             if t.is_nullop() {
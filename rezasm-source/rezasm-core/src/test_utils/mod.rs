@@ -1,7 +1,9 @@
 #![cfg(test)]
 
+mod reader;
 mod workspace_root;
 mod writer;
 
+pub use reader::TestReader;
 pub use workspace_root::workspace_root;
 pub use writer::TestWriter;
@@ -0,0 +1,46 @@
+use crate::simulation::reader::Reader;
+use crate::util::as_any::AsAny;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+
+#[derive(Debug)]
+pub struct TestReader {
+    input: VecDeque<u8>,
+}
+
+impl TestReader {
+    pub fn new(input: &str) -> TestReader {
+        TestReader {
+            input: input.bytes().collect(),
+        }
+    }
+}
+
+impl Reader for TestReader {}
+
+impl Read for TestReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for TestReader {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.input.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsAny for TestReader {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::error::SimulatorError;
+
+pub const DEFAULT_ENDIANNESS: Endianness = Endianness::Big;
+
+/// The order in which the bytes of a word are laid out in memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Endianness {
+    /// The most significant byte is stored at the lowest address.
+    Big,
+    /// The least significant byte is stored at the lowest address.
+    Little,
+}
+
+impl Endianness {
+    pub fn from(name: &str) -> Result<Endianness, SimulatorError> {
+        match name {
+            "big" => Ok(Endianness::Big),
+            "little" => Ok(Endianness::Little),
+            _ => Err(SimulatorError::InvalidEndiannessError(name.to_string())),
+        }
+    }
+}
+
+impl Default for Endianness {
+    #[inline]
+    fn default() -> Self {
+        DEFAULT_ENDIANNESS
+    }
+}
+
+impl Display for Endianness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endianness::Big => f.write_str("big-endian"),
+            Endianness::Little => f.write_str("little-endian"),
+        }
+    }
+}
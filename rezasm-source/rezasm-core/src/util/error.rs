@@ -33,6 +33,33 @@ pub enum ParserError {
     #[error("instruction `{0}` does not accept {1} arguments")]
     InvalidArgumentsCountError(String, usize),
 
+    #[error("a different instruction `{0}` with {1} arguments is already registered")]
+    InstructionConflictError(String, usize),
+
+    #[error("instruction `{0}` on line {1} is not allowed by the instruction policy")]
+    ForbiddenInstructionError(String, usize),
+
+    #[error("instruction `{0}` on line {1} exceeds the limit of {3} uses of `{2}` set by the instruction policy")]
+    InstructionLimitError(String, usize, String, usize),
+
+    #[error("invalid instruction policy: {0}")]
+    InvalidPolicyError(String),
+
+    #[error("could not find import `{0}` from `{1}`")]
+    ImportNotFoundError(String, String),
+
+    #[error("import `{0}` is outside of the root `{1}`")]
+    ImportOutsideRootError(String, String),
+
+    #[error("import cycle: {0}")]
+    ImportCycleError(String),
+
+    #[error("in `{0}`: {1}")]
+    ImportedFileError(String, Box<ParserError>),
+
+    #[error("unknown lint rule `{0}`")]
+    UnknownLintRuleError(String),
+
     #[error("invalid register number `{0}`")]
     InvalidRegisterNumberError(usize),
 
@@ -48,9 +75,15 @@ pub enum ParserError {
     #[error("invalid label definition `{0}`")]
     LabelDefinitionError(String),
 
+    #[error("invalid directive `{0}`, expected `{0} <label>`")]
+    DirectiveError(String),
+
     #[error("invalid numeric immediate `{0}`")]
     NumericalImmediateError(String),
 
+    #[error("immediate `{0}` does not fit in a {1}-byte word")]
+    ImmediateRangeError(i64, usize),
+
     #[error("invalid string immediate `{0}`")]
     StringImmediateError(String),
 
@@ -108,6 +141,15 @@ pub enum SimulatorError {
     #[error("invalid word size `{0}`")]
     InvalidWordSizeError(usize),
 
+    #[error("invalid endianness `{0}`, expected `big` or `little`")]
+    InvalidEndiannessError(String),
+
+    #[error("invalid rounding mode `{0}`, expected `nearest-even`, `nearest-away`, `toward-zero`, `up` or `down`")]
+    InvalidRoundingModeError(String),
+
+    #[error("invalid memory backend `{0}`, expected `dense` or `sparse`")]
+    InvalidMemoryBackendError(String),
+
     #[error("invalid heap pointer `{0}`")]
     InvalidHeapPointerError(usize),
 
@@ -129,17 +171,38 @@ pub enum SimulatorError {
     #[error("label `{0}` does not exist")]
     NonExistentLabelError(String),
 
-    #[error("label `{0}` is already in use")]
-    LabelInUseError(String),
+    #[error("label `{0}` at {1} is already defined at {2}")]
+    LabelInUseError(String, String, String),
+
+    #[error("label `{0}` is not exported by `{1}`")]
+    LabelNotExportedError(String, String),
 
     #[error("attempted to divide by zero")]
     DivideByZeroError,
 
+    #[error("bit field of length `{1}` starting at bit `{0}` does not fit in a {2}-bit word")]
+    InvalidBitFieldError(i64, i64, u32),
+
+    #[error("integer overflow in `{0}` with operands `{1}` and `{2}`")]
+    IntegerOverflow(String, i128, i128),
+
+    #[error("function `{0}` called from line {2} of `{1}` returned without restoring {3}")]
+    CallingConventionError(String, String, i64, String),
+
     #[error("attempted to convert NaN value to an integer")]
     NaNConversionError,
 
     #[error("could not read type {0}")]
     ReadError(String),
+
+    #[error("invalid snapshot: {0}")]
+    SnapshotError(String),
+
+    #[error("unsupported snapshot version `{0}`")]
+    SnapshotVersionError(u32),
+
+    #[error("invalid core dump: {0}")]
+    CoreDumpError(String),
 }
 
 #[derive(Error, Debug)]
@@ -173,6 +236,9 @@ pub enum IoError {
 
     #[error("the given directory doesn't exist")]
     DirectoryError,
+
+    #[error("files are not formatted: {0}")]
+    UnformattedFilesError(String),
 }
 
 impl From<ParseFloatError> for ParserError {
@@ -0,0 +1,315 @@
+use std::fmt::{Display, Formatter};
+
+use crate::util::error::SimulatorError;
+use crate::util::raw_data::{f16_to_f64, f64_to_f16, RawData};
+use crate::util::word_size::WordSize;
+
+/// Raised when an operation has no meaningful result, such as `0 / 0` or comparing with NaN.
+pub const FLAG_INVALID: i64 = 0x8;
+
+/// Raised when a result is too large to be represented and was rounded to infinity or the largest
+/// finite value.
+pub const FLAG_OVERFLOW: i64 = 0x4;
+
+/// Raised when an inexact result is so small that it is subnormal or zero.
+pub const FLAG_UNDERFLOW: i64 = 0x2;
+
+/// Raised when a result had to be rounded.
+pub const FLAG_INEXACT: i64 = 0x1;
+
+/// How the result of a floating point operation is rounded when it cannot be represented exactly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Rounds to the nearest value, and to the one with an even last digit on a tie.
+    #[default]
+    NearestEven,
+    /// Rounds to the nearest value, and away from zero on a tie.
+    NearestAway,
+    /// Rounds towards zero, i.e. truncates.
+    TowardZero,
+    /// Rounds towards positive infinity.
+    Up,
+    /// Rounds towards negative infinity.
+    Down,
+}
+
+impl RoundingMode {
+    pub fn from(name: &str) -> Result<RoundingMode, SimulatorError> {
+        match name {
+            "nearest-even" => Ok(RoundingMode::NearestEven),
+            "nearest-away" => Ok(RoundingMode::NearestAway),
+            "toward-zero" => Ok(RoundingMode::TowardZero),
+            "up" => Ok(RoundingMode::Up),
+            "down" => Ok(RoundingMode::Down),
+            _ => Err(SimulatorError::InvalidRoundingModeError(name.to_string())),
+        }
+    }
+
+    /// Rounds a value to an integer.
+    pub fn round_to_integer(&self, value: f64) -> f64 {
+        match self {
+            RoundingMode::NearestEven => value.round_ties_even(),
+            RoundingMode::NearestAway => value.round(),
+            RoundingMode::TowardZero => value.trunc(),
+            RoundingMode::Up => value.ceil(),
+            RoundingMode::Down => value.floor(),
+        }
+    }
+}
+
+impl Display for RoundingMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundingMode::NearestEven => f.write_str("nearest-even"),
+            RoundingMode::NearestAway => f.write_str("nearest-away"),
+            RoundingMode::TowardZero => f.write_str("toward-zero"),
+            RoundingMode::Up => f.write_str("up"),
+            RoundingMode::Down => f.write_str("down"),
+        }
+    }
+}
+
+/// Rounds the result of a floating point operation to the precision of a word.
+///
+/// # Arguments
+///
+/// * `value` - the result of the operation, rounded to the nearest double.
+/// * `error` - the exact result minus `value`; only its sign is used, to decide which way to round
+///   when `value` can be held exactly or is halfway between two values which can.
+/// * `operands` - the operands of the operation, which decide whether a NaN or infinite result is
+///   an exception.
+/// * `mode` - how to round a result which cannot be represented exactly.
+/// * `word_size` - the size of the word which the result is stored in.
+///
+/// # Returns
+///
+/// The result as a word, and the exception flags which the operation raised.
+pub fn round_to_word(
+    value: f64,
+    error: f64,
+    operands: &[f64],
+    mode: RoundingMode,
+    word_size: &WordSize,
+) -> (RawData, i64) {
+    if value.is_nan() {
+        let flags = if operands.iter().any(|operand| operand.is_nan()) {
+            0
+        } else {
+            FLAG_INVALID
+        };
+        return (RawData::from_float(f64::NAN, word_size), flags);
+    }
+
+    let rounded = nearest(value, word_size);
+    if rounded.is_infinite() && operands.iter().all(|operand| operand.is_finite()) {
+        let toward_zero = match mode {
+            RoundingMode::NearestEven | RoundingMode::NearestAway => false,
+            RoundingMode::TowardZero => true,
+            RoundingMode::Up => rounded < 0.0,
+            RoundingMode::Down => rounded > 0.0,
+        };
+        let result = if toward_zero {
+            largest_finite(word_size).copysign(rounded)
+        } else {
+            rounded
+        };
+        return (
+            RawData::from_float(result, word_size),
+            FLAG_OVERFLOW | FLAG_INEXACT,
+        );
+    }
+
+    // how far the exact result is above the rounded one
+    let difference = if rounded != value {
+        value - rounded
+    } else {
+        error
+    };
+    if difference == 0.0 || difference.is_nan() {
+        return (RawData::from_float(rounded, word_size), 0);
+    }
+
+    let up = difference > 0.0;
+    // a result halfway between two values is only a tie if it is also exact
+    let halfway = rounded != value
+        && difference.abs() * 2.0 == (next(rounded, up, word_size) - rounded).abs();
+    let step = match mode {
+        RoundingMode::NearestEven | RoundingMode::NearestAway if halfway && error != 0.0 => {
+            up == (error > 0.0)
+        }
+        RoundingMode::NearestEven => false,
+        RoundingMode::NearestAway => halfway && up == (rounded > 0.0),
+        RoundingMode::TowardZero => rounded != 0.0 && up == (rounded < 0.0),
+        RoundingMode::Up => up,
+        RoundingMode::Down => !up,
+    };
+    let result = if step {
+        next(rounded, up, word_size)
+    } else {
+        rounded
+    };
+
+    let mut flags = FLAG_INEXACT;
+    if result.abs() < smallest_normal(word_size) {
+        flags |= FLAG_UNDERFLOW;
+    }
+    (RawData::from_float(result, word_size), flags)
+}
+
+/// Gets the value nearest to `value` which a word can hold.
+fn nearest(value: f64, word_size: &WordSize) -> f64 {
+    match word_size {
+        WordSize::Two => f16_to_f64(f64_to_f16(value)),
+        WordSize::Four => value as f32 as f64,
+        WordSize::Eight => value,
+    }
+}
+
+/// Gets the next value above or below `value` which a word can hold.
+///
+/// This steps the bits of the value rather than using `next_up` and `next_down`, which need a newer
+/// compiler than the rest of the crate.
+fn next(value: f64, up: bool, word_size: &WordSize) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    // the smallest value below zero is the sign bit along with the lowest bit
+    let sign = match word_size {
+        WordSize::Two => 0x8000,
+        WordSize::Four => 0x8000_0000,
+        WordSize::Eight => 0x8000_0000_0000_0000,
+    };
+    let bits: u64 = match word_size {
+        WordSize::Two => f64_to_f16(value) as u64,
+        WordSize::Four => (value as f32).to_bits() as u64,
+        WordSize::Eight => value.to_bits(),
+    };
+    let bits = if value == 0.0 {
+        if up {
+            1
+        } else {
+            sign | 1
+        }
+    } else if up == (value > 0.0) {
+        bits + 1
+    } else {
+        bits - 1
+    };
+    match word_size {
+        WordSize::Two => f16_to_f64(bits as u16),
+        WordSize::Four => f32::from_bits(bits as u32) as f64,
+        WordSize::Eight => f64::from_bits(bits),
+    }
+}
+
+fn largest_finite(word_size: &WordSize) -> f64 {
+    match word_size {
+        WordSize::Two => 65504.0,
+        WordSize::Four => f32::MAX as f64,
+        WordSize::Eight => f64::MAX,
+    }
+}
+
+fn smallest_normal(word_size: &WordSize) -> f64 {
+    match word_size {
+        WordSize::Two => 2f64.powi(-14),
+        WordSize::Four => f32::MIN_POSITIVE as f64,
+        WordSize::Eight => f64::MIN_POSITIVE,
+    }
+}
+
+/// Adds two numbers, giving the sum and the error of rounding it to the nearest double.
+pub fn add_with_error(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_part = sum - a;
+    let a_part = sum - b_part;
+    (sum, (a - a_part) + (b - b_part))
+}
+
+/// Multiplies two numbers, giving the product and the error of rounding it to the nearest double.
+pub fn mul_with_error(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    (product, a.mul_add(b, -product))
+}
+
+/// Divides two numbers, giving the quotient and a value with the sign of the error of rounding it
+/// to the nearest double.
+pub fn div_with_error(a: f64, b: f64) -> (f64, f64) {
+    let quotient = a / b;
+    (quotient, (-quotient).mul_add(b, a) / b)
+}
+
+/// Takes the square root of a number, giving the root and a value with the sign of the error of
+/// rounding it to the nearest double.
+pub fn sqrt_with_error(a: f64) -> (f64, f64) {
+    let root = a.sqrt();
+    (root, (-root).mul_add(root, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_to_word() {
+        let third = |mode: RoundingMode| {
+            let (value, error) = div_with_error(1.0, 3.0);
+            let (data, flags) = round_to_word(value, error, &[1.0, 3.0], mode, &WordSize::Four);
+            assert_eq!(flags, FLAG_INEXACT);
+            data.float_value() as f32
+        };
+        assert_eq!(third(RoundingMode::NearestEven), 1.0f32 / 3.0);
+        assert_eq!(third(RoundingMode::Up), 1.0f32 / 3.0);
+        let below_third = f32::from_bits((1.0f32 / 3.0).to_bits() - 1);
+        assert_eq!(third(RoundingMode::Down), below_third);
+        assert_eq!(third(RoundingMode::TowardZero), below_third);
+
+        let (value, error) = add_with_error(1.0, 2f64.powi(-60));
+        for (mode, expected) in [
+            (RoundingMode::NearestEven, 1.0),
+            (RoundingMode::TowardZero, 1.0),
+            (RoundingMode::Up, f64::from_bits(1f64.to_bits() + 1)),
+        ] {
+            let (data, flags) = round_to_word(value, error, &[], mode, &WordSize::Eight);
+            assert_eq!(data.float_value(), expected);
+            assert_eq!(flags, FLAG_INEXACT);
+        }
+
+        let (data, flags) =
+            round_to_word(2.5, 0.0, &[], RoundingMode::NearestEven, &WordSize::Four);
+        assert_eq!((data.float_value(), flags), (2.5, 0));
+
+        let (data, flags) = round_to_word(1e6, 0.0, &[1e6], RoundingMode::Down, &WordSize::Two);
+        assert_eq!(data.float_value(), 65504.0);
+        assert_eq!(flags, FLAG_OVERFLOW | FLAG_INEXACT);
+
+        let (_, flags) = round_to_word(1e-30, 0.0, &[], RoundingMode::NearestEven, &WordSize::Two);
+        assert_eq!(flags, FLAG_INEXACT | FLAG_UNDERFLOW);
+
+        // halfway between two values a word can hold, where any error decides which way is nearest
+        for (word_size, below, above) in [
+            (WordSize::Two, 1.0, 1.0 + 2f64.powi(-10)),
+            (WordSize::Four, 1.0, 1.0 + 2f64.powi(-23)),
+        ] {
+            let tie = (below + above) / 2.0;
+            for (mode, error, expected) in [
+                (RoundingMode::NearestEven, 0.0, below),
+                (RoundingMode::NearestEven, 1e-30, above),
+                (RoundingMode::NearestAway, 0.0, above),
+                (RoundingMode::NearestAway, -1e-30, below),
+                (RoundingMode::NearestAway, 1e-30, above),
+            ] {
+                let (data, flags) = round_to_word(tie, error, &[], mode, &word_size);
+                assert_eq!(data.float_value(), expected);
+                assert_eq!(flags, FLAG_INEXACT);
+            }
+            let (data, _) = round_to_word(-tie, 0.0, &[], RoundingMode::NearestAway, &word_size);
+            assert_eq!(data.float_value(), -above);
+        }
+
+        let (value, error) = sqrt_with_error(-1.0);
+        let (data, flags) = round_to_word(value, error, &[-1.0], RoundingMode::Up, &WordSize::Four);
+        assert!(data.float_value().is_nan());
+        assert_eq!(flags, FLAG_INVALID);
+    }
+}
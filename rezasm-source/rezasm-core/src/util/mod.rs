@@ -1,5 +1,7 @@
 pub mod as_any;
+pub mod endianness;
 pub mod error;
+pub mod floating_point;
 pub mod io;
 pub mod raw_data;
 pub mod word_size;
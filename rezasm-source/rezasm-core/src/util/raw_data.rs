@@ -1,148 +1,319 @@
-use bytebuffer::ByteBuffer;
+use serde::{Deserialize, Serialize};
 
+use crate::util::endianness::Endianness;
+use crate::util::error::SimulatorError;
 use crate::util::word_size::WordSize;
 
-#[derive(Debug, PartialEq, Eq)]
+/// The largest number of bytes a `RawData` can hold.
+const MAX_BYTES: usize = 8;
+
+/// A single word of data, stored inline as big-endian bytes.
+///
+/// Only the first `word_size.value()` bytes are used; the rest are always zero. `RawData` is
+/// `Copy`, so it can be passed around freely without allocating. The byte order only matters once
+/// data is laid out in memory, which is done with `from_bytes` and `to_bytes`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "SerializedRawData", into = "SerializedRawData")]
 pub struct RawData {
-    pub data: Vec<u8>,
+    bytes: [u8; MAX_BYTES],
+    word_size: WordSize,
 }
 
 impl RawData {
     pub fn empty_data(size: &WordSize) -> RawData {
         RawData {
-            data: vec![0; size.value()],
+            bytes: [0; MAX_BYTES],
+            word_size: *size,
         }
     }
 
+    /// Creates data from the big-endian bytes of a single word.
+    ///
+    /// Fewer bytes than a word are zero-extended to the smallest word size which holds them, and
+    /// more than eight bytes are truncated to the last eight, as an integer would be; `try_new`
+    /// rejects them instead.
     pub fn new(data: &[u8]) -> RawData {
-        RawData {
-            data: data.to_vec(),
+        let data = &data[data.len().saturating_sub(MAX_BYTES)..];
+        let word_size = match data.len() {
+            0..=2 => WordSize::Two,
+            3..=4 => WordSize::Four,
+            _ => WordSize::Eight,
+        };
+        let mut bytes = [0; MAX_BYTES];
+        bytes[word_size.value() - data.len()..word_size.value()].copy_from_slice(data);
+        RawData { bytes, word_size }
+    }
+
+    /// Creates data from the big-endian bytes of a single word, or fails if the number of bytes is
+    /// not a valid word size.
+    pub fn try_new(data: &[u8]) -> Result<RawData, SimulatorError> {
+        let word_size = WordSize::from(data.len())?;
+        let mut bytes = [0; MAX_BYTES];
+        bytes[..data.len()].copy_from_slice(data);
+        Ok(RawData { bytes, word_size })
+    }
+
+    /// Creates data from the bytes of a single word stored in the given byte order.
+    pub fn from_bytes(data: &[u8], endianness: &Endianness) -> Result<RawData, SimulatorError> {
+        let mut raw_data = RawData::try_new(data)?;
+        if *endianness == Endianness::Little {
+            raw_data.bytes[..data.len()].reverse();
+        }
+        Ok(raw_data)
+    }
+
+    /// Gets the bytes of the word in the given byte order; only the first `word_size().value()`
+    /// bytes are meaningful.
+    pub fn to_bytes(&self, endianness: &Endianness) -> [u8; MAX_BYTES] {
+        let mut bytes = self.bytes;
+        if *endianness == Endianness::Little {
+            bytes[..self.word_size.value()].reverse();
         }
+        bytes
+    }
+
+    pub fn word_size(&self) -> &WordSize {
+        &self.word_size
+    }
+
+    /// Gets the big-endian bytes of the word.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.word_size.value()]
     }
 
     pub fn get_iter(&self) -> impl Iterator<Item = &'_ u8> {
-        self.data.iter()
+        self.as_bytes().iter()
     }
 
     pub fn int_value(&self) -> i64 {
-        let mut buffer = ByteBuffer::from(self.data.clone());
-        let size = match WordSize::from(self.data.len()) {
-            Ok(x) => x,
-            Err(_) => return 0i64,
-        };
-        match size {
-            WordSize::Four => buffer.read_i32().unwrap() as i64,
-            WordSize::Eight => buffer.read_i64().unwrap(),
+        match self.word_size {
+            WordSize::Two => i16::from_be_bytes(self.word::<2>()) as i64,
+            WordSize::Four => i32::from_be_bytes(self.word::<4>()) as i64,
+            WordSize::Eight => i64::from_be_bytes(self.bytes),
+        }
+    }
+
+    /// Gets the word as an unsigned integer, so that it is zero-extended rather than sign-extended.
+    pub fn unsigned_value(&self) -> u64 {
+        match self.word_size {
+            WordSize::Two => u16::from_be_bytes(self.word::<2>()) as u64,
+            WordSize::Four => u32::from_be_bytes(self.word::<4>()) as u64,
+            WordSize::Eight => u64::from_be_bytes(self.bytes),
         }
     }
 
     pub fn float_value(&self) -> f64 {
-        let mut buffer = ByteBuffer::from(self.data.clone());
-        let size = match WordSize::from(self.data.len()) {
-            Ok(x) => x,
-            Err(_) => return 0f64,
-        };
-        match size {
-            WordSize::Four => buffer.read_f32().unwrap() as f64,
-            WordSize::Eight => buffer.read_f64().unwrap(),
+        match self.word_size {
+            WordSize::Two => f16_to_f64(u16::from_be_bytes(self.word::<2>())),
+            WordSize::Four => f32::from_be_bytes(self.word::<4>()) as f64,
+            WordSize::Eight => f64::from_be_bytes(self.bytes),
         }
     }
 
     pub fn from_int(int: i64, size: &WordSize) -> RawData {
-        let mut buffer = ByteBuffer::new();
         match size {
-            WordSize::Four => buffer.write_i32(int as i32),
-            WordSize::Eight => buffer.write_i64(int),
-        };
-        RawData {
-            data: buffer.into_vec(),
+            WordSize::Two => RawData::from_word(&(int as i16).to_be_bytes(), size),
+            WordSize::Four => RawData::from_word(&(int as i32).to_be_bytes(), size),
+            WordSize::Eight => RawData::from_word(&int.to_be_bytes(), size),
         }
     }
 
     pub fn from_float(float: f64, size: &WordSize) -> RawData {
-        let mut buffer = ByteBuffer::new();
         match size {
-            WordSize::Four => buffer.write_f32(float as f32),
-            WordSize::Eight => buffer.write_f64(float),
-        };
+            WordSize::Two => RawData::from_word(&f64_to_f16(float).to_be_bytes(), size),
+            WordSize::Four => RawData::from_word(&(float as f32).to_be_bytes(), size),
+            WordSize::Eight => RawData::from_word(&float.to_be_bytes(), size),
+        }
+    }
+
+    fn from_word(word: &[u8], size: &WordSize) -> RawData {
+        let mut bytes = [0; MAX_BYTES];
+        bytes[..word.len()].copy_from_slice(word);
         RawData {
-            data: buffer.into_vec(),
+            bytes,
+            word_size: *size,
         }
     }
+
+    fn word<const N: usize>(&self) -> [u8; N] {
+        let mut word = [0; N];
+        word.copy_from_slice(&self.bytes[..N]);
+        word
+    }
 }
 
 impl From<i64> for RawData {
     fn from(value: i64) -> Self {
-        let mut buffer = ByteBuffer::new();
-        buffer.write_i64(value);
-        RawData {
-            data: buffer.into_vec(),
-        }
+        RawData::from_int(value, &WordSize::Eight)
     }
 }
 
 impl From<i32> for RawData {
     fn from(value: i32) -> Self {
-        let mut buffer = ByteBuffer::new();
-        buffer.write_i32(value);
-        RawData {
-            data: buffer.into_vec(),
-        }
+        RawData::from_int(value as i64, &WordSize::Four)
     }
 }
 
-impl Into<i64> for RawData {
-    fn into(self) -> i64 {
-        let size = match WordSize::from(self.data.len()) {
-            Ok(x) => x,
-            Err(_) => return 0i64,
-        };
-        let mut buffer = ByteBuffer::from(self.data);
-        match size {
-            WordSize::Four => buffer.read_i32().unwrap() as i64,
-            WordSize::Eight => buffer.read_i64().unwrap(),
-        }
+impl From<RawData> for i64 {
+    fn from(value: RawData) -> Self {
+        value.int_value()
     }
 }
 
 impl From<f64> for RawData {
     fn from(value: f64) -> Self {
-        let mut buffer = ByteBuffer::new();
-        buffer.write_f64(value);
-        RawData {
-            data: buffer.into_vec(),
-        }
+        RawData::from_float(value, &WordSize::Eight)
     }
 }
 
 impl From<f32> for RawData {
     fn from(value: f32) -> Self {
-        let mut buffer = ByteBuffer::new();
-        buffer.write_f32(value);
-        RawData {
-            data: buffer.into_vec(),
+        RawData::from_float(value as f64, &WordSize::Four)
+    }
+}
+
+impl From<RawData> for f64 {
+    fn from(value: RawData) -> Self {
+        value.float_value()
+    }
+}
+
+/// Converts a float to the bits of an IEEE 754 half precision float, rounding to nearest even.
+///
+/// The value is rounded once, straight from double precision, since rounding it to single
+/// precision first could turn it into a tie which then rounds the wrong way. Values too large for
+/// half precision become infinity, and values too small become zero.
+pub(crate) fn f64_to_f16(value: f64) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let exponent = ((bits >> 52) & 0x7ff) as i32;
+    let mantissa = bits & 0xf_ffff_ffff_ffff;
+
+    if exponent == 0x7ff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 1023 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    let round = |value: u64, shift: u32| -> u64 {
+        let truncated = value >> shift;
+        let remainder = value & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        if remainder > halfway || (remainder == halfway && truncated & 1 == 1) {
+            truncated + 1
+        } else {
+            truncated
+        }
+    };
+
+    if exponent <= 0 {
+        // subnormal, where the implicit leading one becomes explicit
+        if exponent < -10 {
+            return sign;
         }
+        return sign | round(mantissa | (1 << 52), (43 - exponent) as u32) as u16;
     }
+
+    // a carry out of the mantissa correctly moves on to the next exponent, or to infinity
+    sign | round(((exponent as u64) << 52) | mantissa, 42) as u16
 }
 
-impl Into<f64> for RawData {
-    fn into(self) -> f64 {
-        let size = match WordSize::from(self.data.len()) {
-            Ok(x) => x,
-            Err(_) => return 0f64,
-        };
-        let mut buffer = ByteBuffer::from(self.data);
-        match size {
-            WordSize::Four => buffer.read_f32().unwrap() as f64,
-            WordSize::Eight => buffer.read_f64().unwrap(),
+/// Converts the bits of an IEEE 754 half precision float to a float.
+pub(crate) fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    sign * match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+/// The serialized form of `RawData`, which is just the bytes of the word.
+#[derive(Serialize, Deserialize)]
+struct SerializedRawData {
+    data: Vec<u8>,
+}
+
+impl From<RawData> for SerializedRawData {
+    fn from(value: RawData) -> Self {
+        SerializedRawData {
+            data: value.as_bytes().to_vec(),
         }
     }
 }
 
-impl Clone for RawData {
-    fn clone(&self) -> Self {
-        RawData {
-            data: self.data.clone(),
+impl TryFrom<SerializedRawData> for RawData {
+    type Error = SimulatorError;
+
+    fn try_from(value: SerializedRawData) -> Result<Self, Self::Error> {
+        RawData::try_new(&value.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_data() {
+        for size in [WordSize::Two, WordSize::Four, WordSize::Eight] {
+            let data = RawData::from_int(-5, &size);
+            assert_eq!(data.as_bytes().len(), size.value());
+            assert_eq!(data.int_value(), -5);
+            assert_eq!(data.unsigned_value(), (u64::MAX >> (64 - size.bits())) - 4);
+            assert_eq!(RawData::from_float(2.5, &size).float_value(), 2.5);
         }
+        assert_eq!(
+            RawData::from_int(258, &WordSize::Four).as_bytes(),
+            &[0, 0, 1, 2]
+        );
+
+        let json = serde_json::to_string(&RawData::from_int(1, &WordSize::Four)).unwrap();
+        assert_eq!(json, "{\"data\":[0,0,0,1]}");
+        let data: RawData = serde_json::from_str(&json).unwrap();
+        assert_eq!(data.int_value(), 1);
+        assert!(serde_json::from_str::<RawData>("{\"data\":[1,2,3]}").is_err());
+
+        assert!(RawData::try_new(&[1, 2, 3]).is_err());
+        assert_eq!(
+            RawData::new(&[1, 2, 3]),
+            RawData::from_int(0x10203, &WordSize::Four)
+        );
+        assert_eq!(RawData::new(&[]), RawData::from_int(0, &WordSize::Two));
+        assert_eq!(
+            RawData::new(&[9, 0, 0, 0, 0, 0, 0, 0, 1]),
+            RawData::from_int(1, &WordSize::Eight)
+        );
+        assert_eq!(i64::from(RawData::from_int(-7, &WordSize::Four)), -7);
+        assert_eq!(f64::from(RawData::from_float(0.5, &WordSize::Two)), 0.5);
+
+        assert_eq!(RawData::from_int(40000, &WordSize::Two).int_value(), -25536);
+        let half = |value: f64| RawData::from_float(value, &WordSize::Two).float_value();
+        assert_eq!(half(65504.0), 65504.0);
+        assert_eq!(half(1e6), f64::INFINITY);
+        assert_eq!(half(-0.1), -0.0999755859375);
+        assert_eq!(half(2f64.powi(-24)), 2f64.powi(-24));
+        assert!(half(f64::NAN).is_nan());
+        // just above halfway between 1 and the next half, which a single would round down to a tie
+        assert_eq!(
+            half(1.0 + 2f64.powi(-11) + 2f64.powi(-30)),
+            1.0 + 2f64.powi(-10)
+        );
+        assert_eq!(half(2f64.powi(-25) * 1.5), 2f64.powi(-24));
+
+        let data = RawData::from_int(258, &WordSize::Four);
+        let little = data.to_bytes(&Endianness::Little);
+        assert_eq!(&little[..4], &[2, 1, 0, 0]);
+        assert_eq!(
+            RawData::from_bytes(&little[..4], &Endianness::Little).unwrap(),
+            data
+        );
     }
 }
@@ -1,9 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 use crate::util::error::SimulatorError;
 
 pub const DEFAULT_WORD_SIZE: WordSize = WordSize::Four;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WordSize {
+    Two,
     Four,
     Eight,
 }
@@ -11,13 +14,41 @@ pub enum WordSize {
 impl WordSize {
     pub fn value(&self) -> usize {
         match &self {
+            WordSize::Two => 2,
             WordSize::Four => 4,
             WordSize::Eight => 8,
         }
     }
 
+    pub fn bits(&self) -> u32 {
+        self.value() as u32 * 8
+    }
+
+    /// Checks whether an integer fits in a word, either as a signed or as an unsigned value.
+    pub fn can_hold(&self, value: i64) -> bool {
+        match self {
+            WordSize::Eight => true,
+            _ => {
+                let bits = self.bits();
+                value >= -(1i64 << (bits - 1)) && value < (1i64 << bits)
+            }
+        }
+    }
+
+    /// Checks whether the result of a signed operation fits in a word without overflowing.
+    pub fn fits_signed(&self, value: i128) -> bool {
+        let half = 1i128 << (self.bits() - 1);
+        value >= -half && value < half
+    }
+
+    /// Checks whether the result of an unsigned operation fits in a word without overflowing.
+    pub fn fits_unsigned(&self, value: u128) -> bool {
+        value >> self.bits() == 0
+    }
+
     pub fn from(size: usize) -> Result<WordSize, SimulatorError> {
         match size {
+            2 => Ok(WordSize::Two),
             4 => Ok(WordSize::Four),
             8 => Ok(WordSize::Eight),
             _ => Err(SimulatorError::InvalidWordSizeError(size)),
@@ -1,26 +1,51 @@
+use rezasm_core::instructions::instruction_policy::InstructionPolicy;
+use rezasm_core::instructions::reference::InstructionReference;
 use rezasm_core::parser::lexer;
+use rezasm_core::parser::linker::{self, Linker};
+use rezasm_core::simulation::call_stack::Backtrace;
+use rezasm_core::simulation::explanation::Explanation;
+use rezasm_core::simulation::memory;
+use rezasm_core::simulation::memory::Memory;
+use rezasm_core::simulation::memory_backend::MemoryBackendKind;
+use rezasm_core::simulation::reader::DummyReader;
 use rezasm_core::simulation::reader_cell::ReaderCell;
 use rezasm_core::simulation::registry;
 use rezasm_core::simulation::simulator::Simulator;
 
-use rezasm_core::simulation::writer::WriterBox;
+use rezasm_core::simulation::writer::{DummyWriter, WriterBox};
+use rezasm_core::util::endianness::{Endianness, DEFAULT_ENDIANNESS};
+use rezasm_core::util::error::SimulatorError;
+use rezasm_core::util::floating_point::RoundingMode;
+use rezasm_core::util::word_size::DEFAULT_WORD_SIZE;
 use std::string::ToString;
 use std::sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+/// The web simulator uses sparse memory so that resetting it does not reallocate the whole
+/// address space.
+fn new_web_simulator(reader: ReaderCell, writer: WriterBox) -> Simulator {
+    let memory = Memory::new_custom(
+        &DEFAULT_WORD_SIZE,
+        memory::DEFAULT_MEMORY_WORDS,
+        MemoryBackendKind::Sparse,
+        DEFAULT_ENDIANNESS,
+    );
+    Simulator::new_with_memory(memory, reader, writer)
+}
+
 fn internal_simulator(
     reader: Option<ReaderCell>,
     writer: Option<WriterBox>,
 ) -> &'static RwLock<Simulator> {
     static SIMULATOR: OnceLock<RwLock<Simulator>> = OnceLock::new();
     if reader.is_some() && writer.is_some() {
+        SIMULATOR.get_or_init(|| RwLock::new(new_web_simulator(reader.unwrap(), writer.unwrap())))
+    } else {
         SIMULATOR.get_or_init(|| {
-            RwLock::new(Simulator::new_custom_reader_writer(
-                reader.unwrap(),
-                writer.unwrap(),
+            RwLock::new(new_web_simulator(
+                ReaderCell::new(DummyReader::new()),
+                Box::new(DummyWriter::new()),
             ))
         })
-    } else {
-        SIMULATOR.get_or_init(|| RwLock::new(Simulator::new()))
     }
 }
 
@@ -47,48 +72,156 @@ pub fn reset() {
     get_simulator_mut().reset();
 }
 
+/// Loads a program which imports no other files, for where there are no files to import, such as
+/// in the browser.
 pub fn load(lines: &str) -> Result<(), String> {
     let mut simulator = get_simulator_mut();
 
-    for line_string in lines
-        .lines()
-        .map(|string| string.to_string())
-        .collect::<Vec<String>>()
+    let lines = lexer::parse_numbered_lines(
+        lines,
+        simulator.get_word_size(),
+        simulator.get_instruction_set(),
+    )
+    .map_err(|error| format!("Error parsing program: {}", error))?;
+    if let Some((line_number, _)) = lines
+        .iter()
+        .find(|(_, line)| linker::get_import(line).is_some())
     {
-        let line_parse = lexer::parse_line(&line_string.to_string(), simulator.get_word_size());
-
-        match line_parse {
-            None => { /* no-op */ }
-            Some(x) => match x {
-                Ok(line) => match simulator.add_line(line, "".into()) {
-                    Ok(_) => {}
-                    Err(error) => return Err(format!("Error parsing program: {}", error)),
-                },
-                Err(error) => return Err(format!("Error parsing program: {}", error)),
-            },
-        };
+        return Err(format!(
+            "Error parsing program: imports are not supported here, but line {} imports a file",
+            line_number
+        ));
     }
-    Ok(())
+    simulator
+        .add_numbered_lines(lines, "".into())
+        .map_err(|error| format!("Error parsing program: {}", error))
+}
+
+/// Loads a program along with the files it imports, which are found relative to `path`, the path
+/// of the program.
+pub fn load_linked(path: &str, lines: &str) -> Result<(), String> {
+    let mut simulator = get_simulator_mut();
+
+    let files = Linker::new()
+        .link_code(
+            path,
+            lines,
+            simulator.get_word_size(),
+            simulator.get_instruction_set(),
+        )
+        .map_err(|error| format!("Error parsing program: {}", error))?;
+    simulator
+        .add_files(files)
+        .map_err(|error| format!("Error parsing program: {}", error))
 }
 
 pub fn step() -> Result<(), String> {
-    match get_simulator_mut().run_line_from_pc() {
+    let mut simulator = get_simulator_mut();
+    match simulator.run_line_from_pc() {
         Ok(_) => {}
-        Err(error) => return Err(format!("Program error: {}", error)),
+        Err(error) => return Err(program_error(&simulator, error)),
     };
 
-    let simulator = get_simulator();
-
     if simulator.is_error() {
         Err(format!(
-            "Invalid PC: {}",
-            simulator.get_registers().get_pc().get_data().int_value()
+            "Invalid PC: {}\n{}",
+            simulator.get_registers().get_pc().get_data().int_value(),
+            simulator.backtrace()
         ))
     } else {
         Ok(())
     }
 }
 
+/// Runs up to `max_steps` lines at once, returning how many were run.
+pub fn run_steps(max_steps: usize) -> Result<usize, String> {
+    run_lines(|simulator| simulator.run(max_steps))
+}
+
+/// Runs the line at the program counter and, if it is a `call`, the function it calls through to
+/// its `return`, running at most `max_steps` lines and returning how many were run. They are undone
+/// together by a single `step_back`.
+pub fn step_over(max_steps: usize) -> Result<usize, String> {
+    run_lines(|simulator| simulator.step_over(max_steps))
+}
+
+/// Runs until the current function returns, running at most `max_steps` lines and returning how
+/// many were run. They are undone together by a single `step_back`.
+pub fn step_out(max_steps: usize) -> Result<usize, String> {
+    run_lines(|simulator| simulator.step_out(max_steps))
+}
+
+fn run_lines(
+    run: impl FnOnce(&mut Simulator) -> Result<usize, SimulatorError>,
+) -> Result<usize, String> {
+    let mut simulator = get_simulator_mut();
+    let steps = match run(&mut simulator) {
+        Ok(steps) => steps,
+        Err(error) => return Err(program_error(&simulator, error)),
+    };
+
+    if simulator.is_error() {
+        Err(format!(
+            "Invalid PC: {}\n{}",
+            simulator.get_registers().get_pc().get_data().int_value(),
+            simulator.backtrace()
+        ))
+    } else {
+        Ok(steps)
+    }
+}
+
+/// Describes an error the program ran into, along with the functions which were running.
+fn program_error(simulator: &Simulator, error: SimulatorError) -> String {
+    format!("Program error: {}\n{}", error, simulator.backtrace())
+}
+
+/// Gets the functions which are running, from innermost to outermost, for the call stack panel.
+pub fn get_backtrace() -> Backtrace {
+    get_simulator().backtrace()
+}
+
+/// Sets whether steps can be undone; disabling undo makes running considerably faster.
+pub fn set_undo_enabled(enabled: bool) {
+    get_simulator_mut().set_can_undo(enabled);
+}
+
+/// Sets whether integer arithmetic which overflows a word fails instead of wrapping around.
+pub fn set_trap_overflow(enabled: bool) {
+    get_simulator_mut().set_trap_overflow(enabled);
+}
+
+/// Sets whether returning from a function fails unless it restored the saved registers and `$sp`.
+pub fn set_check_calling_convention(enabled: bool) {
+    get_simulator_mut().set_check_calling_convention(enabled);
+}
+
+/// Sets whether each line which is run is explained in words, for the explanation panel.
+pub fn set_explain(enabled: bool) {
+    get_simulator_mut().set_explain(enabled);
+}
+
+/// Gets the explanation of the most recently run line, if explanations are enabled.
+pub fn get_explanation() -> Option<Explanation> {
+    get_simulator().get_explanation().cloned()
+}
+
+/// Sets how floating point results are rounded, by the name of the rounding mode, e.g. `up`.
+pub fn set_rounding_mode(mode: &str) -> Result<(), String> {
+    let mode = RoundingMode::from(mode).map_err(|error| format!("{}", error))?;
+    get_simulator_mut().set_rounding_mode(mode);
+    Ok(())
+}
+
+/// Restricts the instructions which programs loaded afterwards may use, given the policy as JSON.
+pub fn set_instruction_policy(policy: &str) -> Result<(), String> {
+    let policy = InstructionPolicy::from_json(policy).map_err(|error| format!("{}", error))?;
+    get_simulator_mut()
+        .get_instruction_set_mut()
+        .set_policy(policy)
+        .map_err(|error| format!("{}", error))
+}
+
 pub fn step_back() -> Result<(), String> {
     match get_simulator_mut().undo_last_transformation() {
         Ok(_) => Ok(()),
@@ -153,8 +286,9 @@ pub fn get_memory_slice(address: usize, length: usize) -> Result<Vec<i64>, Strin
     let mut result = Vec::new();
     let simulator = get_simulator();
     let memory = simulator.get_memory();
+    let word_size = memory.word_size().value();
     for offset in 0..length {
-        match memory.read(address + offset * 4) {
+        match memory.read(address + offset * word_size) {
             Ok(value) => result.push(value.int_value()),
             Err(error) => return Err(format!("{}", error)),
         }
@@ -162,6 +296,47 @@ pub fn get_memory_slice(address: usize, length: usize) -> Result<Vec<i64>, Strin
     Ok(result)
 }
 
+/// Gets `length` bytes of memory exactly as they are laid out, which depends on the endianness.
+pub fn get_memory_bytes(address: usize, length: usize) -> Result<Vec<u8>, String> {
+    get_simulator()
+        .get_memory()
+        .read_raw(address, length)
+        .map_err(|error| format!("{}", error))
+}
+
 pub fn get_word_size() -> usize {
     get_simulator().get_word_size().value()
 }
+
+pub fn get_endianness() -> String {
+    match get_simulator().get_endianness() {
+        Endianness::Big => "big".to_string(),
+        Endianness::Little => "little".to_string(),
+    }
+}
+
+/// Gets the references of the instructions which an editor can complete `prefix` to.
+pub fn get_instruction_completions(prefix: &str) -> Vec<InstructionReference> {
+    get_simulator()
+        .get_instruction_set()
+        .get_references()
+        .into_iter()
+        .filter(|reference| reference.name.starts_with(prefix))
+        .collect()
+}
+
+/// Gets the Markdown an editor shows when hovering over an instruction name.
+pub fn get_instruction_hover(name: &str) -> Option<String> {
+    let references = get_simulator().get_instruction_set().find_references(name);
+    if references.is_empty() {
+        return None;
+    }
+    Some(
+        references
+            .iter()
+            .map(|reference| reference.to_markdown())
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+    )
+}